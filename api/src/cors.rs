@@ -0,0 +1,102 @@
+// Copyright 2020 The Grin Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::router::{Handler, HandlerObj, ResponseFuture};
+use crate::web::response;
+use futures::future::ok;
+use hyper::header::{
+	HeaderValue, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS,
+	ACCESS_CONTROL_ALLOW_ORIGIN, ORIGIN, VARY,
+};
+use hyper::{Body, HeaderMap, Method, Request, Response, StatusCode};
+
+const CORS_ALLOWED_METHODS: &str = "GET, POST, HEAD, OPTIONS";
+const CORS_ALLOWED_HEADERS: &str = "Content-Type, Authorization";
+
+// CORS Middleware, lets browser based clients (block explorers) query the API
+pub struct CorsMiddleware {
+	allowed_origins: Vec<String>,
+}
+
+impl CorsMiddleware {
+	/// A "*" entry allows any origin, otherwise the request Origin must be
+	/// in the list for the CORS headers to be added.
+	pub fn new(allowed_origins: Vec<String>) -> CorsMiddleware {
+		CorsMiddleware { allowed_origins }
+	}
+
+	fn allowed_origin(&self, req: &Request<Body>) -> Option<HeaderValue> {
+		if self.allowed_origins.iter().any(|o| o == "*") {
+			return Some(HeaderValue::from_static("*"));
+		}
+		let origin = req.headers().get(ORIGIN)?;
+		let origin_str = origin.to_str().ok()?;
+		if self.allowed_origins.iter().any(|o| o == origin_str) {
+			Some(origin.clone())
+		} else {
+			None
+		}
+	}
+}
+
+fn add_cors_headers(headers: &mut HeaderMap, origin: HeaderValue) {
+	if origin != "*" {
+		headers.insert(VARY, HeaderValue::from_static("Origin"));
+	}
+	headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+	headers.insert(
+		ACCESS_CONTROL_ALLOW_METHODS,
+		HeaderValue::from_static(CORS_ALLOWED_METHODS),
+	);
+	headers.insert(
+		ACCESS_CONTROL_ALLOW_HEADERS,
+		HeaderValue::from_static(CORS_ALLOWED_HEADERS),
+	);
+}
+
+impl Handler for CorsMiddleware {
+	fn call(
+		&self,
+		req: Request<Body>,
+		mut handlers: Box<dyn Iterator<Item = HandlerObj>>,
+	) -> ResponseFuture {
+		let origin = self.allowed_origin(&req);
+
+		// Answer the preflight requests here, no need to bother the handlers
+		if req.method() == Method::OPTIONS {
+			let mut resp = Response::builder()
+				.status(StatusCode::NO_CONTENT)
+				.body(Body::empty())
+				.unwrap();
+			if let Some(origin) = origin {
+				add_cors_headers(resp.headers_mut(), origin);
+			}
+			return Box::pin(ok(resp));
+		}
+
+		let next_handler = match handlers.next() {
+			Some(h) => h,
+			None => return response(StatusCode::INTERNAL_SERVER_ERROR, "no handler found"),
+		};
+		let fut = next_handler.call(req, handlers);
+		match origin {
+			Some(origin) => Box::pin(async move {
+				let mut resp = fut.await?;
+				add_cors_headers(resp.headers_mut(), origin);
+				Ok(resp)
+			}),
+			None => fut,
+		}
+	}
+}
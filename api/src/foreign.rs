@@ -292,6 +292,7 @@ where
 	) -> Result<OutputListing, Error> {
 		let txhashset_handler = TxHashSetHandler {
 			chain: self.chain.clone(),
+			allow_download: false,
 		};
 		txhashset_handler.block_height_range_to_pmmr_indices(start_block_height, end_block_height)
 	}
@@ -18,6 +18,7 @@ use crate::core::core::hash::Hash;
 use crate::core::core::transaction::Transaction;
 use crate::core::core::verifier_cache::VerifierCache;
 use crate::foreign::Foreign;
+use crate::handlers::utils::parse_hash;
 use crate::pool::PoolEntry;
 use crate::pool::{BlockChain, PoolAdapter};
 use crate::rest::ErrorKind;
@@ -25,7 +26,7 @@ use crate::types::{
 	BlockHeaderPrintable, BlockPrintable, LocatedTxKernel, OutputListing, OutputPrintable, Tip,
 	Version,
 };
-use crate::{util, Libp2pMessages, Libp2pPeers};
+use crate::{Libp2pMessages, Libp2pPeers};
 
 /// Public definition used to generate Node jsonrpc api.
 /// * When running `grin` with defaults, the V2 api is available at
@@ -917,9 +918,7 @@ where
 	) -> Result<BlockHeaderPrintable, ErrorKind> {
 		let mut parsed_hash: Option<Hash> = None;
 		if let Some(hash) = hash {
-			let vec = util::from_hex(&hash)
-				.map_err(|e| ErrorKind::Argument(format!("invalid block hash: {}", e)))?;
-			parsed_hash = Some(Hash::from_vec(&vec));
+			parsed_hash = Some(parse_hash(&hash).map_err(|e| e.kind().clone())?);
 		}
 		Foreign::get_header(self, height, parsed_hash, commit).map_err(|e| e.kind().clone())
 	}
@@ -942,9 +941,7 @@ where
 	) -> Result<BlockPrintable, ErrorKind> {
 		let mut parsed_hash: Option<Hash> = None;
 		if let Some(hash) = hash {
-			let vec = util::from_hex(&hash)
-				.map_err(|e| ErrorKind::Argument(format!("invalid block hash: {}", e)))?;
-			parsed_hash = Some(Hash::from_vec(&vec));
+			parsed_hash = Some(parse_hash(&hash).map_err(|e| e.kind().clone())?);
 		}
 		Foreign::get_block(
 			self,
@@ -12,8 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod admin_api;
 pub mod blocks_api;
 pub mod chain_api;
+pub mod events_api;
+pub mod mining_api;
 pub mod peers_api;
 pub mod pool_api;
 pub mod server_api;
@@ -21,16 +24,35 @@ pub mod transactions_api;
 pub mod utils;
 pub mod version_api;
 
+use self::admin_api::{
+	AuditBalanceHandler, RebuildOutputIndexHandler, ReindexSpentHandler, ValidateSegmentHandler,
+};
 use self::blocks_api::BlockHandler;
+use self::blocks_api::BlockRelayCountHandler;
 use self::blocks_api::HeaderHandler;
+use self::chain_api::BlockSizeStatsHandler;
+use self::chain_api::ChainAuditHandler;
 use self::chain_api::ChainCompactHandler;
 use self::chain_api::ChainHandler;
+use self::chain_api::ChainOrphansHandler;
+use self::chain_api::ChainStatusHandler;
 use self::chain_api::ChainValidationHandler;
+use self::chain_api::DifficultyHistoryHandler;
 use self::chain_api::KernelHandler;
+use self::chain_api::KernelHeightHandler;
+use self::chain_api::LargestBlocksHandler;
 use self::chain_api::OutputHandler;
+use self::chain_api::PropagationStatsHandler;
+use self::chain_api::VersionDistributionHandler;
+use self::events_api::{EventStream, EventsHandler};
+use self::mining_api::NextDifficultyHandler;
+use self::mining_api::SimulateBlockHandler;
 use self::peers_api::PeerHandler;
 use self::peers_api::PeersAllHandler;
 use self::peers_api::PeersConnectedHandler;
+use self::peers_api::PeersPingHandler;
+use self::peers_api::PeersSyncStatusHandler;
+use self::pool_api::PoolFeeHistogramHandler;
 use self::pool_api::PoolInfoHandler;
 use self::pool_api::PoolPushHandler;
 use self::server_api::IndexHandler;
@@ -45,6 +67,7 @@ use crate::chain::{Chain, SyncState};
 use crate::core::core::verifier_cache::VerifierCache;
 use crate::core::global;
 use crate::core::stratum;
+use crate::cors::CorsMiddleware;
 use crate::foreign::Foreign;
 use crate::foreign_rpc::ForeignRpc;
 use crate::owner::Owner;
@@ -52,11 +75,12 @@ use crate::owner_rpc::OwnerRpc;
 use crate::p2p;
 use crate::pool;
 use crate::pool::{BlockChain, PoolAdapter};
-use crate::rest::{ApiServer, Error, ErrorKind, TLSConfig};
+use crate::rest::{ApiServer, Error, ErrorKind, TLSConfig, TimeoutMiddleware};
 use crate::router::ResponseFuture;
-use crate::router::{Router, RouterError};
+use crate::router::{Node, Router, RouterError};
 use crate::stratum::Stratum;
 use crate::stratum_rpc::StratumRpc;
+use crate::types::{AdminConfig, ApiConfig};
 use crate::util::to_base64;
 use crate::util::RwLock;
 use crate::util::StopState;
@@ -68,6 +92,7 @@ use serde::Serialize;
 use std::net::SocketAddr;
 use std::sync::{Arc, Weak};
 use std::thread;
+use std::time::Duration;
 
 /// Listener version, providing same API but listening for requests on a
 /// port and wrapping the calls
@@ -77,10 +102,13 @@ pub fn node_apis<B, P, V>(
 	tx_pool: Arc<RwLock<pool::TransactionPool<B, P, V>>>,
 	peers: Arc<p2p::Peers>,
 	sync_state: Arc<chain::SyncState>,
+	events: Arc<EventStream>,
 	api_secret: Option<String>,
 	foreign_api_secret: Option<String>,
 	tls_config: Option<TLSConfig>,
 	allow_to_stop: bool,
+	api_config: ApiConfig,
+	admin_config: AdminConfig,
 	stratum_ip_pool: Arc<stratum::connections::StratumIpPool>,
 	api_chan: &'static mut (oneshot::Sender<()>, oneshot::Receiver<()>),
 	stop_state: Arc<StopState>,
@@ -97,10 +125,20 @@ where
 		tx_pool.clone(),
 		peers.clone(),
 		sync_state.clone(),
+		events,
 		allow_to_stop,
+		admin_config,
+		api_config.request_timeout_secs,
 	)
 	.expect("unable to build API router");
 
+	// CORS goes first so the preflight requests are answered before the auth check
+	if !api_config.cors_allowed_origins.is_empty() {
+		router.add_middleware(Arc::new(CorsMiddleware::new(
+			api_config.cors_allowed_origins,
+		)));
+	}
+
 	let basic_auth_key = if global::is_mainnet() {
 		"mwcmain"
 	} else if global::is_floonet() {
@@ -430,7 +468,10 @@ pub fn build_router<B, P, V>(
 	tx_pool: Arc<RwLock<pool::TransactionPool<B, P, V>>>,
 	peers: Arc<p2p::Peers>,
 	sync_state: Arc<chain::SyncState>,
+	events: Arc<EventStream>,
 	allow_to_stop: bool,
+	admin_config: AdminConfig,
+	request_timeout_secs: u64,
 ) -> Result<Router, RouterError>
 where
 	B: BlockChain + 'static,
@@ -439,13 +480,30 @@ where
 {
 	let route_list = vec![
 		"get blocks".to_string(),
+		"get blocks?start_height=N&end_height=M&compact=true".to_string(),
+		"get blocks/xxx/relay_count".to_string(),
+		"get blocks/byoutput/xxx?include_spent=true".to_string(),
+		"get blocks/since/xxx?max_blocks=N".to_string(),
 		"get headers".to_string(),
+		"get headers?min_difficulty=N&max_difficulty=M".to_string(),
 		"get chain".to_string(),
 		"post chain/compact".to_string(),
 		"get chain/validate".to_string(),
+		"get chain/audit".to_string(),
+		"get chain/status".to_string(),
+		"get chain/difficulty?from_height=N&to_height=M".to_string(),
+		"get chain/block_size_stats".to_string(),
+		"get chain/largest_blocks?count=N".to_string(),
+		"get chain/propagation_stats".to_string(),
+		"get chain/orphans".to_string(),
+		"get chain/version_distribution".to_string(),
 		"get chain/kernels/xxx?min_height=yyy&max_height=zzz".to_string(),
+		"get kernels/xxx/height".to_string(),
 		"get chain/outputs/byids?id=xxx,yyy,zzz".to_string(),
 		"get chain/outputs/byheight?start_height=101&end_height=200".to_string(),
+		"get chain/outputs/unspent?start_index=1&max=100".to_string(),
+		"get chain/outputs/merkle_proof/xxx".to_string(),
+		"get outputs/bypos/xxx".to_string(),
 		"get status".to_string(),
 		"get txhashset/roots".to_string(),
 		"get txhashset/lastoutputs?n=10".to_string(),
@@ -453,29 +511,63 @@ where
 		"get txhashset/lastkernels".to_string(),
 		"get txhashset/outputs?start_index=1&max=100".to_string(),
 		"get txhashset/merkleproof?n=1".to_string(),
+		"get txhashset/txhashset.zip".to_string(),
+		"get txhashset/download".to_string(),
+		"get mining/next_difficulty".to_string(),
+		"post mining/simulate".to_string(),
 		"get pool".to_string(),
+		"get pool/fee_histogram".to_string(),
 		"post pool/push_tx".to_string(),
 		"post peers/a.b.c.d:p/ban".to_string(),
 		"post peers/a.b.c.d:p/unban".to_string(),
 		"get peers/all".to_string(),
 		"get peers/connected".to_string(),
+		"get peers/ping?timeout_ms=xxx".to_string(),
+		"get peers/sync_status".to_string(),
 		"get peers/a.b.c.d".to_string(),
 		"get version".to_string(),
+		"get events".to_string(),
+		"post admin/reindex_spent".to_string(),
+		"post admin/rebuild_output_index".to_string(),
+		"post admin/validate_segment?start=1&end=100".to_string(),
+		"post admin/audit_balance".to_string(),
 	];
 	let index_handler = IndexHandler { list: route_list };
 
 	let output_handler = OutputHandler {
 		chain: Arc::downgrade(&chain),
 	};
+	let output_by_pos_handler = OutputHandler {
+		chain: Arc::downgrade(&chain),
+	};
 	let kernel_handler = KernelHandler {
 		chain: Arc::downgrade(&chain),
 	};
+	let kernel_height_handler = KernelHeightHandler {
+		chain: Arc::downgrade(&chain),
+	};
 	let block_handler = BlockHandler {
 		chain: Arc::downgrade(&chain),
 	};
+	let blocks_range_handler = BlockHandler {
+		chain: Arc::downgrade(&chain),
+	};
+	let block_by_output_handler = BlockHandler {
+		chain: Arc::downgrade(&chain),
+	};
+	let blocks_since_checkpoint_handler = BlockHandler {
+		chain: Arc::downgrade(&chain),
+	};
+	let block_relay_count_handler = BlockRelayCountHandler {
+		chain: Arc::downgrade(&chain),
+		peers: Arc::downgrade(&peers),
+	};
 	let header_handler = HeaderHandler {
 		chain: Arc::downgrade(&chain),
 	};
+	let headers_by_difficulty_handler = HeaderHandler {
+		chain: Arc::downgrade(&chain),
+	};
 	let chain_tip_handler = ChainHandler {
 		chain: Arc::downgrade(&chain),
 	};
@@ -485,18 +577,57 @@ where
 	let chain_validation_handler = ChainValidationHandler {
 		chain: Arc::downgrade(&chain),
 	};
+	let difficulty_history_handler = DifficultyHistoryHandler {
+		chain: Arc::downgrade(&chain),
+		cache: RwLock::new(None),
+	};
+	let chain_status_handler = ChainStatusHandler {
+		chain: Arc::downgrade(&chain),
+	};
+	let chain_audit_handler = ChainAuditHandler {
+		chain: Arc::downgrade(&chain),
+		allow_expensive_queries: admin_config.allow_expensive_queries,
+	};
+	let block_size_stats_handler = BlockSizeStatsHandler {
+		chain: Arc::downgrade(&chain),
+	};
+	let largest_blocks_handler = LargestBlocksHandler {
+		chain: Arc::downgrade(&chain),
+	};
+	let propagation_stats_handler = PropagationStatsHandler {
+		chain: Arc::downgrade(&chain),
+	};
+	let chain_orphans_handler = ChainOrphansHandler {
+		chain: Arc::downgrade(&chain),
+	};
+	let version_distribution_handler = VersionDistributionHandler {
+		chain: Arc::downgrade(&chain),
+	};
+	let verifier_cache: Arc<RwLock<dyn VerifierCache>> = tx_pool.read().verifier_cache.clone();
 	let status_handler = StatusHandler {
 		chain: Arc::downgrade(&chain),
 		peers: Arc::downgrade(&peers),
 		sync_state: Arc::downgrade(&sync_state),
+		verifier_cache: Some(Arc::downgrade(&verifier_cache)),
 		allow_to_stop,
 	};
 	let txhashset_handler = TxHashSetHandler {
 		chain: Arc::downgrade(&chain),
+		allow_download: admin_config.allow_txhashset_download,
+	};
+	let simulate_block_handler = SimulateBlockHandler {
+		chain: Arc::downgrade(&chain),
+		allow_expensive_queries: admin_config.allow_expensive_queries,
+	};
+	let next_difficulty_handler = NextDifficultyHandler {
+		chain: Arc::downgrade(&chain),
 	};
 	let pool_info_handler = PoolInfoHandler {
 		tx_pool: Arc::downgrade(&tx_pool),
 	};
+	let pool_fee_histogram_handler = PoolFeeHistogramHandler {
+		tx_pool: Arc::downgrade(&tx_pool),
+	};
 	let pool_push_handler = PoolPushHandler {
 		tx_pool: Arc::downgrade(&tx_pool),
 	};
@@ -506,30 +637,120 @@ where
 	let peers_connected_handler = PeersConnectedHandler {
 		peers: Arc::downgrade(&peers),
 	};
+	let peers_ping_handler = PeersPingHandler {
+		peers: Arc::downgrade(&peers),
+	};
+	let peers_sync_status_handler = PeersSyncStatusHandler {
+		chain: Arc::downgrade(&chain),
+		peers: Arc::downgrade(&peers),
+	};
 	let peer_handler = PeerHandler {
 		peers: Arc::downgrade(&peers),
 	};
 	let version_handler = VersionHandler {
 		chain: Arc::downgrade(&chain),
 	};
+	let events_handler = EventsHandler {
+		events: Arc::downgrade(&events),
+	};
+	let reindex_spent_handler = ReindexSpentHandler {
+		chain: Arc::downgrade(&chain),
+		allow_reindex: admin_config.allow_reindex,
+	};
+	let rebuild_output_index_handler = RebuildOutputIndexHandler {
+		chain: Arc::downgrade(&chain),
+		allow_reindex: admin_config.allow_reindex,
+	};
+	let validate_segment_handler = ValidateSegmentHandler {
+		chain: Arc::downgrade(&chain),
+		allow_reindex: admin_config.allow_reindex,
+	};
+	let audit_balance_handler = AuditBalanceHandler {
+		chain: Arc::downgrade(&chain),
+		allow_reindex: admin_config.allow_reindex,
+	};
 
 	let mut router = Router::new();
 
-	router.add_route("/v1/", Arc::new(index_handler))?;
-	router.add_route("/v1/blocks/*", Arc::new(block_handler))?;
-	router.add_route("/v1/headers/*", Arc::new(header_handler))?;
-	router.add_route("/v1/chain", Arc::new(chain_tip_handler))?;
-	router.add_route("/v1/chain/outputs/*", Arc::new(output_handler))?;
-	router.add_route("/v1/chain/kernels/*", Arc::new(kernel_handler))?;
+	// Read-only public routes answer within the configured timeout, the others
+	// (long running admin calls, the txhashset download, the event stream...)
+	// are left alone.
+	let read_only = |node: &mut Node| {
+		if request_timeout_secs > 0 {
+			node.add_middleware(Arc::new(TimeoutMiddleware::new(Duration::from_secs(
+				request_timeout_secs,
+			))));
+		}
+	};
+
+	read_only(router.add_route("/v1/", Arc::new(index_handler))?);
+	read_only(router.add_route("/v1/blocks", Arc::new(blocks_range_handler))?);
+	read_only(router.add_route("/v1/blocks/*", Arc::new(block_handler))?);
+	read_only(router.add_route(
+		"/v1/blocks/*/relay_count",
+		Arc::new(block_relay_count_handler),
+	)?);
+	read_only(router.add_route("/v1/blocks/byoutput/*", Arc::new(block_by_output_handler))?);
+	read_only(router.add_route(
+		"/v1/blocks/since/*",
+		Arc::new(blocks_since_checkpoint_handler),
+	)?);
+	read_only(router.add_route("/v1/headers", Arc::new(headers_by_difficulty_handler))?);
+	read_only(router.add_route("/v1/headers/*", Arc::new(header_handler))?);
+	read_only(router.add_route("/v1/chain", Arc::new(chain_tip_handler))?);
+	// also serves /v1/chain/outputs/merkle_proof/xxx
+	read_only(router.add_route("/v1/chain/outputs/**", Arc::new(output_handler))?);
+	read_only(router.add_route("/v1/outputs/bypos/*", Arc::new(output_by_pos_handler))?);
+	read_only(router.add_route("/v1/chain/kernels/*", Arc::new(kernel_handler))?);
+	read_only(router.add_route("/v1/kernels/**", Arc::new(kernel_height_handler))?);
 	router.add_route("/v1/chain/compact", Arc::new(chain_compact_handler))?;
 	router.add_route("/v1/chain/validate", Arc::new(chain_validation_handler))?;
+	router.add_route("/v1/chain/audit", Arc::new(chain_audit_handler))?;
+	read_only(router.add_route("/v1/chain/status", Arc::new(chain_status_handler))?);
+	read_only(router.add_route("/v1/chain/difficulty", Arc::new(difficulty_history_handler))?);
+	read_only(router.add_route(
+		"/v1/chain/block_size_stats",
+		Arc::new(block_size_stats_handler),
+	)?);
+	read_only(router.add_route("/v1/chain/largest_blocks", Arc::new(largest_blocks_handler))?);
+	read_only(router.add_route(
+		"/v1/chain/propagation_stats",
+		Arc::new(propagation_stats_handler),
+	)?);
+	read_only(router.add_route("/v1/chain/orphans", Arc::new(chain_orphans_handler))?);
+	read_only(router.add_route(
+		"/v1/chain/version_distribution",
+		Arc::new(version_distribution_handler),
+	)?);
 	router.add_route("/v1/txhashset/*", Arc::new(txhashset_handler))?;
 	router.add_route("/v1/status", Arc::new(status_handler))?;
-	router.add_route("/v1/pool", Arc::new(pool_info_handler))?;
+	read_only(router.add_route(
+		"/v1/mining/next_difficulty",
+		Arc::new(next_difficulty_handler),
+	)?);
+	router.add_route("/v1/mining/simulate", Arc::new(simulate_block_handler))?;
+	read_only(router.add_route("/v1/pool", Arc::new(pool_info_handler))?);
+	read_only(router.add_route(
+		"/v1/pool/fee_histogram",
+		Arc::new(pool_fee_histogram_handler),
+	)?);
 	router.add_route("/v1/pool/push_tx", Arc::new(pool_push_handler))?;
-	router.add_route("/v1/peers/all", Arc::new(peers_all_handler))?;
-	router.add_route("/v1/peers/connected", Arc::new(peers_connected_handler))?;
+	read_only(router.add_route("/v1/peers/all", Arc::new(peers_all_handler))?);
+	read_only(router.add_route("/v1/peers/connected", Arc::new(peers_connected_handler))?);
+	router.add_route("/v1/peers/ping", Arc::new(peers_ping_handler))?;
+	read_only(router.add_route("/v1/peers/sync_status", Arc::new(peers_sync_status_handler))?);
 	router.add_route("/v1/peers/**", Arc::new(peer_handler))?;
-	router.add_route("/v1/version", Arc::new(version_handler))?;
+	read_only(router.add_route("/v1/version", Arc::new(version_handler))?);
+	router.add_route("/v1/events", Arc::new(events_handler))?;
+	router.add_route("/v1/admin/reindex_spent", Arc::new(reindex_spent_handler))?;
+	router.add_route(
+		"/v1/admin/rebuild_output_index",
+		Arc::new(rebuild_output_index_handler),
+	)?;
+	router.add_route(
+		"/v1/admin/validate_segment",
+		Arc::new(validate_segment_handler),
+	)?;
+	router.add_route("/v1/admin/audit_balance", Arc::new(audit_balance_handler))?;
 	Ok(router)
 }
@@ -0,0 +1,142 @@
+// Copyright 2020 The Grin Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// The admin handlers rebuild or scan whole indexes, they're all off unless
+// `allow_reindex = true` is set in the admin config (see require_admin_flag).
+
+use super::utils::{require_admin_flag, w};
+use crate::chain;
+use crate::core::core::hash::Hashed;
+use crate::rest::*;
+use crate::router::{Handler, ResponseFuture};
+use crate::types::BalanceAudit;
+use crate::util::ToHex;
+use crate::web::*;
+use hyper::{Body, Request};
+use std::sync::Weak;
+
+/// Rebuild the spent output index from the full blocks in the db.
+/// POST /v1/admin/reindex_spent
+pub struct ReindexSpentHandler {
+	pub chain: Weak<chain::Chain>,
+	pub allow_reindex: bool,
+}
+
+impl ReindexSpentHandler {
+	pub fn reindex_spent(&self) -> Result<u64, Error> {
+		w(&self.chain)?
+			.reindex_spent_outputs()
+			.map_err(|e| ErrorKind::Internal(format!("reindex spent outputs error, {}", e)).into())
+	}
+}
+
+impl Handler for ReindexSpentHandler {
+	fn post(&self, _req: Request<Body>) -> ResponseFuture {
+		if let Some(resp) = require_admin_flag(self.allow_reindex, "allow_reindex") {
+			return resp;
+		}
+		result_to_response(self.reindex_spent())
+	}
+}
+
+/// Rebuild the output index (commitment -> MMR position) from scratch.
+/// POST /v1/admin/rebuild_output_index
+pub struct RebuildOutputIndexHandler {
+	pub chain: Weak<chain::Chain>,
+	pub allow_reindex: bool,
+}
+
+impl RebuildOutputIndexHandler {
+	pub fn rebuild_output_index(&self) -> Result<u64, Error> {
+		w(&self.chain)?
+			.rebuild_output_index()
+			.map_err(|e| ErrorKind::Internal(format!("rebuild output index error, {}", e)).into())
+	}
+}
+
+impl Handler for RebuildOutputIndexHandler {
+	fn post(&self, _req: Request<Body>) -> ResponseFuture {
+		if let Some(resp) = require_admin_flag(self.allow_reindex, "allow_reindex") {
+			return resp;
+		}
+		result_to_response(self.rebuild_output_index())
+	}
+}
+
+/// Fully validate the blocks of the chain in the height range [start, end].
+/// Returns the number of blocks validated, or the height of the first invalid block.
+/// POST /v1/admin/validate_segment?start=N&end=M
+pub struct ValidateSegmentHandler {
+	pub chain: Weak<chain::Chain>,
+	pub allow_reindex: bool,
+}
+
+impl ValidateSegmentHandler {
+	pub fn validate_segment(&self, req: &Request<Body>) -> Result<u64, Error> {
+		let params = QueryParams::from(req.uri().query());
+		let start = parse_param!(params, "start", 0);
+		let end = parse_param!(params, "end", 0);
+		w(&self.chain)?
+			.validate_chain_segment(start, end)
+			.map_err(|e| match e.kind() {
+				chain::ErrorKind::Other(msg) => ErrorKind::Argument(msg).into(),
+				_ => ErrorKind::Internal(format!("validate segment error, {}", e)).into(),
+			})
+	}
+}
+
+impl Handler for ValidateSegmentHandler {
+	fn post(&self, req: Request<Body>) -> ResponseFuture {
+		if let Some(resp) = require_admin_flag(self.allow_reindex, "allow_reindex") {
+			return resp;
+		}
+		result_to_response(self.validate_segment(&req))
+	}
+}
+
+/// Global balance audit. Sums the unspent output commitments and the kernel
+/// excesses and checks they match, accounting for the coinbase overage and
+/// the total kernel offset.
+/// POST /v1/admin/audit_balance
+pub struct AuditBalanceHandler {
+	pub chain: Weak<chain::Chain>,
+	pub allow_reindex: bool,
+}
+
+impl AuditBalanceHandler {
+	pub fn audit_balance(&self) -> Result<BalanceAudit, Error> {
+		let chain = w(&self.chain)?;
+		let (header, sums) = chain
+			.audit_balance()
+			.map_err(|e| ErrorKind::Internal(format!("balance audit error, {}", e)))?;
+		let genesis = chain.get_header_by_height(0)?;
+		Ok(BalanceAudit {
+			height: header.height,
+			hash: header.hash().to_hex(),
+			utxo_commitment_sum: sums.map(|(utxo_sum, _)| utxo_sum.to_hex()),
+			kernel_excess_sum: sums.map(|(_, kernel_sum)| kernel_sum.to_hex()),
+			overage: header.total_overage(genesis.kernel_mmr_size > 0),
+			matches: sums.is_some(),
+		})
+	}
+}
+
+impl Handler for AuditBalanceHandler {
+	fn post(&self, _req: Request<Body>) -> ResponseFuture {
+		if let Some(resp) = require_admin_flag(self.allow_reindex, "allow_reindex") {
+			return resp;
+		}
+		result_to_response(self.audit_balance())
+	}
+}
@@ -11,23 +11,47 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
-use super::utils::{get_output, get_output_v2, w};
+use super::utils::{get_output, get_output_v2, parse_hash, require_archival, w};
 use crate::chain;
+use crate::core::consensus;
 use crate::core::core::hash::Hash;
 use crate::core::core::hash::Hashed;
+use crate::core::core::{BlockHeader, CompactBlock};
+use crate::core::ser::{self, ProtocolVersion};
+use crate::p2p;
 use crate::rest::*;
 use crate::router::{Handler, ResponseFuture};
 use crate::types::*;
 use crate::util;
+use crate::util::secp::pedersen::Commitment;
 use crate::web::*;
+use hyper::header::{HeaderName, HeaderValue, CONTENT_TYPE};
 use hyper::{Body, Request, StatusCode};
-use regex::Regex;
 use std::sync::Weak;
 
+/// Blocks larger than this (serialized, in bytes) are returned as JSON serialized
+/// directly from the block rather than through BlockPrintable.
+const LARGE_BLOCK_JSON_THRESHOLD: usize = 1_000_000;
+
+/// Maximum number of blocks returned by a single /v1/blocks range request.
+const MAX_BLOCKS_RANGE: u64 = 1000;
+
+/// Maximum number of headers returned by a single /v1/headers time range request.
+const MAX_HEADERS_TIMERANGE: usize = 1000;
+
 /// Gets block headers given either a hash or height or an output commit.
 /// GET /v1/headers/<hash>
 /// GET /v1/headers/<height>
 /// GET /v1/headers/<output commit>
+/// Only hash, height and previous hash with ?minimal
+/// GET /v1/headers/<hash>?minimal
+///
+/// Recent headers (last DIFFICULTY_ADJUST_WINDOW * 10) within a block difficulty range
+/// GET /v1/headers?min_difficulty=N&max_difficulty=M
+///
+/// Headers with a timestamp (seconds since the UNIX epoch) between start and end
+/// (inclusive), at most MAX_HEADERS_TIMERANGE of them in ascending height order
+/// GET /v1/headers?start_time=T1&end_time=T2
 ///
 pub struct HeaderHandler {
 	pub chain: Weak<chain::Chain>,
@@ -35,13 +59,23 @@ pub struct HeaderHandler {
 
 impl HeaderHandler {
 	fn get_header(&self, input: String) -> Result<BlockHeaderPrintable, Error> {
+		let header = self.find_header(input)?;
+		Ok(BlockHeaderPrintable::from_header(&header))
+	}
+
+	fn get_header_minimal(&self, input: String) -> Result<BlockHeaderInfo, Error> {
+		let header = self.find_header(input)?;
+		Ok(BlockHeaderInfo::from_header(&header))
+	}
+
+	fn find_header(&self, input: String) -> Result<BlockHeader, Error> {
 		// will fail quick if the provided isn't a commitment
 		if let Ok(h) = self.get_header_for_output(input.clone()) {
 			return Ok(h);
 		}
 		if let Ok(height) = input.parse() {
 			match w(&self.chain)?.get_header_by_height(height) {
-				Ok(header) => return Ok(BlockHeaderPrintable::from_header(&header)),
+				Ok(header) => return Ok(header),
 				Err(e) => {
 					return Err(ErrorKind::NotFound(format!(
 						"Header for height {}, {}",
@@ -50,17 +84,14 @@ impl HeaderHandler {
 				}
 			}
 		}
-		check_block_param(&input)?;
-		let vec = util::from_hex(&input)
-			.map_err(|e| ErrorKind::Argument(format!("invalid input: {}, {}", input, e)))?;
-		let h = Hash::from_vec(&vec);
+		let h = parse_hash(&input)?;
 		let header = w(&self.chain)?
 			.get_block_header(&h)
 			.map_err(|e| ErrorKind::NotFound(format!("Block header for hash {}, {}", h, e)))?;
-		Ok(BlockHeaderPrintable::from_header(&header))
+		Ok(header)
 	}
 
-	fn get_header_for_output(&self, commit_id: String) -> Result<BlockHeaderPrintable, Error> {
+	fn get_header_for_output(&self, commit_id: String) -> Result<BlockHeader, Error> {
 		let oid = match get_output(&self.chain, &commit_id)? {
 			Some((_, o)) => o,
 			None => {
@@ -69,8 +100,8 @@ impl HeaderHandler {
 				)
 			}
 		};
-		match w(&self.chain)?.get_header_for_output(oid.id()) {
-			Ok(header) => Ok(BlockHeaderPrintable::from_header(&header)),
+		match w(&self.chain)?.get_block_header_by_commit(&oid.commitment()) {
+			Ok(header) => Ok(header),
 			Err(e) => Err(ErrorKind::NotFound(format!(
 				"Header for output {}, {}",
 				commit_id, e
@@ -78,6 +109,102 @@ impl HeaderHandler {
 		}
 	}
 
+	/// Headers of the last DIFFICULTY_ADJUST_WINDOW * 10 blocks with a block difficulty
+	/// between min and max (inclusive), in ascending height order.
+	pub fn get_headers_by_difficulty(
+		&self,
+		min: u64,
+		max: u64,
+	) -> Result<Vec<BlockHeaderPrintable>, Error> {
+		if min > max {
+			return Err(ErrorKind::Argument(format!(
+				"min_difficulty {} is greater than max_difficulty {}",
+				min, max
+			)))?;
+		}
+		let chain = w(&self.chain)?;
+		let diff_iter = chain
+			.difficulty_iter()
+			.map_err(|e| ErrorKind::Internal(format!("can't get difficulty iter: {}", e)))?;
+		let mut headers = vec![];
+		for info in diff_iter.take((consensus::DIFFICULTY_ADJUST_WINDOW * 10) as usize) {
+			let difficulty = info.difficulty.to_num();
+			if difficulty < min || difficulty > max {
+				continue;
+			}
+			let header = chain.get_block_header(&info.block_hash).map_err(|e| {
+				ErrorKind::NotFound(format!("Block header for hash {}, {}", info.block_hash, e))
+			})?;
+			headers.push(BlockHeaderPrintable::from_header(&header));
+		}
+		headers.reverse();
+		Ok(headers)
+	}
+
+	/// Headers with a timestamp between start and end (inclusive, seconds since the
+	/// UNIX epoch), in ascending height order. The height range to scan is found
+	/// with a binary search on height as timestamps increase along the chain, every
+	/// header in it is still checked against the time range.
+	pub fn get_headers_by_timerange(
+		&self,
+		start: i64,
+		end: i64,
+	) -> Result<Vec<BlockHeaderPrintable>, Error> {
+		if start > end {
+			return Err(ErrorKind::Argument(format!(
+				"start_time {} is greater than end_time {}",
+				start, end
+			)))?;
+		}
+		let chain = w(&self.chain)?;
+		let head = chain
+			.head()
+			.map_err(|e| ErrorKind::Internal(format!("can't get head: {}", e)))?;
+		let header_at = |height: u64| -> Result<_, Error> {
+			chain.get_header_by_height(height).map_err(|e| {
+				ErrorKind::Internal(format!("Header for height {}, {}", height, e)).into()
+			})
+		};
+		// lowest height with a timestamp after t (or not before it if inclusive)
+		let lowest_height = |t: i64, inclusive: bool| -> Result<u64, Error> {
+			let (mut lo, mut hi) = (0, head.height + 1);
+			while lo < hi {
+				let mid = lo + (hi - lo) / 2;
+				let ts = header_at(mid)?.timestamp.timestamp();
+				if ts < t || (!inclusive && ts == t) {
+					lo = mid + 1;
+				} else {
+					hi = mid;
+				}
+			}
+			Ok(lo)
+		};
+
+		let from = lowest_height(start, true)?;
+		let to = lowest_height(end, false)?;
+		let mut headers = vec![];
+		for height in from..to {
+			if headers.len() >= MAX_HEADERS_TIMERANGE {
+				break;
+			}
+			let header = header_at(height)?;
+			let ts = header.timestamp.timestamp();
+			if ts >= start && ts <= end {
+				headers.push(BlockHeaderPrintable::from_header(&header));
+			}
+		}
+		Ok(headers)
+	}
+
+	fn headers_by_timerange(
+		&self,
+		params: &QueryParams,
+	) -> Result<Vec<BlockHeaderPrintable>, Error> {
+		let start = parse_param!(params, "start_time", 0);
+		let end = parse_param!(params, "end_time", i64::max_value());
+		self.get_headers_by_timerange(start, end)
+	}
+
 	pub fn get_header_v2(&self, h: &Hash) -> Result<BlockHeaderPrintable, Error> {
 		let chain = w(&self.chain)?;
 		let header = chain
@@ -134,6 +261,18 @@ impl HeaderHandler {
 impl Handler for HeaderHandler {
 	fn get(&self, req: Request<Body>) -> ResponseFuture {
 		let el = right_path_element!(req);
+		let params = QueryParams::from(req.uri().query());
+		if el == "headers" {
+			if params.get("start_time").is_some() || params.get("end_time").is_some() {
+				return result_to_response(self.headers_by_timerange(&params));
+			}
+			let min = parse_param_no_err!(params, "min_difficulty", 0);
+			let max = parse_param_no_err!(params, "max_difficulty", u64::max_value());
+			return result_to_response(self.get_headers_by_difficulty(min, max));
+		}
+		if params.get("minimal").is_some() {
+			return result_to_response(self.get_header_minimal(el.to_string()));
+		}
 		result_to_response(self.get_header(el.to_string()))
 	}
 }
@@ -148,6 +287,38 @@ impl Handler for HeaderHandler {
 ///
 /// Optionally turn off the Merkle proof extraction by passing "?no_merkle_proof" query
 /// param GET /v1/blocks/<hash>?no_merkle_proof
+///
+/// A range of blocks (inclusive, at most MAX_BLOCKS_RANGE) as a JSON array
+/// GET /v1/blocks?start_height=N&end_height=M
+/// GET /v1/blocks?start_height=N&end_height=M&compact=true
+///
+/// Optionally return compact JSON (no whitespace) or the serialized block, base64 encoded
+/// or as is (application/octet-stream)
+/// GET /v1/blocks/<hash>?format=json_compact
+/// GET /v1/blocks/<hash>?format=base64
+/// GET /v1/blocks/<hash>?format=binary
+///
+/// Optionally return only the kernels or only the outputs of the block
+/// GET /v1/blocks/<hash>?kernel_only=true
+/// GET /v1/blocks/<hash>?output_only=true
+///
+/// With kernel_only or output_only, the rest of the block isn't built at all,
+/// which keeps the response cheap for very large blocks.
+/// Full blocks over LARGE_BLOCK_JSON_THRESHOLD bytes are returned as the raw
+/// block serialization (no merkle proofs, no spent flags).
+///
+/// The block timestamp (seconds since the UNIX epoch, UTC) is returned in the
+/// X-Block-Timestamp response header.
+///
+/// Full blocks from a checkpoint block (included) towards the head, at most
+/// MAX_BLOCKS_SINCE_CHECKPOINT per request, archival nodes only. The next page
+/// starts at the last block returned.
+/// GET /v1/blocks/since/<hash>
+/// GET /v1/blocks/since/<hash>?max_blocks=N
+///
+/// The block an output was created in, optionally looking up spent outputs too
+/// GET /v1/blocks/byoutput/<commit>
+/// GET /v1/blocks/byoutput/<commit>?include_spent=true
 pub struct BlockHandler {
 	pub chain: Weak<chain::Chain>,
 }
@@ -171,6 +342,75 @@ impl BlockHandler {
 		)
 	}
 
+	/// Block as JSON, with the same schema as BlockPrintable. Large blocks (see
+	/// LARGE_BLOCK_JSON_THRESHOLD) skip BlockPrintable and are serialized as is.
+	// only: limit the response to the given field ("outputs" or "kernels"),
+	// without building the printable versions of the rest of the block
+	fn get_block_json(
+		&self,
+		h: &Hash,
+		include_proof: bool,
+		include_merkle_proof: bool,
+		only: Option<&str>,
+	) -> Result<serde_json::Value, Error> {
+		let chain = w(&self.chain)?;
+		let block = chain
+			.get_block(h)
+			.map_err(|e| ErrorKind::NotFound(format!("Block for hash {}, {}", h, e)))?;
+		let broken = |e: chain::Error| {
+			ErrorKind::Internal(format!("chain error, broken block for hash {}. {}", h, e))
+		};
+		let json = match only {
+			Some("outputs") => {
+				let outputs = block
+					.outputs()
+					.iter()
+					.map(|output| {
+						OutputPrintable::from_output(
+							output,
+							&chain,
+							Some(&block.header),
+							include_proof,
+							include_merkle_proof,
+						)
+					})
+					.collect::<Result<Vec<_>, _>>()
+					.map_err(broken)?;
+				serde_json::json!({ "outputs": outputs })
+			}
+			Some("kernels") => {
+				let kernels: Vec<_> = block
+					.kernels()
+					.iter()
+					.map(TxKernelPrintable::from_txkernel)
+					.collect();
+				serde_json::json!({ "kernels": kernels })
+			}
+			_ => {
+				let block_size = ser::ser_vec(&block, ProtocolVersion::local())
+					.map_err(|e| {
+						ErrorKind::Internal(format!("can't serialize block {}, {}", h, e))
+					})?
+					.len();
+				if block_size > LARGE_BLOCK_JSON_THRESHOLD {
+					chain.get_block_json(h).map_err(broken)?
+				} else {
+					let block = BlockPrintable::from_block(
+						&block,
+						&chain,
+						include_proof,
+						include_merkle_proof,
+					)
+					.map_err(broken)?;
+					serde_json::to_value(&block).map_err(|e| {
+						ErrorKind::Internal(format!("can't serialize block {} to json, {}", h, e))
+					})?
+				}
+			}
+		};
+		Ok(json)
+	}
+
 	fn get_compact_block(&self, h: &Hash) -> Result<CompactBlockPrintable, Error> {
 		let chain = w(&self.chain)?;
 		let block = chain
@@ -185,6 +425,106 @@ impl BlockHandler {
 		})
 	}
 
+	/// Blocks from start to end height (inclusive), full or compact, in ascending
+	/// height order. Fails if any block in the range is missing (or pruned).
+	pub fn get_blocks_by_height(
+		&self,
+		start: u64,
+		end: u64,
+		compact: bool,
+	) -> Result<Vec<serde_json::Value>, Error> {
+		if end < start {
+			return Err(ErrorKind::Argument(format!(
+				"end_height {} is lower than start_height {}",
+				end, start
+			)))?;
+		}
+		if end - start >= MAX_BLOCKS_RANGE {
+			return Err(ErrorKind::Argument(format!(
+				"too many blocks requested, at most {} per request",
+				MAX_BLOCKS_RANGE
+			)))?;
+		}
+		let chain = w(&self.chain)?;
+		let mut blocks = vec![];
+		for height in start..=end {
+			let h = chain
+				.get_header_by_height(height)
+				.map_err(|e| ErrorKind::NotFound(format!("Header for height {}, {}", height, e)))?
+				.hash();
+			let block = if compact {
+				serde_json::to_value(self.get_compact_block(&h)?).map_err(|e| {
+					ErrorKind::Internal(format!("can't serialize block {} to json, {}", h, e))
+				})?
+			} else {
+				self.get_block_json(&h, false, true, None)?
+			};
+			blocks.push(block);
+		}
+		Ok(blocks)
+	}
+
+	// Block the output with the given commitment was created in.
+	fn get_block_by_output(
+		&self,
+		commit_s: &str,
+		include_spent: bool,
+	) -> Result<BlockPrintable, Error> {
+		let c = util::from_hex(commit_s).map_err(|e| {
+			ErrorKind::Argument(format!("invalid commitment hex {}, {}", commit_s, e))
+		})?;
+		if c.len() != 33 {
+			return Err(ErrorKind::Argument(format!(
+				"invalid commitment {}, get length {}, expected 33",
+				commit_s,
+				c.len()
+			))
+			.into());
+		}
+		let chain = w(&self.chain)?;
+		let block = chain.get_block_by_output_commit(&Commitment::from_vec(c), include_spent)?;
+		BlockPrintable::from_block(&block, &chain, false, true).map_err(|e| {
+			ErrorKind::Internal(format!(
+				"chain error, broken block for output {}. {}",
+				commit_s, e
+			))
+			.into()
+		})
+	}
+
+	// Full blocks from the checkpoint (included) towards the head, archival nodes only.
+	fn get_blocks_since_checkpoint(
+		&self,
+		hash: &str,
+		max_blocks: u64,
+	) -> Result<Vec<BlockPrintable>, Error> {
+		let chain = w(&self.chain)?;
+		require_archival(&chain)?;
+		let checkpoint = parse_hash(hash)?;
+		let blocks = chain
+			.get_blocks_since_checkpoint(&checkpoint, max_blocks)
+			.map_err(|e| match e.kind() {
+				chain::ErrorKind::InvalidHash => {
+					ErrorKind::NotFound(format!("checkpoint {} not on the current chain", hash))
+						.into()
+				}
+				_ => Error::from(e),
+			})?;
+		blocks
+			.iter()
+			.map(|block| {
+				BlockPrintable::from_block(block, &chain, false, true).map_err(|e| {
+					ErrorKind::Internal(format!(
+						"chain error, broken block for hash {}. {}",
+						block.hash(),
+						e
+					))
+					.into()
+				})
+			})
+			.collect()
+	}
+
 	// Try to decode the string as a height or a hash.
 	fn parse_input(&self, input: String) -> Result<Hash, Error> {
 		if let Ok(height) = input.parse() {
@@ -198,10 +538,7 @@ impl BlockHandler {
 				}
 			}
 		}
-		check_block_param(&input)?;
-		let vec = util::from_hex(&input)
-			.map_err(|e| ErrorKind::Argument(format!("invalid input {}, {}", input, e)))?;
-		Ok(Hash::from_vec(&vec))
+		parse_hash(&input)
 	}
 
 	// Try to get hash from height, hash or output commit
@@ -245,44 +582,55 @@ impl BlockHandler {
 			hash, height, commit
 		)))?;
 	}
-}
 
-fn check_block_param(input: &str) -> Result<(), Error> {
-	lazy_static! {
-		static ref RE: Regex = Regex::new(r"[0-9a-fA-F]{64}").unwrap();
-	}
-	if !RE.is_match(&input) {
-		return Err(ErrorKind::Argument(format!(
-			"Not a valid hash or height value {}",
-			input
-		)))?;
+	// Block (or compact block) serialized, the full block as read from the db.
+	fn get_block_bytes(&self, h: &Hash, compact: bool) -> Result<Vec<u8>, Error> {
+		let chain = w(&self.chain)?;
+		if !compact {
+			return chain
+				.get_raw_block_bytes(h)
+				.map_err(|e| ErrorKind::NotFound(format!("Block for hash {}, {}", h, e)).into());
+		}
+		let block = chain
+			.get_block(h)
+			.map_err(|e| ErrorKind::NotFound(format!("Block for hash {}, {}", h, e)))?;
+		let cb: CompactBlock = block.into();
+		ser::ser_vec(&cb, ProtocolVersion::local()).map_err(|e| {
+			ErrorKind::Internal(format!("can't serialize compact block {}, {}", h, e)).into()
+		})
 	}
-	Ok(())
-}
-
-impl Handler for BlockHandler {
-	fn get(&self, req: Request<Body>) -> ResponseFuture {
-		let el = right_path_element!(req);
-		let h = match self.parse_input(el.to_string()) {
-			Err(e) => {
-				return response(
-					StatusCode::BAD_REQUEST,
-					format!("failed to parse request: {}", e),
-				);
-			}
-			Ok(h) => h,
-		};
 
+	fn get_block_response(&self, h: &Hash, req: &Request<Body>) -> ResponseFuture {
 		let mut include_proof = false;
 		let mut include_merkle_proof = true;
+		let mut only = None;
+		let mut compact = false;
+		let mut format = "json".to_owned();
 		if let Some(params) = req.uri().query() {
 			let query = url::form_urlencoded::parse(params.as_bytes());
-			let mut compact = false;
-			for (param, _) in query {
+			for (param, value) in query {
 				match param.as_ref() {
 					"compact" => compact = true,
+					"format" => match value.as_ref() {
+						"json" | "json_compact" | "base64" | "binary" => format = value.to_string(),
+						_ => {
+							return response(
+								StatusCode::BAD_REQUEST,
+								format!("unsupported format: {}", value),
+							)
+						}
+					},
 					"no_merkle_proof" => include_merkle_proof = false,
 					"include_proof" => include_proof = true,
+					"kernel_only" | "output_only" if value == "false" => {}
+					"kernel_only" | "output_only" if only.is_some() => {
+						return response(
+							StatusCode::BAD_REQUEST,
+							"kernel_only and output_only can't be combined",
+						)
+					}
+					"kernel_only" => only = Some("kernels"),
+					"output_only" => only = Some("outputs"),
 					_ => {
 						return response(
 							StatusCode::BAD_REQUEST,
@@ -291,11 +639,115 @@ impl Handler for BlockHandler {
 					}
 				}
 			}
+		}
 
-			if compact {
-				return result_to_response(self.get_compact_block(&h));
+		match (format.as_str(), compact) {
+			("base64", _) => match self.get_block_bytes(h, compact) {
+				Ok(bytes) => response(StatusCode::OK, util::bytes_to_base64(&bytes)),
+				Err(e) => error_response(e),
+			},
+			("binary", _) => match self.get_block_bytes(h, compact) {
+				Ok(bytes) => with_header(
+					response(StatusCode::OK, bytes),
+					CONTENT_TYPE,
+					HeaderValue::from_static("application/octet-stream"),
+				),
+				Err(e) => error_response(e),
+			},
+			("json_compact", true) => result_to_compact_response(self.get_compact_block(h)),
+			(_, true) => result_to_response(self.get_compact_block(h)),
+			(format, false) => {
+				let res = self.get_block_json(h, include_proof, include_merkle_proof, only);
+				if format == "json_compact" {
+					result_to_compact_response(res)
+				} else {
+					result_to_response(res)
+				}
+			}
+		}
+	}
+}
+
+impl Handler for BlockHandler {
+	fn get(&self, req: Request<Body>) -> ResponseFuture {
+		let mut path_elems = req.uri().path().trim_end_matches('/').rsplit('/');
+		match (path_elems.next(), path_elems.next()) {
+			(Some(commit), Some("byoutput")) => {
+				let params = QueryParams::from(req.uri().query());
+				let include_spent = params.get("include_spent").map(|v| v != "false");
+				return result_to_response(
+					self.get_block_by_output(commit, include_spent.unwrap_or(false)),
+				);
+			}
+			(Some(hash), Some("since")) => {
+				let params = QueryParams::from(req.uri().query());
+				let max_blocks =
+					parse_param_no_err!(params, "max_blocks", chain::MAX_BLOCKS_SINCE_CHECKPOINT);
+				return result_to_response(self.get_blocks_since_checkpoint(hash, max_blocks));
+			}
+			_ => {}
+		}
+		let el = right_path_element!(req);
+		if el == "blocks" {
+			let params = QueryParams::from(req.uri().query());
+			let start = match params.get("start_height").map(|v| v.parse::<u64>()) {
+				Some(Ok(start)) => start,
+				_ => return response(StatusCode::BAD_REQUEST, "invalid or missing start_height"),
+			};
+			let end = match params.get("end_height").map(|v| v.parse::<u64>()) {
+				Some(Ok(end)) => end,
+				_ => return response(StatusCode::BAD_REQUEST, "invalid or missing end_height"),
+			};
+			let compact = params.get("compact").map(|v| v != "false").unwrap_or(false);
+			return result_to_response(self.get_blocks_by_height(start, end, compact));
+		}
+		let h = match self.parse_input(el.to_string()) {
+			Err(e) => {
+				return response(
+					StatusCode::BAD_REQUEST,
+					format!("failed to parse request: {}", e),
+				);
 			}
+			Ok(h) => h,
+		};
+
+		let resp = self.get_block_response(&h, &req);
+		match w_fut!(&self.chain).get_block_timestamp(&h) {
+			Ok(ts) => with_header(
+				resp,
+				HeaderName::from_static("x-block-timestamp"),
+				ts.into(),
+			),
+			Err(_) => resp,
+		}
+	}
+}
+
+/// Number of peers a block was relayed to by this node.
+/// GET /v1/blocks/<hash>/relay_count
+pub struct BlockRelayCountHandler {
+	pub chain: Weak<chain::Chain>,
+	pub peers: Weak<p2p::Peers>,
+}
+
+impl BlockRelayCountHandler {
+	fn get_relay_count(&self, hash: &str) -> Result<BlockRelayCount, Error> {
+		let h = parse_hash(hash)?;
+		let relayed_to = w(&self.chain)?.get_block_relay_count(&h)?;
+		let peers_connected = w(&self.peers)?.peer_count();
+		Ok(BlockRelayCount {
+			relayed_to,
+			peers_connected,
+		})
+	}
+}
+
+impl Handler for BlockRelayCountHandler {
+	fn get(&self, req: Request<Body>) -> ResponseFuture {
+		let mut path_elems = req.uri().path().trim_end_matches('/').rsplit('/');
+		match (path_elems.next(), path_elems.next()) {
+			(Some("relay_count"), Some(hash)) => result_to_response(self.get_relay_count(hash)),
+			_ => response(StatusCode::BAD_REQUEST, "invalid url"),
 		}
-		result_to_response(self.get_block(&h, include_proof, include_merkle_proof))
 	}
 }
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::utils::{get_output, get_output_v2, w};
+use super::utils::{get_output, get_output_v2, require_admin_flag, w};
 use crate::chain;
 use crate::core::core::hash::Hashed;
 use crate::rest::*;
@@ -20,9 +20,12 @@ use crate::router::{Handler, ResponseFuture};
 use crate::types::*;
 use crate::util;
 use crate::util::secp::pedersen::Commitment;
+use crate::util::{RwLock, ToHex};
 use crate::web::*;
 use hyper::{Body, Request, StatusCode};
+use std::collections::HashMap;
 use std::sync::Weak;
+use std::time::{Duration, Instant};
 
 /// Chain handler. Get the head details.
 /// GET /v1/chain
@@ -45,6 +48,321 @@ impl Handler for ChainHandler {
 	}
 }
 
+/// Chain status handler. Summary of the chain head.
+/// GET /v1/chain/status
+pub struct ChainStatusHandler {
+	pub chain: Weak<chain::Chain>,
+}
+
+impl ChainStatusHandler {
+	pub fn get_tip_info(&self) -> Result<TipInfo, Error> {
+		let chain = w(&self.chain)?;
+		let info = chain
+			.get_tip_info()
+			.map_err(|e| ErrorKind::Internal(format!("can't get chain status: {}", e)))?;
+		let peer_best_height = chain
+			.get_peer_best_header()
+			.map_err(|e| ErrorKind::Internal(format!("can't get peer best header: {}", e)))?
+			.map(|header| header.height)
+			.unwrap_or(info.height);
+		Ok(TipInfo::from_tip_info(info, peer_best_height))
+	}
+}
+
+impl Handler for ChainStatusHandler {
+	fn get(&self, _req: Request<Body>) -> ResponseFuture {
+		result_to_response(self.get_tip_info())
+	}
+}
+
+/// Block size stats handler. Size statistics over the most recent blocks.
+/// GET /v1/chain/block_size_stats
+pub struct BlockSizeStatsHandler {
+	pub chain: Weak<chain::Chain>,
+}
+
+impl BlockSizeStatsHandler {
+	pub fn get_block_size_stats(&self) -> Result<chain::BlockSizeStats, Error> {
+		w(&self.chain)?
+			.get_block_size_stats()
+			.map_err(|e| ErrorKind::Internal(format!("can't get block size stats: {}", e)).into())
+	}
+}
+
+impl Handler for BlockSizeStatsHandler {
+	fn get(&self, _req: Request<Body>) -> ResponseFuture {
+		result_to_response(self.get_block_size_stats())
+	}
+}
+
+/// Chain audit handler. Sum of all the kernel excesses along with the sum of
+/// all the unspent outputs, for the global balance audit.
+/// Scans the whole kernel MMR, so disabled unless `allow_expensive_queries = true`
+/// is set in the admin config.
+/// GET /v1/chain/audit
+pub struct ChainAuditHandler {
+	pub chain: Weak<chain::Chain>,
+	pub allow_expensive_queries: bool,
+}
+
+impl ChainAuditHandler {
+	pub fn get_audit(&self) -> Result<ChainAudit, Error> {
+		let chain = w(&self.chain)?;
+		let (head, kernel_excess_sum) = chain.get_kernel_excess_sum()?;
+		let sums = chain.get_block_sums(&head.last_block_h)?;
+		Ok(ChainAudit {
+			height: head.height,
+			hash: head.last_block_h.to_hex(),
+			kernel_excess_sum: kernel_excess_sum.to_hex(),
+			utxo_sum: sums.utxo_sum.to_hex(),
+		})
+	}
+}
+
+impl Handler for ChainAuditHandler {
+	fn get(&self, _req: Request<Body>) -> ResponseFuture {
+		if let Some(resp) =
+			require_admin_flag(self.allow_expensive_queries, "allow_expensive_queries")
+		{
+			return resp;
+		}
+		result_to_response(self.get_audit())
+	}
+}
+
+/// Default number of blocks in a largest blocks response.
+const DEFAULT_LARGEST_BLOCKS: usize = 10;
+
+/// Largest blocks handler. Largest blocks of the chain by serialized size.
+/// GET /v1/chain/largest_blocks?count=10
+pub struct LargestBlocksHandler {
+	pub chain: Weak<chain::Chain>,
+}
+
+impl LargestBlocksHandler {
+	pub fn get_largest_blocks(&self, count: usize) -> Result<Vec<BlockSize>, Error> {
+		if count > chain::MAX_LARGEST_BLOCKS {
+			return Err(ErrorKind::Argument(format!(
+				"too many blocks requested, at most {} per request",
+				chain::MAX_LARGEST_BLOCKS
+			)))?;
+		}
+		let blocks = w(&self.chain)?
+			.get_n_largest_blocks(count)
+			.map_err(|e| ErrorKind::Internal(format!("can't get largest blocks: {}", e)))?;
+		Ok(blocks
+			.into_iter()
+			.map(|(hash, size)| BlockSize {
+				hash: hash.to_hex(),
+				size_bytes: size,
+			})
+			.collect())
+	}
+}
+
+impl Handler for LargestBlocksHandler {
+	fn get(&self, req: Request<Body>) -> ResponseFuture {
+		let params = QueryParams::from(req.uri().query());
+		let count = match params.get("count").map(|v| v.parse()) {
+			Some(Ok(count)) => count,
+			Some(Err(_)) => return response(StatusCode::BAD_REQUEST, "invalid count"),
+			None => DEFAULT_LARGEST_BLOCKS,
+		};
+		result_to_response(self.get_largest_blocks(count))
+	}
+}
+
+/// Block propagation stats handler. Min/avg/max time between the block timestamp
+/// and its reception by this node over the most recent blocks.
+/// GET /v1/chain/propagation_stats
+pub struct PropagationStatsHandler {
+	pub chain: Weak<chain::Chain>,
+}
+
+impl PropagationStatsHandler {
+	pub fn get_propagation_stats(&self) -> Result<chain::PropagationStats, Error> {
+		w(&self.chain)?
+			.get_block_propagation_stats()
+			.map_err(|e| ErrorKind::Internal(format!("can't get propagation stats: {}", e)).into())
+	}
+}
+
+impl Handler for PropagationStatsHandler {
+	fn get(&self, _req: Request<Body>) -> ResponseFuture {
+		result_to_response(self.get_propagation_stats())
+	}
+}
+
+/// Header version distribution handler. Number of blocks per header version
+/// over the last DIFFICULTY_ADJUST_WINDOW blocks.
+/// GET /v1/chain/version_distribution
+pub struct VersionDistributionHandler {
+	pub chain: Weak<chain::Chain>,
+}
+
+impl VersionDistributionHandler {
+	pub fn get_version_distribution(&self) -> Result<HashMap<u16, u64>, Error> {
+		w(&self.chain)?
+			.get_block_version_distribution()
+			.map_err(|e| {
+				ErrorKind::Internal(format!("can't get block version distribution: {}", e)).into()
+			})
+	}
+}
+
+impl Handler for VersionDistributionHandler {
+	fn get(&self, _req: Request<Body>) -> ResponseFuture {
+		result_to_response(self.get_version_distribution())
+	}
+}
+
+/// Maximum number of blocks in a difficulty history response.
+const MAX_DIFFICULTY_HISTORY: u64 = 1000;
+
+/// How long a difficulty history response is served from the cache.
+const DIFFICULTY_HISTORY_TTL: Duration = Duration::from_secs(30);
+
+/// Last difficulty history built, with the range it was built for.
+pub struct DifficultyHistoryCache {
+	built: Instant,
+	from_height: u64,
+	to_height: u64,
+	history: Vec<BlockDifficulty>,
+}
+
+/// Difficulty history handler. Difficulty of the blocks in a height range, at
+/// most MAX_DIFFICULTY_HISTORY, cached for DIFFICULTY_HISTORY_TTL.
+/// Defaults to the last MAX_DIFFICULTY_HISTORY blocks.
+/// GET /v1/chain/difficulty?from_height=N&to_height=M
+pub struct DifficultyHistoryHandler {
+	pub chain: Weak<chain::Chain>,
+	pub cache: RwLock<Option<DifficultyHistoryCache>>,
+}
+
+impl DifficultyHistoryHandler {
+	pub fn get_difficulty_history(
+		&self,
+		from_height: Option<u64>,
+		to_height: Option<u64>,
+	) -> Result<Vec<BlockDifficulty>, Error> {
+		let chain = w(&self.chain)?;
+		let to_height = match to_height {
+			Some(h) => h,
+			None => {
+				chain
+					.head()
+					.map_err(|e| ErrorKind::Internal(format!("can't get head: {}", e)))?
+					.height
+			}
+		};
+		let from_height =
+			from_height.unwrap_or_else(|| to_height.saturating_sub(MAX_DIFFICULTY_HISTORY - 1));
+		if from_height > to_height {
+			return Err(ErrorKind::Argument(format!(
+				"from_height {} is greater than to_height {}",
+				from_height, to_height
+			)))?;
+		}
+		if to_height - from_height >= MAX_DIFFICULTY_HISTORY {
+			return Err(ErrorKind::Argument(format!(
+				"too many blocks requested, at most {} per request",
+				MAX_DIFFICULTY_HISTORY
+			)))?;
+		}
+
+		if let Some(ref cache) = *self.cache.read() {
+			if cache.from_height == from_height
+				&& cache.to_height == to_height
+				&& cache.built.elapsed() < DIFFICULTY_HISTORY_TTL
+			{
+				return Ok(cache.history.clone());
+			}
+		}
+
+		let header_at = |height: u64| {
+			chain
+				.get_header_by_height(height)
+				.map_err(|e| ErrorKind::NotFound(format!("Header for height {}, {}", height, e)))
+		};
+		// block difficulty is the increase of the total difficulty over the previous block
+		let mut prev_total = if from_height > 0 {
+			header_at(from_height - 1)?.total_difficulty().to_num()
+		} else {
+			0
+		};
+		let mut history = vec![];
+		for height in from_height..=to_height {
+			let header = header_at(height)?;
+			let total = header.total_difficulty().to_num();
+			history.push(BlockDifficulty {
+				height,
+				hash: header.hash().to_hex(),
+				difficulty: total.saturating_sub(prev_total),
+				secondary_scaling: header.pow.secondary_scaling,
+				timestamp: header.timestamp.timestamp(),
+			});
+			prev_total = total;
+		}
+
+		*self.cache.write() = Some(DifficultyHistoryCache {
+			built: Instant::now(),
+			from_height,
+			to_height,
+			history: history.clone(),
+		});
+		Ok(history)
+	}
+}
+
+impl Handler for DifficultyHistoryHandler {
+	fn get(&self, req: Request<Body>) -> ResponseFuture {
+		let params = QueryParams::from(req.uri().query());
+		let from_height = match params.get("from_height").map(|v| v.parse()) {
+			Some(Ok(h)) => Some(h),
+			Some(Err(_)) => return response(StatusCode::BAD_REQUEST, "invalid from_height"),
+			None => None,
+		};
+		let to_height = match params.get("to_height").map(|v| v.parse()) {
+			Some(Ok(h)) => Some(h),
+			Some(Err(_)) => return response(StatusCode::BAD_REQUEST, "invalid to_height"),
+			None => None,
+		};
+		result_to_response(self.get_difficulty_history(from_height, to_height))
+	}
+}
+
+/// Orphan pool handler. Size of the orphan pool and the last
+/// RECENT_ORPHANS_SIZE orphans received.
+/// GET /v1/chain/orphans
+pub struct ChainOrphansHandler {
+	pub chain: Weak<chain::Chain>,
+}
+
+impl ChainOrphansHandler {
+	pub fn get_orphans(&self) -> Result<ChainOrphans, Error> {
+		let chain = w(&self.chain)?;
+		let recent = chain
+			.get_recent_orphans()
+			.into_iter()
+			.map(|x| OrphanBlock {
+				hash: x.hash.to_hex(),
+				height: x.height,
+			})
+			.collect();
+		Ok(ChainOrphans {
+			count: chain.orphans_len(),
+			evicted: chain.orphans_evicted_len(),
+			recent,
+		})
+	}
+}
+
+impl Handler for ChainOrphansHandler {
+	fn get(&self, _req: Request<Body>) -> ResponseFuture {
+		result_to_response(self.get_orphans())
+	}
+}
+
 /// Chain validation handler.
 /// GET /v1/chain/validate
 pub struct ChainValidationHandler {
@@ -80,21 +398,18 @@ pub struct ChainCompactHandler {
 
 impl ChainCompactHandler {
 	pub fn compact_chain(&self) -> Result<(), Error> {
-		w(&self.chain)?
-			.compact()
-			.map_err(|e| ErrorKind::Internal(format!("compact chain error {}", e)).into())
+		w(&self.chain)?.force_compact()?;
+		Ok(())
 	}
 }
 
 impl Handler for ChainCompactHandler {
 	fn post(&self, _req: Request<Body>) -> ResponseFuture {
-		match w_fut!(&self.chain).compact() {
-			Ok(_) => response(StatusCode::OK, "{}"),
-			Err(e) => response(
-				StatusCode::INTERNAL_SERVER_ERROR,
-				format!("chain compact failed: {}", e),
-			),
-		}
+		let start = Instant::now();
+		let res = self.compact_chain().map(|_| ChainCompaction {
+			elapsed_ms: start.elapsed().as_millis() as u64,
+		});
+		result_to_response(res)
 	}
 }
 
@@ -102,6 +417,12 @@ impl Handler for ChainCompactHandler {
 // GET /v1/chain/outputs/byids?id=xxx,yyy,zzz
 // GET /v1/chain/outputs/byids?id=xxx&id=yyy&id=zzz
 // GET /v1/chain/outputs/byheight?start_height=101&end_height=200
+// Pages through the UTXO set (503 while syncing) -
+// GET /v1/chain/outputs/unspent?start_index=1&max=100&include_proof=true
+// Merkle proof of an unspent output against the current output MMR -
+// GET /v1/chain/outputs/merkle_proof/xxx
+// Unspent output at an output MMR leaf position (404 once spent) -
+// GET /v1/outputs/bypos/xxx
 pub struct OutputHandler {
 	pub chain: Weak<chain::Chain>,
 }
@@ -205,6 +526,27 @@ impl OutputHandler {
 		Ok(out)
 	}
 
+	// a page of the utxo set, with the total count and where the next page starts
+	fn unspent_outputs(&self, req: &Request<Body>) -> Result<UnspentOutputListing, Error> {
+		let params = QueryParams::from(req.uri().query());
+		let start_index = parse_param!(params, "start_index", 1);
+		let max = parse_param!(params, "max", 100);
+		let include_proof = params.get("include_proof").map(|v| v != "false");
+		let listing = self.get_unspent_outputs(start_index, None, max, include_proof)?;
+		let next_start_index = if listing.last_retrieved_index < listing.highest_index {
+			Some(listing.last_retrieved_index + 1)
+		} else {
+			None
+		};
+		Ok(UnspentOutputListing {
+			total_unspent: w(&self.chain)?.unspent_output_count(),
+			highest_index: listing.highest_index,
+			last_retrieved_index: listing.last_retrieved_index,
+			next_start_index,
+			outputs: listing.outputs,
+		})
+	}
+
 	fn outputs_by_ids(&self, req: &Request<Body>) -> Result<Vec<Output>, Error> {
 		let mut commitments: Vec<String> = vec![];
 
@@ -387,11 +729,71 @@ impl OutputHandler {
 	}
 }
 
+impl OutputHandler {
+	fn output_merkle_proof(&self, commit_s: &str) -> Result<OutputMerkleProof, Error> {
+		let c = util::from_hex(commit_s).map_err(|e| {
+			ErrorKind::Argument(format!("invalid commitment hex {}, {}", commit_s, e))
+		})?;
+		if c.len() != 33 {
+			return Err(ErrorKind::Argument(format!(
+				"invalid commitment {}, get length {}, expected 33",
+				commit_s,
+				c.len()
+			))
+			.into());
+		}
+		let commit = Commitment::from_vec(c);
+		let chain = w(&self.chain)?;
+		let pos = chain
+			.get_output_leaf_pos(&commit)
+			.map_err(|e| match e.kind() {
+				chain::ErrorKind::OutputNotFound(_) | chain::ErrorKind::OutputSpent => {
+					ErrorKind::NotFound(format!("unspent output {}", commit_s))
+				}
+				_ => ErrorKind::Internal(format!("failed to get output {}, {}", commit_s, e)),
+			})?;
+		let merkle_proof = chain.get_merkle_proof_for_pos(commit.hash()).map_err(|e| {
+			ErrorKind::Internal(format!(
+				"failed to get merkle proof for {}, {}",
+				commit_s, e
+			))
+		})?;
+		Ok(OutputMerkleProof::new(
+			commit_s.to_owned(),
+			pos,
+			&merkle_proof,
+		))
+	}
+
+	fn output_by_pos(&self, pos_s: &str) -> Result<OutputPrintable, Error> {
+		let pos = pos_s
+			.parse::<u64>()
+			.map_err(|_| ErrorKind::Argument(format!("invalid output position {}", pos_s)))?;
+		let chain = w(&self.chain)?;
+		let output = chain.get_output_at_leaf_pos(pos)?;
+		let output = OutputPrintable::from_output(&output, &chain, None, true, false)?;
+		Ok(output)
+	}
+}
+
 impl Handler for OutputHandler {
 	fn get(&self, req: Request<Body>) -> ResponseFuture {
+		let mut path_elems = req.uri().path().trim_end_matches('/').rsplit('/');
+		match (path_elems.next(), path_elems.next()) {
+			(Some(commit), Some("merkle_proof")) => {
+				return result_to_response(self.output_merkle_proof(commit));
+			}
+			(Some(pos), Some("bypos")) => return result_to_response(self.output_by_pos(pos)),
+			_ => {}
+		}
 		match right_path_element!(req) {
 			"byids" => result_to_response(self.outputs_by_ids(&req)),
 			"byheight" => result_to_response(self.outputs_block_batch(&req)),
+			"unspent" if w_fut!(&self.chain).is_syncing() => response(
+				StatusCode::SERVICE_UNAVAILABLE,
+				"txhashset not available yet, node is syncing",
+			),
+			"unspent" => result_to_response(self.unspent_outputs(&req)),
 			_ => response(StatusCode::BAD_REQUEST, ""),
 		}
 	}
@@ -523,3 +925,52 @@ impl Handler for KernelHandler {
 		result_to_response(self.get_kernel(req))
 	}
 }
+
+/// Kernel height handler, height of the block a kernel was committed in
+/// GET /v1/kernels/XXX/height
+pub struct KernelHeightHandler {
+	pub chain: Weak<chain::Chain>,
+}
+
+impl KernelHeightHandler {
+	pub fn get_kernel_height(&self, excess_s: &str) -> Result<KernelHeight, Error> {
+		let excess_v = util::from_hex(excess_s).map_err(|e| {
+			ErrorKind::RequestError(format!("invalid excess hex {}, {}", excess_s, e))
+		})?;
+		if excess_v.len() != 33 {
+			return Err(ErrorKind::RequestError(format!(
+				"invalid excess {}, get length {}, expected 33",
+				excess_s,
+				excess_v.len()
+			))
+			.into());
+		}
+		let excess = Commitment::from_vec(excess_v);
+
+		let height = w(&self.chain)?
+			.get_kernel_block_height(&excess)
+			.map_err(|e| match e.kind() {
+				chain::ErrorKind::TxKernelNotFound => {
+					ErrorKind::NotFound(format!("Kernel {} not found", excess_s))
+				}
+				_ => ErrorKind::Internal(format!(
+					"Unable to get a height for the excess {}, {}",
+					excess_s, e
+				)),
+			})?;
+		Ok(KernelHeight {
+			excess: excess_s.to_string(),
+			height,
+		})
+	}
+}
+
+impl Handler for KernelHeightHandler {
+	fn get(&self, req: Request<Body>) -> ResponseFuture {
+		let mut path_elems = req.uri().path().trim_end_matches('/').rsplit('/');
+		match (path_elems.next(), path_elems.next()) {
+			(Some("height"), Some(excess)) => result_to_response(self.get_kernel_height(excess)),
+			_ => response(StatusCode::BAD_REQUEST, "invalid url"),
+		}
+	}
+}
@@ -0,0 +1,140 @@
+// Copyright 2020 The Grin Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::utils::w;
+use crate::router::{Handler, ResponseFuture};
+use crate::types::NodeEvent;
+use crate::web::*;
+use futures::future;
+use futures::stream::{self, Stream, StreamExt};
+use hyper::header::{HeaderValue, CACHE_CONTROL, CONTENT_TYPE};
+use hyper::{Body, Request, StatusCode};
+use std::io;
+use std::sync::Weak;
+use tokio::sync::broadcast::{self, RecvError};
+
+/// Number of events kept for the event stream subscribers. A subscriber
+/// falling further behind than that is disconnected.
+pub const EVENT_STREAM_CAPACITY: usize = 1024;
+
+/// Last message sent to a subscriber that fell too far behind.
+const OVERFLOW_MESSAGE: &str = "503 Event Stream Overflow";
+
+/// Broadcast channel of the node events. The node adapters publish to it,
+/// every event stream subscriber gets its own receiver.
+pub struct EventStream {
+	sender: broadcast::Sender<NodeEvent>,
+}
+
+impl EventStream {
+	/// New channel keeping up to `capacity` events for the slowest subscriber
+	pub fn new(capacity: usize) -> EventStream {
+		let (sender, _) = broadcast::channel(capacity);
+		EventStream { sender }
+	}
+
+	/// Send an event to all the current subscribers, if any
+	pub fn publish(&self, event: NodeEvent) {
+		let _ = self.sender.send(event);
+	}
+
+	/// Receiver for the events published from now on
+	pub fn subscribe(&self) -> broadcast::Receiver<NodeEvent> {
+		self.sender.subscribe()
+	}
+}
+
+fn event_message(event: &NodeEvent) -> String {
+	format!(
+		"event: {}\ndata: {}\n\n",
+		event.name(),
+		serde_json::to_string(event).expect("node event to json")
+	)
+}
+
+/// Server-sent event messages for a subscriber. Ends with the overflow message
+/// if the subscriber lags more than the channel capacity behind.
+pub fn event_messages(rx: broadcast::Receiver<NodeEvent>) -> impl Stream<Item = String> {
+	stream::unfold(Some(rx), |rx| async move {
+		let mut rx = match rx {
+			Some(rx) => rx,
+			None => return None,
+		};
+		match rx.recv().await {
+			Ok(event) => Some((event_message(&event), Some(rx))),
+			Err(RecvError::Lagged(_)) => Some((
+				format!("event: error\ndata: {}\n\n", OVERFLOW_MESSAGE),
+				None,
+			)),
+			Err(RecvError::Closed) => None,
+		}
+	})
+}
+
+/// Event stream handler. Server-sent events for the blocks accepted and reverted
+/// by the chain and for the transactions accepted by the pool.
+/// GET /v1/events
+pub struct EventsHandler {
+	pub events: Weak<EventStream>,
+}
+
+impl Handler for EventsHandler {
+	fn get(&self, _req: Request<Body>) -> ResponseFuture {
+		let events = match w(&self.events) {
+			Ok(events) => events,
+			Err(e) => return error_response(e),
+		};
+		let messages = event_messages(events.subscribe()).map(Ok::<_, io::Error>);
+		let mut resp = just_response(StatusCode::OK, Body::wrap_stream(messages));
+		let headers = resp.headers_mut();
+		headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+		headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+		Box::pin(future::ok(resp))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use futures::executor::block_on;
+
+	#[test]
+	fn event_stream_overflow() {
+		let events = EventStream::new(2);
+		let mut messages = Box::pin(event_messages(events.subscribe()));
+
+		events.publish(NodeEvent::TxAccepted {
+			hash: "aa".to_owned(),
+			kernels: vec!["bb".to_owned()],
+		});
+		assert_eq!(
+			block_on(messages.next()).unwrap(),
+			"event: tx_accepted\n\
+			 data: {\"type\":\"tx_accepted\",\"hash\":\"aa\",\"kernels\":[\"bb\"]}\n\n"
+		);
+
+		// one more event than the subscriber can lag behind
+		for height in 1..=3 {
+			events.publish(NodeEvent::BlockAccepted {
+				height,
+				hash: "cc".to_owned(),
+			});
+		}
+		assert_eq!(
+			block_on(messages.next()).unwrap(),
+			"event: error\ndata: 503 Event Stream Overflow\n\n"
+		);
+		assert_eq!(block_on(messages.next()), None);
+	}
+}
@@ -0,0 +1,103 @@
+// Copyright 2020 The Grin Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::utils::{require_admin_flag, w};
+use crate::chain;
+use crate::core::core::Transaction;
+use crate::core::global;
+use crate::rest::*;
+use crate::router::{Handler, ResponseFuture};
+use crate::types::*;
+use crate::web::*;
+use hyper::{Body, Request};
+use std::sync::Weak;
+
+/// Expected difficulty of the next block on top of the current chain head.
+/// GET /v1/mining/next_difficulty
+pub struct NextDifficultyHandler {
+	pub chain: Weak<chain::Chain>,
+}
+
+impl NextDifficultyHandler {
+	pub fn get_next_difficulty(&self) -> Result<NextDifficulty, Error> {
+		let chain = w(&self.chain)?;
+		let head = chain
+			.head()
+			.map_err(|e| ErrorKind::Internal(format!("can't get tip: {}", e)))?;
+		let difficulty = chain
+			.estimate_next_difficulty()
+			.map_err(|e| ErrorKind::Internal(format!("can't estimate next difficulty: {}", e)))?;
+		Ok(NextDifficulty {
+			height: head.height + 1,
+			difficulty: difficulty.to_num(),
+		})
+	}
+}
+
+impl Handler for NextDifficultyHandler {
+	fn get(&self, _req: Request<Body>) -> ResponseFuture {
+		result_to_response(self.get_next_difficulty())
+	}
+}
+
+/// Upper bound of the JSON size of the posted transactions per unit of block
+/// weight, the heaviest elements (kernels) take about half of it.
+const MAX_SIMULATE_BYTES_PER_WEIGHT: usize = 200;
+
+/// Simulate the next block with the posted transactions (JSON array), without
+/// mining it. Returns its weight and fees and the transactions that can't be included.
+/// The posted transactions can't weigh more than a block, nothing gets validated
+/// otherwise. Disabled unless `allow_expensive_queries = true` is set in the
+/// admin config.
+/// POST /v1/mining/simulate
+pub struct SimulateBlockHandler {
+	pub chain: Weak<chain::Chain>,
+	pub allow_expensive_queries: bool,
+}
+
+async fn simulate_block(
+	chain: Weak<chain::Chain>,
+	req: Request<Body>,
+) -> Result<SimulatedBlock, Error> {
+	let max_weight = global::max_block_weight();
+	let max_size = max_weight as usize * MAX_SIMULATE_BYTES_PER_WEIGHT;
+	let txs: Vec<Transaction> = parse_body_limited(req, max_size).await?;
+
+	let weight = txs.iter().fold(0u64, |acc, tx| {
+		acc.saturating_add(tx.body.body_weight_as_block())
+	});
+	if weight > max_weight {
+		return Err(ErrorKind::Argument(format!(
+			"transactions weight {} exceeds the max block weight {}",
+			weight, max_weight
+		)))?;
+	}
+
+	let res = w(&chain)?
+		.simulate_block(&txs)
+		.map_err(|e| ErrorKind::Internal(format!("can't simulate block: {}", e)))?;
+	Ok(SimulatedBlock::from_result(&res))
+}
+
+impl Handler for SimulateBlockHandler {
+	fn post(&self, req: Request<Body>) -> ResponseFuture {
+		if let Some(resp) =
+			require_admin_flag(self.allow_expensive_queries, "allow_expensive_queries")
+		{
+			return resp;
+		}
+		let chain = self.chain.clone();
+		Box::pin(async move { result_to_response(simulate_block(chain, req).await).await })
+	}
+}
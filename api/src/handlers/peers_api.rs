@@ -13,25 +13,53 @@
 // limitations under the License.
 
 use super::utils::w;
+use crate::chain;
 use crate::p2p::types::{PeerAddr, PeerInfoDisplay, ReasonForBan};
 use crate::p2p::{self, PeerData};
 use crate::rest::*;
 use crate::router::{Handler, ResponseFuture};
+use crate::types::{PeerDataPrintable, PeerInfoPrintable, PeerLatency, PeerSyncStatus};
 use crate::web::*;
+use chrono::Utc;
 use grin_p2p::types::Direction;
 use grin_p2p::types::PeerInfoDisplayLegacy;
 use hyper::{Body, Request, StatusCode};
+use std::cmp;
 use std::net::SocketAddr;
 use std::sync::Weak;
+use std::time::Duration;
+
+/// Default time to wait for pongs when pinging all the connected peers
+const PING_TIMEOUT_MS: u64 = 5_000;
+
+/// Maximum time to wait for pongs when pinging all the connected peers, a
+/// larger timeout_ms is capped to it
+const MAX_PING_TIMEOUT_MS: u64 = 30_000;
 
 pub struct PeersAllHandler {
 	pub peers: Weak<p2p::Peers>,
 }
 
+impl PeersAllHandler {
+	pub fn get_all_peers(&self) -> Result<Vec<PeerDataPrintable>, Error> {
+		let peers = w(&self.peers)?;
+		let res = peers
+			.all_peers()
+			.into_iter()
+			.map(|data| {
+				let connection = peers
+					.get_connected_peer(data.addr.clone())
+					.map(|p| PeerInfoPrintable::from_peer_info(&p.info));
+				PeerDataPrintable { data, connection }
+			})
+			.collect();
+		Ok(res)
+	}
+}
+
 impl Handler for PeersAllHandler {
 	fn get(&self, _req: Request<Body>) -> ResponseFuture {
-		let peers = &w_fut!(&self.peers).all_peers();
-		json_response_pretty(&peers)
+		result_to_response(self.get_all_peers())
 	}
 }
 
@@ -78,45 +106,99 @@ impl PeersConnectedHandler {
 		}
 		Ok(peers_ret)
 	}
+
+	/// Connection details of all the connected peers.
+	pub fn get_connected_peers_info(&self) -> Result<Vec<PeerInfoPrintable>, Error> {
+		let res = w(&self.peers)?
+			.connected_peers()
+			.iter()
+			.map(|p| PeerInfoPrintable::from_peer_info(&p.info))
+			.collect();
+		Ok(res)
+	}
 }
 
 impl Handler for PeersConnectedHandler {
 	fn get(&self, _req: Request<Body>) -> ResponseFuture {
-		let peers: Vec<PeerInfoDisplay> = w_fut!(&self.peers)
-			.connected_peers()
-			.iter()
-			.map(|p| p.info.clone().into())
+		result_to_compact_response(self.get_connected_peers_info())
+	}
+}
+
+/// Ping all the connected peers and report their round trip times.
+/// Peers that didn't answer within the timeout (5s by default, at most 30s)
+/// are not listed.
+/// GET /v1/peers/ping
+/// GET /v1/peers/ping?timeout_ms=2000
+pub struct PeersPingHandler {
+	pub peers: Weak<p2p::Peers>,
+}
+
+impl PeersPingHandler {
+	pub fn ping_all_peers(&self, timeout: Duration) -> Result<Vec<PeerLatency>, Error> {
+		let latencies = w(&self.peers)?
+			.ping_all_peers(timeout)
+			.map_err(|e| ErrorKind::Internal(format!("Unable to ping peers, {:?}", e)))?;
+		let mut res: Vec<PeerLatency> = latencies
+			.into_iter()
+			.map(|(addr, rtt)| PeerLatency {
+				addr: addr.to_string(),
+				latency_ms: rtt.as_millis() as u64,
+			})
 			.collect();
+		res.sort_by_key(|p| p.latency_ms);
+		Ok(res)
+	}
+}
 
-		let mut peers_ret: Vec<PeerInfoDisplayLegacy> = Vec::new();
-		for peer in peers.clone() {
-			let peer_addr_str = match peer.addr {
-				// for tor we just return this because older wallets
-				// can't process this.
-				PeerAddr::Onion(_) => format!("127.0.0.1:{}", 3414),
-				PeerAddr::Ip(ip) => format!("{}:{}", ip.ip(), ip.port()),
-			};
+impl Handler for PeersPingHandler {
+	fn get(&self, req: Request<Body>) -> ResponseFuture {
+		let params = QueryParams::from(req.uri().query());
+		let timeout_ms: u64 = parse_param_no_err!(params, "timeout_ms", PING_TIMEOUT_MS);
+		let timeout = Duration::from_millis(cmp::min(timeout_ms, MAX_PING_TIMEOUT_MS));
+		result_to_response(self.ping_all_peers(timeout))
+	}
+}
 
-			let peer_direction = if peer.direction == Direction::OutboundTor {
-				Direction::Outbound
-			} else if peer.direction == Direction::InboundTor {
-				Direction::Inbound
-			} else {
-				peer.direction
-			};
+/// Height, total difficulty and time since the last message of every connected
+/// peer, to follow the sync progress. A warning is logged when all the peers are
+/// behind our chain tip, our node is likely isolated from the network.
+/// GET /v1/peers/sync_status
+pub struct PeersSyncStatusHandler {
+	pub chain: Weak<chain::Chain>,
+	pub peers: Weak<p2p::Peers>,
+}
 
-			let peer_display = PeerInfoDisplayLegacy {
-				capabilities: peer.capabilities,
-				user_agent: peer.user_agent,
-				version: peer.version,
-				addr: peer_addr_str,
-				direction: peer_direction,
-				total_difficulty: peer.total_difficulty,
-				height: peer.height,
-			};
-			peers_ret.push(peer_display);
+impl PeersSyncStatusHandler {
+	pub fn get_peer_sync_status(&self) -> Result<Vec<PeerSyncStatus>, Error> {
+		let head = w(&self.chain)?
+			.head()
+			.map_err(|e| ErrorKind::Internal(format!("Unable to get chain tip, {}", e)))?;
+		let now = Utc::now();
+		let res: Vec<PeerSyncStatus> = w(&self.peers)?
+			.connected_peers()
+			.iter()
+			.map(|p| PeerSyncStatus {
+				addr: p.info.addr.to_string(),
+				height: p.info.height(),
+				total_difficulty: p.info.total_difficulty().to_num(),
+				last_message_secs: (now - p.info.last_seen()).num_seconds(),
+			})
+			.collect();
+		let head_difficulty = head.total_difficulty.to_num();
+		if !res.is_empty() && res.iter().all(|p| p.total_difficulty < head_difficulty) {
+			warn!(
+				"All {} connected peers are behind our chain tip at {}, node may be isolated",
+				res.len(),
+				head.height
+			);
 		}
-		json_response(&peers_ret)
+		Ok(res)
+	}
+}
+
+impl Handler for PeersSyncStatusHandler {
+	fn get(&self, _req: Request<Body>) -> ResponseFuture {
+		result_to_response(self.get_peer_sync_status())
 	}
 }
 
@@ -54,6 +54,31 @@ where
 	}
 }
 
+/// Get the fee rate histogram of the transaction pool, used for fee estimation.
+/// GET /v1/pool/fee_histogram
+pub struct PoolFeeHistogramHandler<B, P, V>
+where
+	B: BlockChain,
+	P: PoolAdapter,
+	V: VerifierCache + 'static,
+{
+	pub tx_pool: Weak<RwLock<pool::TransactionPool<B, P, V>>>,
+}
+
+impl<B, P, V> Handler for PoolFeeHistogramHandler<B, P, V>
+where
+	B: BlockChain,
+	P: PoolAdapter,
+	V: VerifierCache + 'static,
+{
+	fn get(&self, _req: Request<Body>) -> ResponseFuture {
+		let pool_arc = w_fut!(&self.tx_pool);
+		let pool = pool_arc.read();
+
+		json_response(&pool.fee_histogram())
+	}
+}
+
 pub struct PoolHandler<B, P, V>
 where
 	B: BlockChain,
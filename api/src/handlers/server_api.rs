@@ -14,10 +14,12 @@
 
 use super::utils::w;
 use crate::chain::{Chain, SyncState, SyncStatus};
+use crate::core::core::verifier_cache::VerifierCache;
 use crate::p2p;
 use crate::rest::*;
 use crate::router::{Handler, ResponseFuture};
 use crate::types::*;
+use crate::util::RwLock;
 use crate::web::*;
 use grin_core::global;
 use hyper::{Body, Request, StatusCode};
@@ -45,22 +47,39 @@ pub struct StatusHandler {
 	pub chain: Weak<Chain>,
 	pub peers: Weak<p2p::Peers>,
 	pub sync_state: Weak<SyncState>,
+	pub verifier_cache: Option<Weak<RwLock<dyn VerifierCache>>>,
 	pub allow_to_stop: bool, //
 }
 
 impl StatusHandler {
 	pub fn get_status(&self) -> Result<Status, Error> {
-		let head = w(&self.chain)?
+		let chain = w(&self.chain)?;
+		let peers = w(&self.peers)?;
+		let head = chain
 			.head()
 			.map_err(|e| ErrorKind::Internal(format!("Unable to get chain tip, {}", e)))?;
 		let sync_status = w(&self.sync_state)?.status();
 		let (api_sync_status, api_sync_info) = sync_status_to_api(sync_status);
-		Ok(Status::from_tip_and_peers(
-			head,
-			w(&self.peers)?.peer_count(),
-			api_sync_status,
-			api_sync_info,
-		))
+		let mut status =
+			Status::from_tip_and_peers(head, peers.peer_count(), api_sync_status, api_sync_info);
+		// disk usage is informative only, don't fail the status request on it
+		status.disk_usage = match (chain.estimate_disk_usage(), peers.store_disk_size()) {
+			(Ok(usage), Ok(peer_db_bytes)) => Some(usage.with_peer_db(peer_db_bytes)),
+			(Err(e), _) => {
+				warn!("get_status: unable to estimate chain disk usage, {}", e);
+				None
+			}
+			(_, Err(e)) => {
+				warn!("get_status: unable to get peer db size, {:?}", e);
+				None
+			}
+		};
+		status.verifier_cache = self
+			.verifier_cache
+			.as_ref()
+			.and_then(|cache| cache.upgrade())
+			.map(|cache| cache.read().stats());
+		Ok(status)
 	}
 }
 
@@ -21,11 +21,19 @@ use crate::types::*;
 use crate::util;
 use crate::util::secp::pedersen::Commitment;
 use crate::web::*;
-use hyper::{Body, Request, StatusCode};
+use futures::future::ok;
+use futures::stream;
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::{Body, Request, Response, StatusCode};
+use std::fs;
 use std::sync::Weak;
+use tokio::fs::File;
+use tokio::io::AsyncReadExt;
+use tokio::sync::Semaphore;
 
 // Sum tree handler. Retrieve the roots:
 // GET /v1/txhashset/roots
+// GET /v1/txhashset/roots?height=1000
 //
 // Last inserted nodes::
 // GET /v1/txhashset/lastoutputs (gets last 10)
@@ -39,19 +47,46 @@ use std::sync::Weak;
 //
 // Build a merkle proof for a given pos
 // GET /v1/txhashset/merkleproof?n=1
+//
+// Download the txhashset archive currently offered to peers (zip), disabled
+// unless `allow_txhashset_download = true` is set in the admin config
+// GET /v1/txhashset/txhashset.zip
+// GET /v1/txhashset/download
+
+/// Max number of txhashset archive downloads served at the same time, the
+/// others get a 503
+const MAX_TXHASHSET_DOWNLOADS: usize = 1;
+/// Size of the chunks the txhashset archive is streamed in
+const TXHASHSET_DOWNLOAD_CHUNK_SIZE: usize = 64 * 1024;
+
+lazy_static! {
+	static ref TXHASHSET_DOWNLOADS: Semaphore = Semaphore::new(MAX_TXHASHSET_DOWNLOADS);
+}
 
 pub struct TxHashSetHandler {
 	pub chain: Weak<chain::Chain>,
+	pub allow_download: bool,
 }
 
 impl TxHashSetHandler {
-	// gets roots
-	fn get_roots(&self) -> Result<TxHashSet, Error> {
+	// gets roots, at the head or at the provided height
+	fn get_roots(&self, height: Option<&String>) -> Result<TxHashSet, Error> {
 		let chain = w(&self.chain)?;
-		let res = TxHashSet::from_head(&chain).map_err(|e| {
-			ErrorKind::Internal(format!("failed to read roots from txhashset, {}", e))
-		})?;
-		Ok(res)
+		let height = match height {
+			None => {
+				let res = TxHashSet::from_head(&chain).map_err(|e| {
+					ErrorKind::Internal(format!("failed to read roots from txhashset, {}", e))
+				})?;
+				return Ok(res);
+			}
+			Some(height) => height.parse::<u64>().map_err(|_| {
+				ErrorKind::RequestError("invalid value of parameter height".to_owned())
+			})?,
+		};
+		let header = chain
+			.get_header_by_height(height)
+			.map_err(|e| ErrorKind::NotFound(format!("Header at height {}, {}", height, e)))?;
+		Ok(TxHashSet::from_header(&header))
 	}
 
 	// gets last n outputs inserted in to the tree
@@ -160,6 +195,101 @@ impl TxHashSetHandler {
 			mmr_index: output_pos,
 		})
 	}
+
+	// the txhashset archive at the last archive interval boundary, with its height and size
+	fn get_archive(&self) -> Result<(u64, u64, fs::File), Error> {
+		let chain = w(&self.chain)?;
+		let header = chain.txhashset_archive_header().map_err(|e| {
+			ErrorKind::Internal(format!("failed to get txhashset archive header, {}", e))
+		})?;
+		let (_, _, file) = chain
+			.txhashset_read(header.hash())
+			.map_err(|e| ErrorKind::Internal(format!("failed to read txhashset archive, {}", e)))?;
+		let len = file
+			.metadata()
+			.map_err(|e| ErrorKind::Internal(format!("failed to read txhashset archive, {}", e)))?
+			.len();
+		Ok((header.height, len, file))
+	}
+
+	// streams the txhashset archive, at most MAX_TXHASHSET_DOWNLOADS at a time
+	fn download(&self) -> ResponseFuture {
+		if !self.allow_download {
+			return response(
+				StatusCode::FORBIDDEN,
+				"txhashset download is disabled, set allow_txhashset_download = true",
+			);
+		}
+		let permit = match TXHASHSET_DOWNLOADS.try_acquire() {
+			Ok(permit) => permit,
+			Err(_) => {
+				return response(
+					StatusCode::SERVICE_UNAVAILABLE,
+					"too many txhashset downloads in progress",
+				)
+			}
+		};
+		let (height, len, file) = match self.get_archive() {
+			Ok(res) => res,
+			Err(e) => return result_to_response::<()>(Err(e)),
+		};
+		info!(
+			"txhashset download: serving archive at {}, {} bytes",
+			height, len
+		);
+
+		// the permit is held until the whole archive is sent (or the stream dropped)
+		let state = Some((File::from_std(file), permit, 0u64, 0u64));
+		let chunks = stream::unfold(state, move |state| async move {
+			let (mut file, permit, sent, mut reported) = state?;
+			let mut buf = vec![0; TXHASHSET_DOWNLOAD_CHUNK_SIZE];
+			match file.read(&mut buf).await {
+				Ok(0) => {
+					info!(
+						"txhashset download: archive at {} sent, {} bytes",
+						height, sent
+					);
+					None
+				}
+				Ok(n) => {
+					buf.truncate(n);
+					let sent = sent + n as u64;
+					// progress, every 10%
+					let percent = if len > 0 { sent * 100 / len } else { 100 };
+					if percent >= reported + 10 {
+						reported = percent - percent % 10;
+						debug!(
+							"txhashset download: {}% ({} of {} bytes)",
+							reported, sent, len
+						);
+					}
+					Some((Ok(buf), Some((file, permit, sent, reported))))
+				}
+				Err(e) => {
+					warn!(
+						"txhashset download: failed after {} of {} bytes, {}",
+						sent, len, e
+					);
+					Some((Err(e), None))
+				}
+			}
+		});
+
+		let resp = Response::builder()
+			.status(StatusCode::OK)
+			.header(CONTENT_TYPE, "application/octet-stream")
+			.header(CONTENT_LENGTH, len)
+			.header("X-MWC-Height", height)
+			.header("X-Archive-Height", height)
+			.body(Body::wrap_stream(chunks));
+		match resp {
+			Ok(resp) => Box::pin(ok(resp)),
+			Err(e) => response(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				format!("failed to build txhashset download response, {}", e),
+			),
+		}
+	}
 }
 
 impl Handler for TxHashSetHandler {
@@ -181,7 +311,7 @@ impl Handler for TxHashSetHandler {
 		};
 
 		match right_path_element!(req) {
-			"roots" => result_to_response(self.get_roots()),
+			"roots" => result_to_response(self.get_roots(params.get("height"))),
 			"lastoutputs" => result_to_response(self.get_last_n_output(last_n)),
 			"lastrangeproofs" => result_to_response(self.get_last_n_rangeproof(last_n)),
 			"lastkernels" => result_to_response(self.get_last_n_kernel(last_n)),
@@ -190,6 +320,7 @@ impl Handler for TxHashSetHandler {
 				self.block_height_range_to_pmmr_indices(start_height, end_height),
 			),
 			"merkleproof" => result_to_response(self.get_merkle_proof_for_output(&id)),
+			"txhashset.zip" | "download" => self.download(),
 			_ => response(StatusCode::BAD_REQUEST, ""),
 		}
 	}
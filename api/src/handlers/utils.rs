@@ -14,12 +14,15 @@
 
 use crate::chain;
 use crate::chain::types::CommitPos;
-use crate::core::core::hash::Hashed;
+use crate::core::core::hash::{Hash, Hashed};
 use crate::core::core::OutputIdentifier;
 use crate::rest::*;
+use crate::router::ResponseFuture;
 use crate::types::*;
 use crate::util;
 use crate::util::secp::pedersen::Commitment;
+use crate::web::response;
+use hyper::StatusCode;
 use std::sync::{Arc, Weak};
 
 // All handlers use `Weak` references instead of `Arc` to avoid cycles that
@@ -30,6 +33,43 @@ pub fn w<T>(weak: &Weak<T>) -> Result<Arc<T>, Error> {
 		.ok_or_else(|| ErrorKind::Internal("failed to upgrade weak reference".to_owned()).into())
 }
 
+/// Guard for endpoints that only make sense on an archival node (full block history).
+/// Returns an `Argument` error if the node is running with pruning enabled.
+pub fn require_archival(chain: &chain::Chain) -> Result<(), Error> {
+	if chain.archive_mode() {
+		Ok(())
+	} else {
+		Err(ErrorKind::Argument("endpoint requires archival mode".to_owned()).into())
+	}
+}
+
+/// Guard for the endpoints that are off unless an admin config flag is set.
+/// Returns the 403 response to send back if the flag isn't set.
+pub fn require_admin_flag(enabled: bool, flag: &str) -> Option<ResponseFuture> {
+	if enabled {
+		None
+	} else {
+		Some(response(
+			StatusCode::FORBIDDEN,
+			format!(
+				"endpoint is disabled, set {} = true in the admin config",
+				flag
+			),
+		))
+	}
+}
+
+/// Parses a hash from its hex representation, exactly 64 characters.
+pub fn parse_hash(s: &str) -> Result<Hash, Error> {
+	if s.len() != 64 {
+		return Err(ErrorKind::Argument(format!(
+			"Not a valid hash {}, expected 64 hex characters",
+			s
+		)))?;
+	}
+	Hash::from_hex(s).map_err(|e| ErrorKind::Argument(format!("Not a valid hash, {}", e)).into())
+}
+
 /// Internal function to retrieves an output by a given commitment
 fn get_unspent(
 	chain: &Arc<chain::Chain>,
@@ -89,3 +129,19 @@ pub fn get_output_v2(
 
 	Ok(Some((output_printable, out)))
 }
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn parse_hash_hex() {
+		let hex = "0ad8d8d1d0c5e0ae3ceba6ab40b24f7ce3a31c9a1a3b7bf9dbaeb6ea1a2b2c3d";
+		assert_eq!(parse_hash(hex).unwrap(), Hash::from_hex(hex).unwrap());
+		// too short, too long, not hex
+		assert!(parse_hash(&hex[..62]).is_err());
+		assert!(parse_hash(&format!("{}00", hex)).is_err());
+		assert!(parse_hash(&hex.replace('a', "z")).is_err());
+		assert!(parse_hash("").is_err());
+	}
+}
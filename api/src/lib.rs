@@ -35,6 +35,7 @@ extern crate log;
 mod web;
 pub mod auth;
 pub mod client;
+mod cors;
 mod foreign;
 mod foreign_rpc;
 mod handlers;
@@ -49,8 +50,12 @@ mod types;
 pub use crate::auth::{
 	BasicAuthMiddleware, BasicAuthURIMiddleware, MWC_BASIC_REALM, MWC_FOREIGN_BASIC_REALM,
 };
+pub use crate::cors::CorsMiddleware;
 pub use crate::foreign::Foreign;
 pub use crate::foreign_rpc::ForeignRpc;
+pub use crate::handlers::blocks_api::{BlockHandler, HeaderHandler};
+pub use crate::handlers::chain_api::OutputHandler;
+pub use crate::handlers::events_api::{EventStream, EVENT_STREAM_CAPACITY};
 pub use crate::handlers::node_apis;
 pub use crate::owner::Owner;
 pub use crate::owner::{
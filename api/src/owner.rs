@@ -91,6 +91,7 @@ impl Owner {
 			chain: self.chain.clone(),
 			peers: self.peers.clone(),
 			sync_state: self.sync_state.clone(),
+			verifier_cache: None,
 			allow_to_stop: false,
 		};
 		status_handler.get_status()
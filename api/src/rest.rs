@@ -35,9 +35,11 @@ use std::fmt::{self, Display};
 use std::fs::File;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use std::{io, thread};
 use tokio::net::TcpListener;
 use tokio::runtime::Runtime;
+use tokio::{task, time};
 use tokio_rustls::TlsAcceptor;
 
 /// Errors that can be returned by an ApiEndpoint implementation.
@@ -62,6 +64,8 @@ pub enum ErrorKind {
 	Router(RouterError),
 	#[fail(display = "API P2P error: {}", _0)]
 	P2pError(String),
+	#[fail(display = "API Chain error: {}", _1)]
+	Chain(u16, String),
 }
 
 impl Fail for Error {
@@ -111,7 +115,10 @@ impl From<RouterError> for Error {
 impl From<crate::chain::Error> for Error {
 	fn from(error: crate::chain::Error) -> Error {
 		Error {
-			inner: Context::new(ErrorKind::Internal(error.to_string())),
+			inner: Context::new(ErrorKind::Chain(
+				error.to_api_status_code(),
+				error.to_string(),
+			)),
 		}
 	}
 }
@@ -335,6 +342,64 @@ impl ApiServer {
 
 pub struct LoggingMiddleware {}
 
+/// Returns 504 Gateway Timeout if the request isn't answered in time.
+/// Handlers mostly do their work synchronously when called, so they run on the
+/// blocking thread pool. A timed out handler is not cancelled, it can't be
+/// interrupted and still runs to completion in the background, holding whatever
+/// locks it took. Only meant for the cheap read-only routes.
+pub struct TimeoutMiddleware {
+	timeout: Duration,
+}
+
+impl TimeoutMiddleware {
+	pub fn new(timeout: Duration) -> TimeoutMiddleware {
+		TimeoutMiddleware { timeout }
+	}
+}
+
+impl Handler for TimeoutMiddleware {
+	fn call(
+		&self,
+		req: Request<Body>,
+		handlers: Box<dyn Iterator<Item = HandlerObj>>,
+	) -> ResponseFuture {
+		let timeout = self.timeout;
+		let handlers: Vec<HandlerObj> = handlers.collect();
+		let handle = task::spawn_blocking(move || {
+			let mut handlers = handlers.into_iter();
+			match handlers.next() {
+				Some(handler) => handler.call(req, Box::new(handlers)),
+				None => response(StatusCode::INTERNAL_SERVER_ERROR, "no handler found"),
+			}
+		});
+		Box::pin(async move {
+			let res = time::timeout(timeout, async move {
+				match handle.await {
+					Ok(fut) => fut.await,
+					Err(e) => {
+						response(
+							StatusCode::INTERNAL_SERVER_ERROR,
+							format!("request handler failed, {}", e),
+						)
+						.await
+					}
+				}
+			})
+			.await;
+			match res {
+				Ok(resp) => resp,
+				Err(_) => {
+					response(
+						StatusCode::GATEWAY_TIMEOUT,
+						format!("request not answered within {:?}", timeout),
+					)
+					.await
+				}
+			}
+		})
+	}
+}
+
 impl Handler for LoggingMiddleware {
 	fn call(
 		&self,
@@ -20,6 +20,7 @@ use crate::core::{core, ser};
 use crate::p2p;
 use crate::util::secp::pedersen;
 use crate::util::{self, ToHex};
+use chrono::Utc;
 use grin_p2p::libp2p_connection;
 use serde;
 use serde::de::MapAccess;
@@ -67,6 +68,39 @@ impl Tip {
 	}
 }
 
+/// Summary of the chain head
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TipInfo {
+	/// Height of the head
+	pub height: u64,
+	/// Hash of the head block
+	pub hash: String,
+	/// Total difficulty of the chain
+	pub total_difficulty: u64,
+	/// Timestamp of the head block, seconds since the UNIX epoch (UTC)
+	pub timestamp: i64,
+	/// Kernel MMR size at the head
+	pub kernel_mmr_size: u64,
+	/// Output MMR size at the head
+	pub output_mmr_size: u64,
+	/// Height of the best header known from peers (head height if not ahead)
+	pub peer_best_height: u64,
+}
+
+impl TipInfo {
+	pub fn from_tip_info(info: chain::TipInfo, peer_best_height: u64) -> TipInfo {
+		TipInfo {
+			height: info.height,
+			hash: info.hash.to_hex(),
+			total_difficulty: info.total_difficulty,
+			timestamp: info.timestamp,
+			kernel_mmr_size: info.kernel_mmr_size,
+			output_mmr_size: info.output_mmr_size,
+			peer_best_height,
+		}
+	}
+}
+
 /// Status page containing different server information
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Status {
@@ -83,6 +117,12 @@ pub struct Status {
 	// Additional sync information
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub sync_info: Option<serde_json::Value>,
+	// Disk space used per subsystem
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub disk_usage: Option<chain::DiskUsage>,
+	// Verifier cache occupancy and hit/miss counters
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub verifier_cache: Option<core::verifier_cache::Stats>,
 }
 
 impl Status {
@@ -99,6 +139,8 @@ impl Status {
 			tip: Tip::from_tip(current_tip),
 			sync_status,
 			sync_info,
+			disk_usage: None,
+			verifier_cache: None,
 		}
 	}
 }
@@ -112,6 +154,16 @@ pub struct TxHashSet {
 	pub range_proof_root_hash: String,
 	// Kernel set root hash
 	pub kernel_root_hash: String,
+	// Output MMR size
+	pub output_mmr_size: u64,
+	// Rangeproof MMR size (rangeproofs share the output MMR positions)
+	pub rangeproof_mmr_size: u64,
+	// Kernel MMR size
+	pub kernel_mmr_size: u64,
+	// Hash of the block header the roots were read from
+	pub block_hash: String,
+	// Height of the block header the roots were read from
+	pub height: u64,
 }
 
 impl TxHashSet {
@@ -120,11 +172,21 @@ impl TxHashSet {
 	/// We will have validated the roots on this header against the roots of the txhashset.
 	pub fn from_head(chain: &chain::Chain) -> Result<TxHashSet, chain::Error> {
 		let header = chain.head_header()?;
-		Ok(TxHashSet {
+		Ok(TxHashSet::from_header(&header))
+	}
+
+	/// PMMR roots and sizes committed to by the provided header.
+	pub fn from_header(header: &core::BlockHeader) -> TxHashSet {
+		TxHashSet {
 			output_root_hash: header.output_root.to_hex(),
 			range_proof_root_hash: header.range_proof_root.to_hex(),
 			kernel_root_hash: header.kernel_root.to_hex(),
-		})
+			output_mmr_size: header.output_mmr_size,
+			rangeproof_mmr_size: header.output_mmr_size,
+			kernel_mmr_size: header.kernel_mmr_size,
+			block_hash: header.hash().to_hex(),
+			height: header.height,
+		}
 	}
 }
 
@@ -750,6 +812,21 @@ pub struct OutputListing {
 	pub outputs: Vec<OutputPrintable>,
 }
 
+/// A page of the UTXO set, see OutputListing
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UnspentOutputListing {
+	/// Total number of unspent outputs
+	pub total_unspent: u64,
+	/// The last available output index
+	pub highest_index: u64,
+	/// The last insertion index retrieved
+	pub last_retrieved_index: u64,
+	/// Index to start the next page from, none once the end of the set is reached
+	pub next_start_index: Option<u64>,
+	/// A printable version of the outputs
+	pub outputs: Vec<OutputPrintable>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LocatedTxKernel {
 	pub tx_kernel: TxKernel,
@@ -757,6 +834,324 @@ pub struct LocatedTxKernel {
 	pub mmr_index: u64,
 }
 
+/// Expected difficulty of the next block to be mined
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NextDifficulty {
+	/// Height of the next block
+	pub height: u64,
+	/// Expected difficulty of the next block
+	pub difficulty: u64,
+}
+
+/// Transaction rejected from a simulated block
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InvalidTx {
+	/// Index of the transaction in the request
+	pub index: usize,
+	/// Why the transaction is invalid
+	pub error: String,
+}
+
+/// Outcome of simulating the next block with a set of transactions
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SimulatedBlock {
+	/// Weight of the block (coinbase included) with the valid transactions
+	pub weight: u64,
+	/// Total fees of the valid transactions
+	pub fees: u64,
+	/// Transactions that can't be included
+	pub invalid_txs: Vec<InvalidTx>,
+}
+
+impl SimulatedBlock {
+	pub fn from_result(res: &chain::SimulatedBlockResult) -> SimulatedBlock {
+		SimulatedBlock {
+			weight: res.weight,
+			fees: res.fees,
+			invalid_txs: res
+				.invalid_txs
+				.iter()
+				.map(|(index, e)| InvalidTx {
+					index: *index,
+					error: e.to_string(),
+				})
+				.collect(),
+		}
+	}
+}
+
+/// Difficulty of a block, for the difficulty history
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlockDifficulty {
+	/// Height of the block
+	pub height: u64,
+	/// Hash of the block
+	pub hash: String,
+	/// Difficulty of the block (primary and secondary PoW alike)
+	pub difficulty: u64,
+	/// Secondary PoW scaling factor of the block
+	pub secondary_scaling: u32,
+	/// Block timestamp (seconds since the UNIX epoch, UTC)
+	pub timestamp: i64,
+}
+
+/// Commitment sums of the chain state as of the head, for the global balance audit
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChainAudit {
+	/// Height of the chain head
+	pub height: u64,
+	/// Hash of the chain head
+	pub hash: String,
+	/// Sum of the excesses of all the kernels
+	pub kernel_excess_sum: String,
+	/// Sum of the unspent output commitments minus the coinbase overage, as
+	/// recorded for the head block
+	pub utxo_sum: String,
+}
+
+/// Result of the global balance audit, the unspent output commitments checked
+/// against the kernel excesses as of the chain head
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BalanceAudit {
+	/// Height of the chain head
+	pub height: u64,
+	/// Hash of the chain head
+	pub hash: String,
+	/// Sum of the unspent output commitments minus the overage, none if the
+	/// sums do not match
+	pub utxo_commitment_sum: Option<String>,
+	/// Sum of the excesses of all the kernels, none if the sums do not match
+	pub kernel_excess_sum: Option<String>,
+	/// Total coinbase overage up to the chain head
+	pub overage: i64,
+	/// Whether the UTXO sum minus the overage matches the kernel excess sum
+	/// plus the total kernel offset
+	pub matches: bool,
+}
+
+/// Result of a chain compaction
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChainCompaction {
+	/// Time the compaction took in milliseconds
+	pub elapsed_ms: u64,
+}
+
+/// Serialized size of a block
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlockSize {
+	/// Hash of the block
+	pub hash: String,
+	/// Size of the block in bytes
+	pub size_bytes: u64,
+}
+
+/// Height of the block a kernel was committed in
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KernelHeight {
+	/// Kernel excess commitment
+	pub excess: String,
+	/// Height of the block including the kernel
+	pub height: u64,
+}
+
+/// Merkle proof of an unspent output against the current output MMR
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OutputMerkleProof {
+	/// The output commitment
+	pub commit: String,
+	/// MMR position of the output leaf
+	pub mmr_index: u64,
+	/// Size of the output MMR the proof was built against
+	pub mmr_size: u64,
+	/// Sibling hashes from the leaf up to the root
+	pub path: Vec<String>,
+}
+
+impl OutputMerkleProof {
+	pub fn new(commit: String, mmr_index: u64, proof: &MerkleProof) -> OutputMerkleProof {
+		OutputMerkleProof {
+			commit,
+			mmr_index,
+			mmr_size: proof.mmr_size,
+			path: proof.path.iter().map(|h| h.to_hex()).collect(),
+		}
+	}
+}
+
+/// Number of peers a block was relayed to by the node
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlockRelayCount {
+	/// Number of peers the block was broadcast to
+	pub relayed_to: u64,
+	/// Number of peers currently connected
+	pub peers_connected: u32,
+}
+
+/// Orphan block received by the node
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OrphanBlock {
+	/// Hash of the block
+	pub hash: String,
+	/// Height of the block
+	pub height: u64,
+}
+
+/// Orphan pool state along with the most recently received orphans
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChainOrphans {
+	/// Number of blocks currently in the orphan pool
+	pub count: usize,
+	/// Total number of orphans evicted from the pool
+	pub evicted: usize,
+	/// Most recently received orphans, oldest first
+	pub recent: Vec<OrphanBlock>,
+}
+
+/// Round trip time of a ping to a connected peer
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeerLatency {
+	/// Address of the peer
+	pub addr: String,
+	/// Ping round trip time in milliseconds
+	pub latency_ms: u64,
+}
+
+/// Sync progress of a connected peer
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeerSyncStatus {
+	/// Address of the peer
+	pub addr: String,
+	/// Height last reported by the peer
+	pub height: u64,
+	/// Total difficulty last reported by the peer
+	pub total_difficulty: u64,
+	/// Seconds since we last heard from the peer
+	pub last_message_secs: i64,
+}
+
+/// Connection details of a connected peer. Address and direction keep the
+/// legacy format (tor peers listed as plain inbound/outbound on localhost)
+/// as older wallets can't process anything else.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PeerInfoPrintable {
+	/// Address of the peer
+	pub addr: String,
+	/// Capabilities advertised by the peer
+	pub capabilities: p2p::Capabilities,
+	/// User agent of the peer
+	pub user_agent: String,
+	/// Protocol version negotiated with the peer
+	pub version: u32,
+	/// Inbound or outbound connection
+	pub direction: p2p::Direction,
+	/// Height last reported by the peer
+	pub height: u64,
+	/// Total difficulty last reported by the peer
+	pub total_difficulty: u64,
+	/// Seconds since the connection was established
+	pub connected_secs: i64,
+	/// Seconds since we last heard from the peer
+	pub last_seen_secs: i64,
+}
+
+impl PeerInfoPrintable {
+	pub fn from_peer_info(info: &p2p::PeerInfo) -> PeerInfoPrintable {
+		let addr = match info.addr {
+			p2p::PeerAddr::Onion(_) => format!("127.0.0.1:{}", 3414),
+			p2p::PeerAddr::Ip(ip) => format!("{}:{}", ip.ip(), ip.port()),
+		};
+		let direction = if info.is_outbound() {
+			p2p::Direction::Outbound
+		} else {
+			p2p::Direction::Inbound
+		};
+		let now = Utc::now();
+		PeerInfoPrintable {
+			addr,
+			capabilities: info.capabilities,
+			user_agent: info.user_agent.clone(),
+			version: info.version.value(),
+			direction,
+			height: info.height(),
+			total_difficulty: info.total_difficulty().to_num(),
+			connected_secs: (now - info.first_seen()).num_seconds(),
+			last_seen_secs: (now - info.last_seen()).num_seconds(),
+		}
+	}
+}
+
+/// Stored data of a known peer, with the connection details if we are
+/// currently connected to it
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeerDataPrintable {
+	#[serde(flatten)]
+	pub data: p2p::PeerData,
+	/// Connection details, none if not connected
+	pub connection: Option<PeerInfoPrintable>,
+}
+
+/// Change of the node state pushed to the event stream subscribers
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NodeEvent {
+	/// Block is now part of the main chain
+	BlockAccepted {
+		/// Height of the block
+		height: u64,
+		/// Hash of the block
+		hash: String,
+	},
+	/// Block is not part of the main chain anymore after a reorg
+	BlockReverted {
+		/// Height of the block
+		height: u64,
+		/// Hash of the block
+		hash: String,
+	},
+	/// Transaction accepted to the pool and broadcasted to our peers
+	TxAccepted {
+		/// Hash of the transaction
+		hash: String,
+		/// Excess commitments of the transaction kernels
+		kernels: Vec<String>,
+	},
+}
+
+impl NodeEvent {
+	/// Block accepted event for a block header
+	pub fn block_accepted(header: &core::BlockHeader) -> NodeEvent {
+		NodeEvent::BlockAccepted {
+			height: header.height,
+			hash: header.hash().to_hex(),
+		}
+	}
+
+	/// Block reverted event for a block header
+	pub fn block_reverted(header: &core::BlockHeader) -> NodeEvent {
+		NodeEvent::BlockReverted {
+			height: header.height,
+			hash: header.hash().to_hex(),
+		}
+	}
+
+	/// Transaction accepted event for a transaction
+	pub fn tx_accepted(tx: &core::Transaction) -> NodeEvent {
+		NodeEvent::TxAccepted {
+			hash: tx.hash().to_hex(),
+			kernels: tx.kernels().iter().map(|k| k.excess.to_hex()).collect(),
+		}
+	}
+
+	/// Name of the event, the `type` field of its json
+	pub fn name(&self) -> &'static str {
+		match self {
+			NodeEvent::BlockAccepted { .. } => "block_accepted",
+			NodeEvent::BlockReverted { .. } => "block_reverted",
+			NodeEvent::TxAccepted { .. } => "tx_accepted",
+		}
+	}
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct PoolInfo {
 	/// Size of the pool
@@ -783,9 +1178,66 @@ pub struct Libp2pMessages {
 	pub libp2p_messages: Vec<libp2p_connection::ReceivedMessage>,
 }
 
+/// Node administration settings for the API.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AdminConfig {
+	/// Allow the (expensive) db index rebuild endpoints under /v1/admin
+	#[serde(default)]
+	pub allow_reindex: bool,
+	/// Allow the expensive read only endpoints, /v1/chain/audit (scans the whole
+	/// kernel MMR) and /v1/mining/simulate (builds a block from the posted txs)
+	#[serde(default)]
+	pub allow_expensive_queries: bool,
+	/// Allow downloading the txhashset archive at /v1/txhashset/txhashset.zip
+	#[serde(default)]
+	pub allow_txhashset_download: bool,
+}
+
+impl Default for AdminConfig {
+	fn default() -> AdminConfig {
+		AdminConfig {
+			allow_reindex: false,
+			allow_expensive_queries: false,
+			allow_txhashset_download: false,
+		}
+	}
+}
+
+/// General REST API settings.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApiConfig {
+	/// Origins allowed to query the API from a browser (CORS headers).
+	/// "*" allows any origin, an empty list (the default) disables CORS.
+	#[serde(default = "default_cors_allowed_origins")]
+	pub cors_allowed_origins: Vec<String>,
+	/// Seconds a request to one of the read-only v1 endpoints may take before
+	/// 504 Gateway Timeout is returned. The handler isn't cancelled, it still runs
+	/// to completion. 0 disables the timeout.
+	#[serde(default = "default_request_timeout_secs")]
+	pub request_timeout_secs: u64,
+}
+
+impl Default for ApiConfig {
+	fn default() -> ApiConfig {
+		ApiConfig {
+			cors_allowed_origins: default_cors_allowed_origins(),
+			request_timeout_secs: default_request_timeout_secs(),
+		}
+	}
+}
+
+fn default_cors_allowed_origins() -> Vec<String> {
+	vec![]
+}
+
+fn default_request_timeout_secs() -> u64 {
+	30
+}
+
 #[cfg(test)]
 mod test {
 	use super::*;
+	use crate::core::pow::Difficulty;
 	use serde_json;
 
 	#[test]
@@ -816,4 +1268,42 @@ mod test {
 		let serialized = serde_json::to_string(&deserialized).unwrap();
 		assert_eq!(serialized, hex_commit);
 	}
+
+	#[test]
+	fn block_header_printable_difficulty() {
+		let mut header = core::BlockHeader::default();
+		header.kernel_mmr_size = 7;
+		header.pow.total_difficulty = Difficulty::from_num(1000);
+		header.pow.secondary_scaling = 3;
+
+		let printable = BlockHeaderPrintable::from_header(&header);
+		assert_eq!(printable.kernel_mmr_size, 7);
+		assert_eq!(printable.total_difficulty, 1000);
+		assert_eq!(printable.secondary_scaling, 3);
+
+		let minimal = serde_json::to_value(BlockHeaderInfo::from_header(&header)).unwrap();
+		let fields: Vec<_> = minimal.as_object().unwrap().keys().cloned().collect();
+		assert_eq!(fields, vec!["hash", "height", "previous"]);
+	}
+
+	#[test]
+	fn serialize_peer_info_printable() {
+		let peer_info = "{\
+			 \"addr\":\"10.0.0.1:3414\",\
+			 \"capabilities\":{\"bits\":31},\
+			 \"user_agent\":\"MW/MWC 4.0.0\",\
+			 \"version\":3,\
+			 \"direction\":\"Outbound\",\
+			 \"height\":1000,\
+			 \"total_difficulty\":123456,\
+			 \"connected_secs\":3600,\
+			 \"last_seen_secs\":5\
+			 }";
+		let deserialized: PeerInfoPrintable = serde_json::from_str(&peer_info).unwrap();
+		assert_eq!(deserialized.capabilities, p2p::Capabilities::FULL_NODE);
+		assert_eq!(deserialized.direction, p2p::Direction::Outbound);
+		assert_eq!(deserialized.connected_secs, 3600);
+		let serialized = serde_json::to_string(&deserialized).unwrap();
+		assert_eq!(serialized, peer_info);
+	}
 }
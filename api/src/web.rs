@@ -2,7 +2,9 @@ use crate::rest::*;
 use crate::router::ResponseFuture;
 use bytes::Buf;
 use futures::future::ok;
-use hyper::body;
+use futures::TryFutureExt;
+use hyper::body::{self, HttpBody};
+use hyper::header::{HeaderName, HeaderValue};
 use hyper::{Body, Request, Response, StatusCode};
 use serde::{Deserialize, Serialize};
 use serde_json;
@@ -24,6 +26,31 @@ where
 	})
 }
 
+/// Parse request body, failing as soon as it gets larger than max_size bytes.
+pub async fn parse_body_limited<T>(req: Request<Body>, max_size: usize) -> Result<T, Error>
+where
+	for<'de> T: Deserialize<'de> + Send + 'static,
+{
+	let mut body = req.into_body();
+	let mut raw = vec![];
+	while let Some(chunk) = body.data().await {
+		let chunk =
+			chunk.map_err(|e| ErrorKind::RequestError(format!("Failed to read request: {}", e)))?;
+		if raw.len() + chunk.len() > max_size {
+			return Err(ErrorKind::RequestError(format!(
+				"Request body too large, at most {} bytes",
+				max_size
+			))
+			.into());
+		}
+		raw.extend_from_slice(&chunk);
+	}
+
+	serde_json::from_slice(&raw).map_err(|e| {
+		ErrorKind::RequestError(format!("Invalid request body (expected json), {}", e)).into()
+	})
+}
+
 /// Convert Result to ResponseFuture
 pub fn result_to_response<T>(res: Result<T, Error>) -> ResponseFuture
 where
@@ -31,24 +58,42 @@ where
 {
 	match res {
 		Ok(s) => json_response_pretty(&s),
-		Err(e) => match e.kind() {
-			ErrorKind::Argument(msg) => response(StatusCode::BAD_REQUEST, msg.clone()),
-			ErrorKind::RequestError(msg) => response(StatusCode::BAD_REQUEST, msg.clone()),
-			ErrorKind::NotFound(msg) => response(StatusCode::NOT_FOUND, msg.clone()),
-			ErrorKind::Internal(msg) => response(StatusCode::INTERNAL_SERVER_ERROR, msg.clone()),
-			ErrorKind::ResponseError(msg) => {
-				response(StatusCode::INTERNAL_SERVER_ERROR, msg.clone())
-			}
-			// place holder
-			ErrorKind::Router(err) => response(
-				StatusCode::INTERNAL_SERVER_ERROR,
-				format!("Router Error, {}", err),
-			),
-			ErrorKind::P2pError(err) => response(
-				StatusCode::INTERNAL_SERVER_ERROR,
-				format!("P2P Error, {}", err),
-			),
-		},
+		Err(e) => error_response(e),
+	}
+}
+
+/// Convert Result to ResponseFuture, as compact json (no whitespace)
+pub fn result_to_compact_response<T>(res: Result<T, Error>) -> ResponseFuture
+where
+	T: Serialize,
+{
+	match res {
+		Ok(s) => json_response(&s),
+		Err(e) => error_response(e),
+	}
+}
+
+/// Error as HTTP response, with the status code matching its kind
+pub fn error_response(e: Error) -> ResponseFuture {
+	match e.kind() {
+		ErrorKind::Argument(msg) => response(StatusCode::BAD_REQUEST, msg.clone()),
+		ErrorKind::RequestError(msg) => response(StatusCode::BAD_REQUEST, msg.clone()),
+		ErrorKind::NotFound(msg) => response(StatusCode::NOT_FOUND, msg.clone()),
+		ErrorKind::Internal(msg) => response(StatusCode::INTERNAL_SERVER_ERROR, msg.clone()),
+		ErrorKind::ResponseError(msg) => response(StatusCode::INTERNAL_SERVER_ERROR, msg.clone()),
+		// place holder
+		ErrorKind::Router(err) => response(
+			StatusCode::INTERNAL_SERVER_ERROR,
+			format!("Router Error, {}", err),
+		),
+		ErrorKind::P2pError(err) => response(
+			StatusCode::INTERNAL_SERVER_ERROR,
+			format!("P2P Error, {}", err),
+		),
+		ErrorKind::Chain(code, msg) => response(
+			StatusCode::from_u16(*code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+			msg.clone(),
+		),
 	}
 }
 
@@ -93,6 +138,14 @@ pub fn response<T: Into<Body> + Debug>(status: StatusCode, text: T) -> ResponseF
 	Box::pin(ok(just_response(status, text)))
 }
 
+/// Add a header to the response once it's ready
+pub fn with_header(resp: ResponseFuture, name: HeaderName, value: HeaderValue) -> ResponseFuture {
+	Box::pin(resp.map_ok(move |mut resp| {
+		resp.headers_mut().insert(name, value);
+		resp
+	}))
+}
+
 pub struct QueryParams {
 	params: HashMap<String, Vec<String>>,
 }
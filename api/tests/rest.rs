@@ -1,10 +1,23 @@
 use grin_api as api;
+use grin_chain as chain;
+use grin_core as core;
+use grin_keychain as keychain;
 use grin_util as util;
 
 use crate::api::*;
+use crate::chain::types::NoopAdapter;
+use crate::chain::{Chain, Options};
+use crate::core::core::hash::Hashed;
+use crate::core::core::verifier_cache::LruVerifierCache;
+use crate::core::core::Block;
+use crate::core::libtx::{self, ProofBuilder};
+use crate::core::{consensus, genesis, pow};
+use crate::keychain::{ExtKeychain, ExtKeychainPath, Keychain};
+use crate::util::ToHex;
 use futures::channel::oneshot;
 use grin_core::global;
 use hyper::{Body, Request, StatusCode};
+use std::fs;
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
@@ -85,6 +98,218 @@ fn test_start_api() {
 	thread::sleep(time::Duration::from_millis(1_000));
 }
 
+/// Chain with a non-empty genesis and `height` blocks mined on top of it.
+fn mine_chain(dir_name: &str, height: u64) -> Chain {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	let _ = fs::remove_dir_all(dir_name);
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let reward = |n: u64| {
+		let key_id = ExtKeychainPath::new(1, n as u32, 0, 0, 0).to_identifier();
+		libtx::reward::output(&keychain, &builder, &key_id, 0, false, n).unwrap()
+	};
+	let (output, kernel) = reward(0);
+	let genesis = genesis::genesis_dev().with_reward(output, kernel);
+	let chain = Chain::init(
+		dir_name.to_string(),
+		Arc::new(NoopAdapter {}),
+		genesis,
+		pow::verify_size,
+		Arc::new(util::RwLock::new(LruVerifierCache::new())),
+		false,
+	)
+	.unwrap();
+
+	for n in 1..=height {
+		let prev = chain.head_header().unwrap();
+		let next_header_info = consensus::next_difficulty(1, chain.difficulty_iter().unwrap());
+		let mut b = Block::new(&prev, &[], next_header_info.difficulty, reward(n)).unwrap();
+		b.header.timestamp = prev.timestamp + chrono::Duration::seconds(60);
+		b.header.pow.secondary_scaling = next_header_info.secondary_scaling;
+		chain.set_txhashset_roots(&mut b).unwrap();
+		let edge_bits = global::min_edge_bits();
+		b.header.pow.proof.edge_bits = edge_bits;
+		pow::pow_size(
+			&mut b.header,
+			next_header_info.difficulty,
+			global::proofsize(),
+			edge_bits,
+		)
+		.unwrap();
+		chain.process_block(b, Options::MINE).unwrap();
+	}
+	chain
+}
+
+#[test]
+fn test_blocks_by_height() {
+	util::init_test_logger();
+	let chain_dir = ".mwc_api_blocks_by_height";
+	let chain = Arc::new(mine_chain(chain_dir, 5));
+
+	let mut server = ApiServer::new();
+	let mut router = Router::new();
+	router
+		.add_route(
+			"/v1/blocks",
+			Arc::new(BlockHandler {
+				chain: Arc::downgrade(&chain),
+			}),
+		)
+		.unwrap();
+	let server_addr = "127.0.0.1:14435";
+	let addr: SocketAddr = server_addr.parse().expect("unable to parse server address");
+	let api_chan: &'static mut (oneshot::Sender<()>, oneshot::Receiver<()>) =
+		Box::leak(Box::new(oneshot::channel::<()>()));
+	assert!(server.start(addr, router, None, api_chan).is_ok());
+	let url = |query: &str| format!("http://{}/v1/blocks?{}", server_addr, query);
+
+	// happy path, both bounds included
+	let blocks: Vec<BlockPrintable> = get_with_retry(&url("start_height=2&end_height=4")).unwrap();
+	let heights: Vec<u64> = blocks.iter().map(|b| b.header.height).collect();
+	assert_eq!(heights, vec![2, 3, 4]);
+	let blocks: Vec<CompactBlockPrintable> =
+		get_with_retry(&url("start_height=0&end_height=5&compact=true")).unwrap();
+	assert_eq!(blocks.len(), 6);
+
+	// at most 1000 blocks per request, checked before looking at the chain
+	let res = get_with_retry::<Vec<BlockPrintable>>(&url("start_height=0&end_height=1000"));
+	assert!(res.unwrap_err().to_string().contains("400"));
+	let res = get_with_retry::<Vec<BlockPrintable>>(&url("start_height=4&end_height=2"));
+	assert!(res.unwrap_err().to_string().contains("400"));
+
+	// partially above the head, nothing is returned
+	let res = get_with_retry::<Vec<BlockPrintable>>(&url("start_height=4&end_height=7"));
+	assert!(res.unwrap_err().to_string().contains("404"));
+
+	assert!(server.stop());
+	thread::sleep(time::Duration::from_millis(1_000));
+	drop(chain);
+	let _ = fs::remove_dir_all(chain_dir);
+}
+
+#[test]
+fn test_blocks_since_requires_archival() {
+	util::init_test_logger();
+	let chain_dir = ".mwc_api_blocks_since_requires_archival";
+	// not an archival node
+	let chain = Arc::new(mine_chain(chain_dir, 2));
+
+	let mut server = ApiServer::new();
+	let mut router = Router::new();
+	router
+		.add_route(
+			"/v1/blocks/since/*",
+			Arc::new(BlockHandler {
+				chain: Arc::downgrade(&chain),
+			}),
+		)
+		.unwrap();
+	let server_addr = "127.0.0.1:14437";
+	let addr: SocketAddr = server_addr.parse().expect("unable to parse server address");
+	let api_chan: &'static mut (oneshot::Sender<()>, oneshot::Receiver<()>) =
+		Box::leak(Box::new(oneshot::channel::<()>()));
+	assert!(server.start(addr, router, None, api_chan).is_ok());
+
+	let genesis = chain.get_header_by_height(0).unwrap();
+	let url = format!(
+		"http://{}/v1/blocks/since/{}",
+		server_addr,
+		genesis.hash().to_hex()
+	);
+	let res = get_with_retry::<Vec<BlockPrintable>>(&url);
+	assert!(res.unwrap_err().to_string().contains("400"));
+
+	assert!(server.stop());
+	thread::sleep(time::Duration::from_millis(1_000));
+	drop(chain);
+	let _ = fs::remove_dir_all(chain_dir);
+}
+
+#[test]
+fn test_headers_by_timerange() {
+	util::init_test_logger();
+	let chain_dir = ".mwc_api_headers_by_timerange";
+	let chain = Arc::new(mine_chain(chain_dir, 5));
+	let handler = HeaderHandler {
+		chain: Arc::downgrade(&chain),
+	};
+	let heights = |start: i64, end: i64| -> Vec<u64> {
+		handler
+			.get_headers_by_timerange(start, end)
+			.unwrap()
+			.iter()
+			.map(|h| h.height)
+			.collect()
+	};
+
+	// blocks are mined 60 seconds apart
+	let genesis = chain.get_header_by_height(0).unwrap().timestamp.timestamp();
+	assert_eq!(heights(genesis + 120, genesis + 240), vec![2, 3, 4]);
+	assert_eq!(heights(genesis + 61, genesis + 179), vec![2]);
+	assert_eq!(heights(genesis, genesis), vec![0]);
+	assert_eq!(heights(0, i64::max_value()), vec![0, 1, 2, 3, 4, 5]);
+
+	// nothing in the range
+	assert!(heights(genesis + 121, genesis + 179).is_empty());
+	assert!(heights(genesis + 301, i64::max_value()).is_empty());
+	assert!(heights(0, genesis - 1).is_empty());
+	assert!(handler
+		.get_headers_by_timerange(genesis + 1, genesis)
+		.is_err());
+
+	drop(handler);
+	drop(chain);
+	let _ = fs::remove_dir_all(chain_dir);
+}
+
+#[test]
+fn test_output_routes() {
+	util::init_test_logger();
+	let chain_dir = ".mwc_api_output_routes";
+	let chain = Arc::new(mine_chain(chain_dir, 3));
+
+	let mut server = ApiServer::new();
+	let mut router = Router::new();
+	router
+		.add_route(
+			"/v1/chain/outputs/**",
+			Arc::new(OutputHandler {
+				chain: Arc::downgrade(&chain),
+			}),
+		)
+		.unwrap();
+	let server_addr = "127.0.0.1:14436";
+	let addr: SocketAddr = server_addr.parse().expect("unable to parse server address");
+	let api_chan: &'static mut (oneshot::Sender<()>, oneshot::Receiver<()>) =
+		Box::leak(Box::new(oneshot::channel::<()>()));
+	assert!(server.start(addr, router, None, api_chan).is_ok());
+	let url = |path: &str| format!("http://{}/v1/chain/outputs/{}", server_addr, path);
+
+	let head = chain.head_header().unwrap();
+	let output = chain.get_block(&head.hash()).unwrap().outputs()[0].clone();
+	let commit = output.commitment().to_hex();
+
+	// one and two path elements below /v1/chain/outputs, same handler
+	let outputs: Vec<Output> = get_with_retry(&url(&format!("byids?id={}", commit))).unwrap();
+	assert_eq!(outputs.len(), 1);
+	let proof: OutputMerkleProof =
+		get_with_retry(&url(&format!("merkle_proof/{}", commit))).unwrap();
+	assert_eq!(proof.commit, commit);
+	assert_eq!(proof.mmr_index, chain.get_output_pos(&output.id()).unwrap());
+	assert_eq!(proof.mmr_size, head.output_mmr_size);
+
+	// unknown output
+	let unknown = format!("08{}", "00".repeat(32));
+	let res = get_with_retry::<OutputMerkleProof>(&url(&format!("merkle_proof/{}", unknown)));
+	assert!(res.unwrap_err().to_string().contains("404"));
+
+	assert!(server.stop());
+	thread::sleep(time::Duration::from_millis(1_000));
+	drop(chain);
+	let _ = fs::remove_dir_all(chain_dir);
+}
+
 // To enable this test you need a trusted PKCS12 (p12) certificate bundle
 // Hyper-tls client doesn't accept self-signed certificates. The easiest way is to use mkcert
 // https://github.com/FiloSottile/mkcert to install CA and generate a certificate on your local machine.
@@ -111,14 +336,20 @@ fn test_start_api_tls() {
 }
 
 fn request_with_retry(url: &str) -> Result<Vec<String>, api::Error> {
+	get_with_retry(url)
+}
+
+// Retries while the server is starting, an error response is returned as is.
+fn get_with_retry<T>(url: &str) -> Result<T, api::Error>
+where
+	for<'de> T: serde::Deserialize<'de>,
+{
 	let mut tries = 0;
 	loop {
-		let res = api::client::get::<Vec<String>>(url, None);
-		if res.is_ok() {
-			return res;
-		}
-		if tries > 5 {
-			return res;
+		let res = api::client::get::<T>(url, None);
+		match res {
+			Err(ref e) if tries <= 5 && e.to_string().contains("Cannot make request") => {}
+			_ => return res,
 		}
 		tries += 1;
 		thread::sleep(time::Duration::from_millis(500));
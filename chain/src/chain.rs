@@ -15,32 +15,37 @@
 //! Facade and handler for the rest of the blockchain implementation
 //! and mostly the chain pipeline.
 
+use crate::core::consensus;
+use crate::core::core::committed;
 use crate::core::core::hash::{Hash, Hashed, ZERO_HASH};
 use crate::core::core::merkle_proof::MerkleProof;
+use crate::core::core::pmmr;
 use crate::core::core::verifier_cache::VerifierCache;
 use crate::core::core::{
-	Block, BlockHeader, BlockSums, Committed, Inputs, KernelFeatures, Output, OutputIdentifier,
-	Transaction, TxKernel,
+	Block, BlockHeader, BlockSums, CommitWrapper, Committed, Inputs, KernelFeatures, Output,
+	OutputIdentifier, Transaction, TransactionBody, TxKernel, Weighting,
 };
 use crate::core::global;
 use crate::core::pow;
-use crate::core::ser::ProtocolVersion;
+use crate::core::ser::{self, ProtocolVersion};
 use crate::error::{Error, ErrorKind};
 use crate::pipe;
 use crate::store;
 use crate::txhashset;
 use crate::txhashset::{PMMRHandle, TxHashSet};
 use crate::types::{
-	BlockStatus, ChainAdapter, CommitPos, NoStatus, Options, Tip, TxHashsetWriteStatus,
+	BlockSizeStats, BlockStatus, ChainAdapter, CommitPos, DiskUsage, HashHeight, NoStatus, Options,
+	PropagationStats, SimulatedBlockResult, Tip, TipInfo, TxHashsetWriteStatus,
 };
 use crate::util::secp::pedersen::{Commitment, RangeProof};
 use crate::{util::RwLock, ChainStore};
 use grin_store::Error::NotFoundErr;
 use grin_util::ToHex;
-use std::collections::HashMap;
+use std::cmp::{self, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
 use std::fs::{self, File};
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
@@ -50,6 +55,28 @@ pub const MAX_ORPHAN_SIZE: usize = 200;
 /// When evicting, very old orphans are evicted first
 const MAX_ORPHAN_AGE_SECS: u64 = 300;
 
+/// Number of most recently received orphans we keep track of (for diagnostics)
+pub const RECENT_ORPHANS_SIZE: usize = 100;
+
+/// Maximum number of blocks returned by get_n_largest_blocks
+pub const MAX_LARGEST_BLOCKS: usize = 1000;
+
+/// Maximum number of blocks returned by a single get_blocks_since_checkpoint call
+pub const MAX_BLOCKS_SINCE_CHECKPOINT: u64 = 100;
+
+/// Number of most recent blocks the block size stats are computed over
+const BLOCK_SIZE_STATS_WINDOW: u64 = 1000;
+
+/// Number of most recent blocks the block propagation stats are computed over,
+/// older reception times are pruned as new blocks are received
+pub(crate) const PROPAGATION_STATS_WINDOW: u64 = 1000;
+
+/// Number of spent commitments loaded at once when iterating over spent commitments
+const SPENT_COMMITMENTS_PAGE_SIZE: usize = 1000;
+
+/// Same as above for the testing chain types, small enough to span a few pages
+const TESTING_SPENT_COMMITMENTS_PAGE_SIZE: usize = 2;
+
 /// Banned block. We don't accept any blockchain with this has
 pub const BLOCK_TO_BAN: &str = "00020440a401086e57e1b7a92ebb0277c7f7fd47a38269ecc6789c2a80333725";
 
@@ -68,6 +95,8 @@ pub struct OrphanBlockPool {
 	height_idx: RwLock<HashMap<u64, Vec<Hash>>>,
 	// accumulated number of evicted block because of MAX_ORPHAN_SIZE limitation
 	evicted: AtomicUsize,
+	// the last RECENT_ORPHANS_SIZE orphans received, most recent last
+	recent: RwLock<VecDeque<HashHeight>>,
 }
 
 impl OrphanBlockPool {
@@ -76,6 +105,7 @@ impl OrphanBlockPool {
 			orphans: RwLock::new(HashMap::new()),
 			height_idx: RwLock::new(HashMap::new()),
 			evicted: AtomicUsize::new(0),
+			recent: RwLock::new(VecDeque::with_capacity(RECENT_ORPHANS_SIZE)),
 		}
 	}
 
@@ -88,7 +118,22 @@ impl OrphanBlockPool {
 		self.evicted.load(Ordering::Relaxed)
 	}
 
+	fn recent(&self) -> Vec<HashHeight> {
+		self.recent.read().iter().cloned().collect()
+	}
+
 	fn add(&self, orphan: Orphan) {
+		{
+			let mut recent = self.recent.write();
+			if recent.len() >= RECENT_ORPHANS_SIZE {
+				recent.pop_front();
+			}
+			recent.push_back(HashHeight {
+				hash: orphan.block.hash(),
+				height: orphan.block.header.height,
+			});
+		}
+
 		let mut orphans = self.orphans.write();
 		let mut height_idx = self.height_idx.write();
 		{
@@ -158,7 +203,21 @@ pub struct Chain {
 	// POW verification function
 	pow_verifier: fn(&BlockHeader) -> Result<(), pow::Error>,
 	archive_mode: bool,
+	readonly: bool,
 	genesis: BlockHeader,
+	// block size stats, cached along with the tip they were computed for
+	block_size_stats: Arc<RwLock<Option<(Hash, BlockSizeStats)>>>,
+	// set while a compaction is running
+	compacting: Arc<AtomicBool>,
+}
+
+/// Clears the compacting flag when the running compaction ends.
+struct CompactingGuard<'a>(&'a AtomicBool);
+
+impl<'a> Drop for CompactingGuard<'a> {
+	fn drop(&mut self) {
+		self.0.store(false, Ordering::SeqCst);
+	}
 }
 
 impl Chain {
@@ -175,11 +234,19 @@ impl Chain {
 	) -> Result<Chain, Error> {
 		let store = Arc::new(store::ChainStore::new(&db_root)?);
 
+		// A db written by a newer node may not be readable by us.
+		Chain::check_db_version(&store)?;
+
 		// DB migrations to be run prior to the chain being used.
 		// Migrate full blocks to protocol version v3.
 		Chain::migrate_db_v2_v3(&store)?;
 		// Migrate output positions from commitment-based to ID-based.
 		Chain::migrate_db_outputs(&store)?;
+		{
+			let batch = store.batch()?;
+			batch.save_db_version(store::CHAIN_DB_VERSION)?;
+			batch.commit()?;
+		}
 
 		// open the txhashset, creating a new one if necessary
 		let mut txhashset = txhashset::TxHashSet::open(db_root.clone(), store.clone(), None)?;
@@ -225,7 +292,10 @@ impl Chain {
 			pow_verifier,
 			verifier_cache,
 			archive_mode,
+			readonly: false,
 			genesis: genesis.header,
+			block_size_stats: Arc::new(RwLock::new(None)),
+			compacting: Arc::new(AtomicBool::new(false)),
 		};
 
 		// If known bad block exists on "current chain" then rewind prior to this.
@@ -240,6 +310,69 @@ impl Chain {
 		Ok(chain)
 	}
 
+	/// Opens an existing chain without write access, for processes watching a
+	/// data dir owned by another node. The db must be at our current version
+	/// and have a head already, as neither migrations nor the genesis setup
+	/// can run. The txhashset and header MMR files are opened but never
+	/// written to: blocks, headers and txhashsets are refused with ReadOnly.
+	pub fn init_readonly(
+		db_root: String,
+		adapter: Arc<dyn ChainAdapter + Send + Sync>,
+		genesis: Block,
+		pow_verifier: fn(&BlockHeader) -> Result<(), pow::Error>,
+		verifier_cache: Arc<RwLock<dyn VerifierCache>>,
+		archive_mode: bool,
+	) -> Result<Chain, Error> {
+		let store = Arc::new(store::ChainStore::new_readonly(&db_root)?);
+
+		match store.db_version()? {
+			Some(version) if version == store::CHAIN_DB_VERSION => {}
+			version => {
+				return Err(ErrorKind::DatabaseMigrationRequired {
+					current_version: version.unwrap_or(0),
+					required_version: store::CHAIN_DB_VERSION,
+				}
+				.into())
+			}
+		}
+		// An empty db or one for another chain can't be set up without write access.
+		store.head()?;
+		store.get_block_header(&genesis.hash())?;
+
+		let txhashset = txhashset::TxHashSet::open_readonly(db_root.clone(), store.clone())?;
+		let header_pmmr = PMMRHandle::new_readonly(
+			Path::new(&db_root).join("header").join("header_head"),
+			false,
+			ProtocolVersion(1),
+		)?;
+		let sync_pmmr = PMMRHandle::new_readonly(
+			Path::new(&db_root).join("header").join("sync_head"),
+			false,
+			ProtocolVersion(1),
+		)?;
+
+		let chain = Chain {
+			db_root,
+			store,
+			adapter,
+			orphans: Arc::new(OrphanBlockPool::new()),
+			txhashset: Arc::new(RwLock::new(txhashset)),
+			header_pmmr: Arc::new(RwLock::new(header_pmmr)),
+			sync_pmmr: Arc::new(RwLock::new(sync_pmmr)),
+			pow_verifier,
+			verifier_cache,
+			archive_mode,
+			readonly: true,
+			genesis: genesis.header,
+			block_size_stats: Arc::new(RwLock::new(None)),
+			compacting: Arc::new(AtomicBool::new(false)),
+		};
+
+		chain.log_heads()?;
+
+		Ok(chain)
+	}
+
 	/// Return our shared header MMR handle.
 	pub fn header_pmmr(&self) -> Arc<RwLock<PMMRHandle<BlockHeader>>> {
 		self.header_pmmr.clone()
@@ -255,6 +388,23 @@ impl Chain {
 		self.store.clone()
 	}
 
+	/// Is this node running in archive mode (full block history is kept)?
+	pub fn archive_mode(&self) -> bool {
+		self.archive_mode
+	}
+
+	/// Was this chain opened read-only (see init_readonly)?
+	pub fn is_readonly(&self) -> bool {
+		self.readonly
+	}
+
+	fn check_writable(&self) -> Result<(), Error> {
+		if self.readonly {
+			return Err(ErrorKind::ReadOnly.into());
+		}
+		Ok(())
+	}
+
 	/// Known bad block that we must rewind prior to if seen on "current chain".
 	fn rewind_bad_block(&self) -> Result<(), Error> {
 		let hash = Hash::from_hex(BLOCK_TO_BAN)?;
@@ -376,6 +526,7 @@ impl Chain {
 	/// Processes a single block, then checks for orphans, processing
 	/// those as well if they're found
 	pub fn process_block(&self, b: Block, opts: Options) -> Result<Option<Tip>, Error> {
+		self.check_writable()?;
 		let height = b.header.height;
 		let res = self.process_block_single(b, opts);
 		if res.is_ok() {
@@ -589,6 +740,7 @@ impl Chain {
 	/// Note: This will update header MMR and corresponding header_head
 	/// if total work increases (on the header chain).
 	pub fn process_block_header(&self, bh: &BlockHeader, opts: Options) -> Result<(), Error> {
+		self.check_writable()?;
 		let mut header_pmmr = self.header_pmmr.write();
 		let mut txhashset = self.txhashset.write();
 		let batch = self.store.batch()?;
@@ -602,6 +754,7 @@ impl Chain {
 	/// This is only ever used during sync and is based on sync_head.
 	/// We update header_head here if our total work increases.
 	pub fn sync_block_headers(&self, headers: &[BlockHeader], opts: Options) -> Result<(), Error> {
+		self.check_writable()?;
 		let mut sync_pmmr = self.sync_pmmr.write();
 		let mut header_pmmr = self.header_pmmr.write();
 		let mut txhashset = self.txhashset.write();
@@ -718,6 +871,130 @@ impl Chain {
 		self.txhashset.read().get_unspent(output_id)
 	}
 
+	/// Output MMR position of the unspent output with the given commitment, as
+	/// needed to build a merkle proof. Fails with OutputSpent if the output was
+	/// spent, OutputNotFound if it was never seen.
+	pub fn get_output_leaf_pos(&self, commit: &Commitment) -> Result<u64, Error> {
+		let output_id = commit.hash();
+		if let Some((_, pos)) = self.get_unspent(output_id)? {
+			return Ok(pos.pos);
+		}
+		match self.store.get_spent_ids(&output_id) {
+			Ok(Some(_)) => Err(ErrorKind::OutputSpent.into()),
+			Ok(None) => Err(ErrorKind::OutputNotFound(format!(
+				"Not found output {}",
+				commit.to_hex()
+			))
+			.into()),
+			Err(e) => Err(ErrorKind::StoreErr(e, "chain get spent ids".to_owned()).into()),
+		}
+	}
+
+	/// Input commitments of the block that spent the given output. The transactions
+	/// of a block are aggregated, so the spending transaction can't be told apart
+	/// from the rest of its block. Empty if the output is unspent.
+	/// Spends are only indexed within the horizon, fails with OutputAlreadyPruned
+	/// if the spending block has been pruned.
+	pub fn get_inputs_for_output(&self, commit: &Commitment) -> Result<Vec<Commitment>, Error> {
+		if self.get_unspent(commit.hash())?.is_some() {
+			return Ok(vec![]);
+		}
+		let block = self.get_spending_block(commit)?;
+		let inputs: Vec<CommitWrapper> = block.inputs().into();
+		Ok(inputs.iter().map(|input| input.commitment()).collect())
+	}
+
+	/// The block the given output was created in. Unspent outputs are looked up
+	/// in the output index. With include_spent, outputs no longer in the UTXO set
+	/// are looked up through the spent index of the block that spent them, only
+	/// kept within the horizon. Fails with OutputNotFound for an unknown output
+	/// and OutputAlreadyPruned if the block is no longer in the db.
+	pub fn get_block_by_output_commit(
+		&self,
+		commit: &Commitment,
+		include_spent: bool,
+	) -> Result<Block, Error> {
+		let height = match self.get_unspent(commit.hash())? {
+			Some((_, pos)) => pos.height,
+			None if include_spent => self.get_spent_output_height(commit)?,
+			None => {
+				return Err(ErrorKind::OutputNotFound(format!(
+					"Not found unspent output {}",
+					commit.to_hex()
+				))
+				.into())
+			}
+		};
+		let header = self.get_header_by_height(height)?;
+		match self.store.get_block(&header.hash()) {
+			Ok(block) => Ok(block),
+			Err(NotFoundErr(_)) => Err(ErrorKind::OutputAlreadyPruned(format!(
+				"output {} created at height {}",
+				commit.to_hex(),
+				height
+			))
+			.into()),
+			Err(e) => Err(ErrorKind::StoreErr(e, "chain get block".to_owned()).into()),
+		}
+	}
+
+	// Creation height of a spent output, from the spent index of the block
+	// spending it. The spent index follows the order of the block inputs.
+	fn get_spent_output_height(&self, commit: &Commitment) -> Result<u64, Error> {
+		let block = self.get_spending_block(commit)?;
+		let spent_index = self
+			.store
+			.get_spent_index(&block.hash())
+			.map_err(|e| ErrorKind::StoreErr(e, "chain get spent index".to_owned()))?;
+		let inputs: Vec<CommitWrapper> = block.inputs().into();
+		inputs
+			.iter()
+			.position(|input| input.commitment() == *commit)
+			.and_then(|i| spent_index.get(i))
+			.map(|pos| pos.height)
+			.ok_or_else(|| {
+				ErrorKind::OutputNotFound(format!("No spend found for output {}", commit.to_hex()))
+					.into()
+			})
+	}
+
+	// The block spending the given output on our chain.
+	fn get_spending_block(&self, commit: &Commitment) -> Result<Block, Error> {
+		let spent_ids = self
+			.store
+			.get_spent_ids(&commit.hash())
+			.map_err(|e| ErrorKind::StoreErr(e, "chain get spent ids".to_owned()))?
+			.unwrap_or_default();
+
+		// the output might have been spent on forks as well, we want the spend on our chain
+		let spent_by = spent_ids.into_iter().find(|hh| {
+			self.get_header_by_height(hh.height)
+				.map(|header| header.hash() == hh.hash)
+				.unwrap_or(false)
+		});
+		let spent_by = match spent_by {
+			Some(hh) => hh,
+			None => {
+				return Err(ErrorKind::OutputNotFound(format!(
+					"No spend found for output {}",
+					commit.to_hex()
+				))
+				.into())
+			}
+		};
+
+		match self.store.get_block(&spent_by.hash) {
+			Ok(block) => Ok(block),
+			Err(NotFoundErr(_)) => Err(ErrorKind::OutputAlreadyPruned(format!(
+				"output {} spent at height {}",
+				commit.to_hex(),
+				spent_by.height
+			))
+			.into()),
+			Err(e) => Err(ErrorKind::StoreErr(e, "chain get block".to_owned()).into()),
+		}
+	}
+
 	/// Retrieves an unspent output using its PMMR position
 	pub fn get_unspent_output_at(&self, pos: u64) -> Result<Output, Error> {
 		let header_pmmr = self.header_pmmr.read();
@@ -727,6 +1004,32 @@ impl Chain {
 		})
 	}
 
+	/// Retrieves the output at the given leaf position of the output MMR.
+	/// Returns OutputSpent if the leaf has since been spent, OutputAlreadyPruned
+	/// once it's also been pruned from the output MMR by a compaction.
+	pub fn get_output_at_leaf_pos(&self, leaf_pos: u64) -> Result<Output, Error> {
+		let last_pos = self.txhashset.read().highest_output_insertion_index();
+		if leaf_pos == 0 || leaf_pos > last_pos || !pmmr::is_leaf(leaf_pos) {
+			return Err(
+				ErrorKind::OutputNotFound(format!("no output leaf at {}", leaf_pos)).into(),
+			);
+		}
+		self.get_unspent_output_at(leaf_pos)
+			.map_err(|e| match e.kind() {
+				ErrorKind::OutputNotFound(_) => {
+					// spent, still in the data file until the next compaction
+					let txhashset = self.txhashset.read();
+					if txhashset.get_output_commit_from_file(leaf_pos).is_some() {
+						ErrorKind::OutputSpent.into()
+					} else {
+						ErrorKind::OutputAlreadyPruned(format!("output leaf at {}", leaf_pos))
+							.into()
+					}
+				}
+				_ => e,
+			})
+	}
+
 	/// Validate the tx against the current UTXO set and recent kernels (NRD relative lock heights).
 	pub fn validate_tx(&self, tx: &Transaction) -> Result<(), Error> {
 		self.validate_tx_against_utxo(tx)?;
@@ -739,10 +1042,7 @@ impl Chain {
 	/// The extension and the db batch are discarded.
 	/// The batch ensures duplicate NRD kernels within the tx are handled correctly.
 	fn validate_tx_kernels(&self, tx: &Transaction) -> Result<(), Error> {
-		let has_nrd_kernel = tx.kernels().iter().any(|k| match k.features {
-			KernelFeatures::NoRecentDuplicate { .. } => true,
-			_ => false,
-		});
+		let has_nrd_kernel = tx.kernels().iter().any(|k| k.is_nrd());
 		if !has_nrd_kernel {
 			return Ok(());
 		}
@@ -820,12 +1120,60 @@ impl Chain {
 		})
 	}
 
+	/// Simulate a block on top of the head with the provided transactions, in
+	/// order, without mining (or storing) it. Each transaction is checked on its
+	/// own against the UTXO set, for lock heights and coinbase maturity, and
+	/// against the transactions accepted before it (inputs spent twice, block
+	/// weight). Only the valid ones count toward the block weight and fees.
+	pub fn simulate_block(&self, txs: &[Transaction]) -> Result<SimulatedBlockResult, Error> {
+		let max_weight = global::max_block_weight();
+		// the coinbase output and kernel
+		let mut weight = TransactionBody::weight_as_block(0, 1, 1);
+		let mut fees: u64 = 0;
+		let mut spent = HashSet::new();
+		let mut invalid_txs = vec![];
+
+		for (idx, tx) in txs.iter().enumerate() {
+			let tx_weight = tx.body.body_weight_as_block();
+			let inputs = tx.inputs_committed();
+			let res = tx
+				.validate(Weighting::AsTransaction, self.verifier_cache.clone())
+				.map_err(|e| Error::from(ErrorKind::Transaction(e)))
+				.and_then(|_| self.verify_tx_lock_height(tx))
+				.and_then(|_| self.verify_coinbase_maturity(&tx.inputs()))
+				.and_then(|_| self.validate_tx(tx))
+				.and_then(|_| {
+					if inputs.iter().any(|commit| spent.contains(commit)) {
+						Err(ErrorKind::OutputSpent.into())
+					} else if weight.saturating_add(tx_weight) > max_weight {
+						Err(ErrorKind::Other("block weight exceeded".to_owned()).into())
+					} else {
+						Ok(())
+					}
+				});
+			match res {
+				Ok(_) => {
+					weight += tx_weight;
+					fees = fees.saturating_add(tx.fee());
+					spent.extend(inputs);
+				}
+				Err(e) => invalid_txs.push((idx, e.kind())),
+			}
+		}
+
+		Ok(SimulatedBlockResult {
+			weight,
+			fees,
+			invalid_txs,
+		})
+	}
+
 	/// Validate the current chain state.
 	pub fn validate(&self, fast_validation: bool) -> Result<(), Error> {
 		let header = self.store.head_header()?;
 
 		// Lets just treat an "empty" node that just got started up as valid.
-		if header.height == 0 {
+		if header.is_genesis() {
 			return Ok(());
 		}
 
@@ -843,6 +1191,122 @@ impl Chain {
 		})
 	}
 
+	/// Fully validates the blocks of our chain in the height range [start, end] (PoW,
+	/// block body, inputs, outputs, kernels and MMR roots), without validating the rest
+	/// of the chain. Returns the number of blocks validated, stops at the first invalid
+	/// block with InvalidChainSegment reporting its height.
+	pub fn validate_chain_segment(&self, start: u64, end: u64) -> Result<u64, Error> {
+		let mut valid = 0;
+		let mut invalid = None;
+		self.validate_blocks(start, end, |header, res| match res {
+			Ok(_) => {
+				valid += 1;
+				true
+			}
+			Err(e) => {
+				invalid = Some(ErrorKind::InvalidChainSegment(header.height, e.to_string()));
+				false
+			}
+		})?;
+		match invalid {
+			Some(kind) => Err(kind.into()),
+			None => Ok(valid),
+		}
+	}
+
+	/// Fully validates a single block of our chain (PoW, block body, inputs, outputs,
+	/// kernels and MMR roots) against the chain state at its parent.
+	pub fn validate_block(&self, h: &Hash) -> Result<(), Error> {
+		let header = self.get_block_header(h)?;
+		self.is_on_current_chain(&header)?;
+		let mut res = Ok(());
+		self.validate_blocks(header.height, header.height, |_, r| {
+			res = r;
+			false
+		})?;
+		res
+	}
+
+	/// Fully validates the blocks of our chain in the height range [start, end], like
+	/// validate_block, passing the result for each block to `on_block`.
+	/// An invalid block is still applied (it is part of our chain) so the blocks
+	/// after it can be validated, returning false from `on_block` stops early.
+	/// Returns the height of the last block passed to `on_block`, lower than `end`
+	/// if it stopped early or an invalid block couldn't be applied at all.
+	/// Fails if the range is invalid, starts below the tail of a pruned node
+	/// or a block can't be read.
+	pub fn validate_blocks<F>(&self, start: u64, end: u64, mut on_block: F) -> Result<u64, Error>
+	where
+		F: FnMut(&BlockHeader, Result<(), Error>) -> bool,
+	{
+		let head = self.head()?;
+		if start == 0 || start > end || end > head.height {
+			return Err(ErrorKind::Other(format!(
+				"invalid segment {}-{}, expected 0 < start <= end <= {}",
+				start, end, head.height
+			))
+			.into());
+		}
+		if !self.archive_mode {
+			let tail_height = self.tail().map(|t| t.height).unwrap_or(0);
+			if start < tail_height {
+				return Err(ErrorKind::Other(format!(
+					"blocks below height {} are pruned, start from there at least",
+					tail_height
+				))
+				.into());
+			}
+		}
+
+		// header hashes first, the header MMR can't be read once we hold the extension
+		let prev = self.get_header_by_height(start - 1)?;
+		let hashes = (start..=end)
+			.map(|height| self.get_header_hash_by_height(height))
+			.collect::<Result<Vec<_>, _>>()?;
+
+		let mut header_pmmr = self.header_pmmr.write();
+		let mut txhashset = self.txhashset.write();
+		txhashset::extending_readonly(&mut header_pmmr, &mut txhashset, |ext, batch| {
+			pipe::rewind_and_apply_fork(&prev, ext, batch)?;
+			let mut last_height = prev.height;
+			for hash in &hashes {
+				let block = batch
+					.get_block(hash)
+					.map_err(|e| ErrorKind::StoreErr(e, "validate blocks".to_owned()))?;
+				let res = pipe::revalidate_block(
+					&block,
+					ext,
+					batch,
+					self.pow_verifier,
+					self.verifier_cache.clone(),
+				);
+				let failed = res.is_err();
+				last_height = block.header.height;
+				if !on_block(&block.header, res) {
+					break;
+				}
+				// The block may have been partially applied, rewind to its parent and
+				// apply it again without validation to move on. If it can't be applied
+				// the blocks after it can't be validated, stop there.
+				if failed {
+					let prev = batch.get_previous_header(&block.header)?;
+					let applied = ext.extension.rewind(&prev, batch).and_then(|_| {
+						ext.extension
+							.apply_block(&block, ext.header_extension, batch)
+					});
+					if let Err(e) = applied {
+						warn!(
+							"validate blocks: can't apply invalid block {} at {}, stopping, {}",
+							hash, block.header.height, e
+						);
+						break;
+					}
+				}
+			}
+			Ok(last_height)
+		})
+	}
+
 	/// Sets prev_root on a brand new block header by applying the previous header to the header MMR.
 	pub fn set_prev_root_only(&self, header: &mut BlockHeader) -> Result<(), Error> {
 		let mut header_pmmr = self.header_pmmr.write();
@@ -986,7 +1450,7 @@ impl Chain {
 		let mut count = 0;
 		let mut current = header.clone();
 		txhashset::rewindable_kernel_view(&txhashset, |view, batch| {
-			while current.height > 0 {
+			while !current.is_genesis() {
 				view.rewind(&current)?;
 				view.validate_root()?;
 				current = batch.get_previous_header(&current)?;
@@ -1160,6 +1624,7 @@ impl Chain {
 		txhashset_data: File,
 		status: &dyn TxHashsetWriteStatus,
 	) -> Result<bool, Error> {
+		self.check_writable()?;
 		status.on_setup();
 
 		// Initial check whether this txhashset is needed or not
@@ -1341,12 +1806,89 @@ impl Chain {
 		Ok(())
 	}
 
+	/// Rebuild the spent output index (output id -> spending block) from scratch.
+	/// Replays the inputs of every full block we have in the db on the current chain,
+	/// from the tail (genesis for an archive node) up to the head.
+	/// Used to repair a corrupted index without a full resync.
+	/// Returns the number of index entries rebuilt.
+	pub fn reindex_spent_outputs(&self) -> Result<u64, Error> {
+		// Hold the txhashset lock so no block can be processed while we rebuild.
+		let header_pmmr = self.header_pmmr.read();
+		let _txhashset = self.txhashset.write();
+		let batch = self.store.batch()?;
+
+		let removed = batch.clear_spent_ids()?;
+
+		let head = batch.head()?;
+		let tail_height = match batch.tail() {
+			Ok(tail) => tail.height,
+			Err(_) => 0,
+		};
+
+		debug!(
+			"reindex_spent_outputs: removed {} entries, replaying blocks {} to {}",
+			removed, tail_height, head.height
+		);
+
+		let mut count = 0;
+		for height in tail_height..=head.height {
+			let hash = header_pmmr.get_header_hash_by_height(height)?;
+			let block = match batch.get_block(&hash) {
+				Ok(block) => block,
+				Err(NotFoundErr(_)) => continue,
+				Err(e) => {
+					return Err(ErrorKind::StoreErr(e, "reindex spent outputs".to_owned()).into())
+				}
+			};
+			let spent_ids: Vec<Hash> = match block.inputs() {
+				Inputs::CommitOnly(inputs) => inputs.iter().map(|x| x.output_id()).collect(),
+				Inputs::FeaturesAndCommit(inputs) => inputs.iter().map(|x| x.output_id()).collect(),
+			};
+			for id in spent_ids {
+				batch.save_spent_id(&id, HashHeight { hash, height })?;
+				count += 1;
+			}
+		}
+
+		batch.commit()?;
+
+		info!(
+			"reindex_spent_outputs: rebuilt {} spent index entries up to {} at {}",
+			count, head.last_block_h, head.height
+		);
+
+		Ok(count)
+	}
+
+	/// Rebuild the output_pos index (output id -> MMR position and height) from scratch,
+	/// from the current UTXO set and the headers from genesis to the head.
+	/// Used to repair a corrupted index, lookups fall back to slow scans otherwise.
+	/// Returns the number of index entries rebuilt.
+	pub fn rebuild_output_index(&self) -> Result<u64, Error> {
+		// Hold the txhashset lock so no block can be processed while we rebuild.
+		let header_pmmr = self.header_pmmr.read();
+		let txhashset = self.txhashset.write();
+		let batch = self.store.batch()?;
+		let removed = batch.clear_output_pos_index()?;
+		debug!("rebuild_output_index: removed {} entries", removed);
+		let count = txhashset.init_output_pos_index(&header_pmmr, &batch)?;
+		batch.commit()?;
+
+		info!(
+			"rebuild_output_index: rebuilt {} output index entries",
+			count
+		);
+
+		Ok(count)
+	}
+
 	/// Triggers chain compaction.
 	///
 	/// * compacts the txhashset based on current prune_list
 	/// * removes historical blocks and associated data from the db (unless archive mode)
 	///
 	pub fn compact(&self) -> Result<(), Error> {
+		self.check_writable()?;
 		// A node may be restarted multiple times in a short period of time.
 		// We compact at most once per 60 blocks in this situation by comparing
 		// current "head" and "tail" height to our cut-through horizon and
@@ -1364,6 +1906,23 @@ impl Chain {
 			}
 		}
 
+		self.force_compact()
+	}
+
+	/// Triggers chain compaction right away, even if the chain was compacted
+	/// shortly before. Fails with CompactionInProgress if another compaction
+	/// is already running.
+	pub fn force_compact(&self) -> Result<(), Error> {
+		self.check_writable()?;
+		if self.compacting.swap(true, Ordering::SeqCst) {
+			return Err(ErrorKind::CompactionInProgress.into());
+		}
+		// Cleared on drop, so a panic during compaction does not block it forever.
+		let _guard = CompactingGuard(&self.compacting);
+		self.compact_txhashset_and_db()
+	}
+
+	fn compact_txhashset_and_db(&self) -> Result<(), Error> {
 		// Take a write lock on the txhashet and start a new writeable db batch.
 		let header_pmmr = self.header_pmmr.read();
 		let mut txhashset = self.txhashset.write();
@@ -1398,6 +1957,25 @@ impl Chain {
 		Ok(())
 	}
 
+	/// Compact the txhashset up to the provided height (at or below the
+	/// cut-through horizon) to free disk space, see TxHashSet::compact_to_height.
+	/// Blocks and the chain tail are left as they are.
+	/// Not allowed in production mode. Returns the number of bytes freed.
+	pub fn compact_to_height(&self, height: u64) -> Result<u64, Error> {
+		let header_pmmr = self.header_pmmr.read();
+		let mut txhashset = self.txhashset.write();
+		let batch = self.store.batch()?;
+
+		let freed = txhashset.compact_to_height(height, &header_pmmr, &batch)?;
+
+		// Make sure our output_pos index is consistent with the UTXO set.
+		txhashset.init_output_pos_index(&header_pmmr, &batch)?;
+
+		batch.commit()?;
+
+		Ok(freed)
+	}
+
 	/// returns the last n nodes inserted into the output sum tree
 	pub fn get_last_n_output(&self, distance: u64) -> Vec<(Hash, OutputIdentifier)> {
 		self.txhashset.read().last_n_output(distance)
@@ -1418,6 +1996,11 @@ impl Chain {
 		Ok(self.txhashset.read().get_output_pos(&output_id)?)
 	}
 
+	/// Total number of unspent outputs in the UTXO set.
+	pub fn unspent_output_count(&self) -> u64 {
+		self.txhashset.read().unspent_output_count()
+	}
+
 	/// outputs by insertion index
 	pub fn unspent_outputs_by_pmmr_index(
 		&self,
@@ -1471,6 +2054,12 @@ impl Chain {
 		self.orphans.len()
 	}
 
+	/// The last RECENT_ORPHANS_SIZE orphans received (oldest first). Some of them
+	/// may have been processed or evicted from the orphan pool since.
+	pub fn get_recent_orphans(&self) -> Vec<HashHeight> {
+		self.orphans.recent()
+	}
+
 	/// Tip (head) of the block chain.
 	pub fn head(&self) -> Result<Tip, Error> {
 		self.store
@@ -1478,6 +2067,21 @@ impl Chain {
 			.map_err(|e| ErrorKind::StoreErr(e, "chain head".to_owned()).into())
 	}
 
+	/// Hash of the tip (head) of the block chain. Cheaper than head() when only
+	/// the hash is needed: the hash is read straight from the stored tip bytes,
+	/// no Tip (nor header) gets deserialized.
+	pub fn get_tip_hash(&self) -> Result<Hash, Error> {
+		self.store
+			.head_hash()
+			.map_err(|e| ErrorKind::StoreErr(e, "chain head hash".to_owned()).into())
+	}
+
+	/// MMR position (1-based, like every pos in the MMRs) the first kernel of the
+	/// next block will be appended at, i.e. right after the last kernel of the tip.
+	pub fn get_next_kernel_mmr_pos(&self) -> Result<u64, Error> {
+		Ok(self.head_header()?.kernel_mmr_size + 1)
+	}
+
 	/// Tail of the block chain in this node after compact (cross-block cut-through)
 	pub fn tail(&self) -> Result<Tip, Error> {
 		self.store
@@ -1499,6 +2103,12 @@ impl Chain {
 			.map_err(|e| ErrorKind::StoreErr(e, "chain head header".to_owned()).into())
 	}
 
+	/// Summary of the chain head. Everything comes from the head header, so it's
+	/// consistent even if the head moves while we read it.
+	pub fn get_tip_info(&self) -> Result<TipInfo, Error> {
+		Ok(TipInfo::from_header(&self.head_header()?))
+	}
+
 	/// Gets a block by hash
 	pub fn get_block(&self, h: &Hash) -> Result<Block, Error> {
 		self.store
@@ -1506,6 +2116,29 @@ impl Chain {
 			.map_err(|e| ErrorKind::StoreErr(e, "chain get block".to_owned()).into())
 	}
 
+	/// Serialized block, with the local protocol version (as sent to peers).
+	/// Read straight from the db when the db uses that same version, the
+	/// block is deserialized and serialized again otherwise.
+	pub fn get_raw_block_bytes(&self, h: &Hash) -> Result<Vec<u8>, Error> {
+		if self.store.protocol_version() == ProtocolVersion::local() {
+			self.store
+				.get_block_bytes(h)
+				.map_err(|e| ErrorKind::StoreErr(e, "chain get raw block".to_owned()).into())
+		} else {
+			let block = self.get_block(h)?;
+			ser::ser_vec(&block, ProtocolVersion::local()).map_err(|e| ErrorKind::SerErr(e).into())
+		}
+	}
+
+	/// Total miner reward (block subsidy plus fees) for the block with the given hash.
+	/// The coinbase outputs must commit to exactly this amount, which is checked
+	/// against the coinbase kernels before the reward is returned.
+	pub fn get_block_subsidy_plus_fees(&self, h: &Hash) -> Result<u64, Error> {
+		let block = self.get_block(h)?;
+		block.verify_coinbase().map_err(ErrorKind::Block)?;
+		Ok(consensus::reward(block.total_fees(), block.header.height))
+	}
+
 	/// Gets a block header by hash
 	pub fn get_block_header(&self, h: &Hash) -> Result<BlockHeader, Error> {
 		self.store
@@ -1513,6 +2146,13 @@ impl Chain {
 			.map_err(|e| ErrorKind::StoreErr(e, "chain get header".to_owned()).into())
 	}
 
+	/// Timestamp of the block with the given hash, in seconds since the UNIX epoch.
+	/// Header timestamps are always UTC. The header db has no partial reads so
+	/// this costs a header read, callers just don't have to deal with the header.
+	pub fn get_block_timestamp(&self, h: &Hash) -> Result<i64, Error> {
+		Ok(self.get_block_header(h)?.timestamp.timestamp())
+	}
+
 	/// Get previous block header.
 	pub fn get_previous_header(&self, header: &BlockHeader) -> Result<BlockHeader, Error> {
 		self.store
@@ -1520,6 +2160,21 @@ impl Chain {
 			.map_err(|e| ErrorKind::StoreErr(e, "chain get previous header".to_owned()).into())
 	}
 
+	/// Accumulated proof of work up to and including the given header, as the
+	/// number of siphash bits the miners are expected to have computed:
+	///
+	///   work = total_difficulty << base_edge_bits
+	///
+	/// Block difficulty is already scaled by consensus::graph_weight, which is
+	/// 2^(edge_bits - base_edge_bits + 1) * edge_bits for the graph the block
+	/// was mined on, so the shift turns each block's contribution into
+	/// difficulty * 2^(edge_bits + 1) * edge_bits and keeps the result
+	/// comparable across PoW variants. Only reads the given header.
+	pub fn get_header_chain_work(&self, h: &Hash) -> Result<u128, Error> {
+		let header = self.get_block_header(h)?;
+		Ok((header.total_difficulty().to_num() as u128) << global::base_edge_bits())
+	}
+
 	/// Get block_sums by header hash.
 	pub fn get_block_sums(&self, h: &Hash) -> Result<BlockSums, Error> {
 		self.store
@@ -1527,6 +2182,48 @@ impl Chain {
 			.map_err(|e| ErrorKind::StoreErr(e, "chain get block_sums".to_owned()).into())
 	}
 
+	/// Pedersen sum of the excesses of all the kernels as of the chain head,
+	/// kernels are never pruned. Along with the total kernel offset it must
+	/// match the sum of the UTXO commitments minus the coinbase overage.
+	/// Returns the head the sum was computed at.
+	/// Note: Takes a read lock on the txhashset.
+	pub fn get_kernel_excess_sum(&self) -> Result<(Tip, Commitment), Error> {
+		let txhashset = self.txhashset.read();
+		// The head only moves with the txhashset write lock held.
+		let head = self.head()?;
+		let kernel_sum = txhashset.kernel_excess_sum()?;
+		Ok((head, kernel_sum))
+	}
+
+	/// Global balance audit, the full kernel sums validation of the txhashset
+	/// as of the chain head. Returns the head header along with the
+	/// (utxo sum minus overage, kernel sum), or None if the sums do not match.
+	pub fn audit_balance(&self) -> Result<(BlockHeader, Option<(Commitment, Commitment)>), Error> {
+		let mut header_pmmr = self.header_pmmr.write();
+		let mut txhashset = self.txhashset.write();
+		txhashset::extending_readonly(&mut header_pmmr, &mut txhashset, |ext, batch| {
+			let header = batch.get_block_header(&ext.extension.head().last_block_h)?;
+			let sums = match ext.extension.validate_kernel_sums(&self.genesis, &header) {
+				Ok(sums) => Some(sums),
+				Err(e) => match e.kind() {
+					ErrorKind::Committed(committed::Error::KernelSumMismatch) => None,
+					_ => return Err(e),
+				},
+			};
+			Ok((header, sums))
+		})
+	}
+
+	/// Gets a block by hash as JSON, serialized straight from the block itself.
+	/// Meant for very large blocks, where building the printable (api) types
+	/// would allocate a lot of intermediate data.
+	pub fn get_block_json(&self, h: &Hash) -> Result<serde_json::Value, Error> {
+		let block = self.get_block(h)?;
+		serde_json::to_value(&block).map_err(|e| {
+			ErrorKind::Other(format!("failed to serialize block {} to json, {}", h, e)).into()
+		})
+	}
+
 	/// Gets the block header at the provided height.
 	/// Note: Takes a read lock on the header_pmmr.
 	pub fn get_header_by_height(&self, height: u64) -> Result<BlockHeader, Error> {
@@ -1540,6 +2237,21 @@ impl Chain {
 		self.header_pmmr.read().get_header_hash_by_height(height)
 	}
 
+	/// Older db versions are migrated in place on startup, a db with a version we
+	/// don't know about has to be migrated (or downgraded) before we can use it.
+	fn check_db_version(store: &ChainStore) -> Result<(), Error> {
+		match store.db_version()? {
+			Some(version) if version > store::CHAIN_DB_VERSION => {
+				Err(ErrorKind::DatabaseMigrationRequired {
+					current_version: version,
+					required_version: store::CHAIN_DB_VERSION,
+				}
+				.into())
+			}
+			_ => Ok(()),
+		}
+	}
+
 	/// Migrate our local db from v2 to v3.
 	/// "commit only" inputs.
 	fn migrate_db_v2_v3(store: &ChainStore) -> Result<(), Error> {
@@ -1583,8 +2295,32 @@ impl Chain {
 				.into())
 			}
 		};
+
+		// The header chain can be on a different fork than the txhashset when a
+		// header with more work was received ahead of its block. The header MMR
+		// can only be trusted up to the fork point, where both still agree, so walk
+		// back the body chain down to it. The head is only updated with the
+		// txhashset write lock held, we hold the read lock.
+		let head = self.head()?;
+		let mut header = self.get_block_header(&head.last_block_h)?;
+		while header.height > pos.height {
+			match header_pmmr.get_header_hash_by_height(header.height) {
+				Ok(hash) if hash == header.hash() => break,
+				_ => header = self.get_previous_header(&header)?,
+			}
+		}
+		if header.height == pos.height {
+			return Ok(header);
+		}
 		let hash = header_pmmr.get_header_hash_by_height(pos.height)?;
-		Ok(self.get_block_header(&hash)?)
+		self.get_block_header(&hash)
+	}
+
+	/// Gets the header of the block the unspent output with the given commitment
+	/// was created in, whatever its features (coinbase or plain). Only the headers
+	/// are used, so this works on non-archive nodes for pruned blocks too.
+	pub fn get_block_header_by_commit(&self, commit: &Commitment) -> Result<BlockHeader, Error> {
+		self.get_header_for_output(commit.hash())
 	}
 
 	/// Gets the kernel with a given excess and the block height it is included in.
@@ -1640,6 +2376,29 @@ impl Chain {
 
 		Ok(Some((kernel, header.height, mmr_index)))
 	}
+
+	/// Height of the block the kernel with the given excess was committed in.
+	/// Fails with TxKernelNotFound if there is no such kernel on the chain.
+	pub fn get_kernel_block_height(&self, excess: &Commitment) -> Result<u64, Error> {
+		match self.get_kernel_height(excess, None, None)? {
+			Some((_, height, _)) => Ok(height),
+			None => Err(ErrorKind::TxKernelNotFound.into()),
+		}
+	}
+
+	/// Lock height of the kernel with the given excess, None for kernels that
+	/// aren't height locked. Fails with TxKernelNotFound if there is no such kernel
+	/// on the chain (see TransactionPool::retrieve_kernel for the mempool).
+	pub fn get_kernel_lock_height(&self, excess: &Commitment) -> Result<Option<u64>, Error> {
+		match self.get_kernel_height(excess, None, None)? {
+			Some((kernel, _, _)) => match kernel.features {
+				KernelFeatures::HeightLocked { lock_height, .. } => Ok(Some(lock_height)),
+				_ => Ok(None),
+			},
+			None => Err(ErrorKind::TxKernelNotFound.into()),
+		}
+	}
+
 	/// Gets the block header in which a given kernel mmr index appears in the txhashset.
 	pub fn get_header_for_kernel_index(
 		&self,
@@ -1716,6 +2475,323 @@ impl Chain {
 		Ok(store::DifficultyIter::from(head.last_block_h, store))
 	}
 
+	/// Sum of the difficulties of the last 5 blocks. How far our total difficulty
+	/// can lag behind the best known one before we consider ourselves out of sync.
+	pub fn sync_difficulty_threshold(&self) -> Result<pow::Difficulty, Error> {
+		Ok(self
+			.difficulty_iter()?
+			.map(|x| x.difficulty)
+			.take(5)
+			.fold(pow::Difficulty::zero(), |sum, val| sum + val))
+	}
+
+	/// Best header known from our peers, the one of our header head as we sync
+	/// headers from the most work peers. None if it isn't ahead of our chain head.
+	pub fn get_peer_best_header(&self) -> Result<Option<BlockHeader>, Error> {
+		let head = self.head()?;
+		let header_head = self.header_head()?;
+		if header_head.total_difficulty > head.total_difficulty {
+			Ok(Some(self.get_block_header(&header_head.last_block_h)?))
+		} else {
+			Ok(None)
+		}
+	}
+
+	/// Whether our chain head is significantly (see sync_difficulty_threshold)
+	/// behind the best header known from our peers (see get_peer_best_header).
+	/// Doesn't need the sync state so usable anywhere we have the chain.
+	/// Assume syncing if the chain can't be read.
+	pub fn is_syncing(&self) -> bool {
+		let lagging = || -> Result<bool, Error> {
+			let peer_best = match self.get_peer_best_header()? {
+				Some(header) => header,
+				None => return Ok(false),
+			};
+			let head = self.head()?;
+			let threshold = self.sync_difficulty_threshold()?;
+			Ok(peer_best.total_difficulty() > head.total_difficulty + threshold)
+		};
+		lagging().unwrap_or(true)
+	}
+
+	/// Iterate over the commitments spent by the blocks from the provided height
+	/// up to the current head, in ascending height order. The spent index is read
+	/// in pages of SPENT_COMMITMENTS_PAGE_SIZE commitments to keep memory usage
+	/// bounded. Iteration stops after the first error.
+	/// Fails on a non-archival node if the height is below the tail (blocks pruned).
+	pub fn get_all_spent_commitments_since(
+		&self,
+		height: u64,
+	) -> Result<impl Iterator<Item = Result<Commitment, Error>> + '_, Error> {
+		let head = self.head()?;
+		if !self.archive_mode {
+			let tail_height = self.tail().map(|t| t.height).unwrap_or(0);
+			if height < tail_height {
+				return Err(ErrorKind::Other(format!(
+					"height {} is below the horizon, blocks are pruned up to height {}",
+					height, tail_height
+				))
+				.into());
+			}
+		}
+		Ok(SpentCommitmentIter {
+			chain: self,
+			next_height: height,
+			head_height: head.height,
+			page: VecDeque::new(),
+		})
+	}
+
+	/// Full blocks from the checkpoint (included) towards the current head, in
+	/// ascending height order, at most `max_blocks` (capped at
+	/// MAX_BLOCKS_SINCE_CHECKPOINT) of them. The next page starts at the last
+	/// block returned. Archival nodes only, the others don't have the older blocks
+	/// (ArchivalNodeRequired). Fails with InvalidHash if the checkpoint isn't on
+	/// the current chain.
+	pub fn get_blocks_since_checkpoint(
+		&self,
+		checkpoint_hash: &Hash,
+		max_blocks: u64,
+	) -> Result<Vec<Block>, Error> {
+		if !self.archive_mode {
+			return Err(ErrorKind::ArchivalNodeRequired.into());
+		}
+		let checkpoint = self
+			.get_block_header(checkpoint_hash)
+			.map_err(|_| ErrorKind::InvalidHash)?;
+		if self.is_on_current_chain(&checkpoint).is_err() {
+			return Err(ErrorKind::InvalidHash.into());
+		}
+		let max_blocks = cmp::min(max_blocks, MAX_BLOCKS_SINCE_CHECKPOINT);
+		if max_blocks == 0 {
+			return Ok(vec![]);
+		}
+		let head = self.head()?;
+		let end = cmp::min(head.height, checkpoint.height + max_blocks - 1);
+		(checkpoint.height..=end)
+			.map(|height| {
+				let hash = self.get_header_hash_by_height(height)?;
+				self.get_block(&hash)
+			})
+			.collect()
+	}
+
+	/// Age distribution of the current UTXO set, as (epoch start height, number
+	/// of unspent outputs created in that epoch) for epochs of
+	/// DIFFICULTY_ADJUST_WINDOW blocks. Epochs are mapped to outputs through the
+	/// output MMR size committed in the headers.
+	pub fn get_utxo_age_distribution(&self) -> Result<Vec<(u64, u64)>, Error> {
+		let head = self.head()?;
+		let epoch_len = consensus::DIFFICULTY_ADJUST_WINDOW;
+
+		let mut epoch_heights = vec![];
+		let mut mmr_sizes = vec![];
+		let mut start_height = 0;
+		while start_height <= head.height {
+			let end_height = (start_height + epoch_len - 1).min(head.height);
+			let header = self.get_header_by_height(end_height)?;
+			epoch_heights.push(start_height);
+			mmr_sizes.push(header.output_mmr_size);
+			start_height += epoch_len;
+		}
+
+		// Outputs added after we read the head fall beyond the last size and are skipped.
+		let counts = self
+			.txhashset
+			.read()
+			.count_unspent_outputs_by_mmr_size(&mmr_sizes);
+		Ok(epoch_heights.into_iter().zip(counts).collect())
+	}
+
+	/// Size statistics of the last BLOCK_SIZE_STATS_WINDOW full blocks (serialized
+	/// with the local protocol version), from the sizes recorded when the blocks
+	/// were processed. Cached until the chain head changes.
+	/// Pruned blocks and blocks restored from a txhashset archive are not known.
+	pub fn get_block_size_stats(&self) -> Result<BlockSizeStats, Error> {
+		let head = self.head()?;
+		if let Some((hash, stats)) = *self.block_size_stats.read() {
+			if hash == head.last_block_h {
+				return Ok(stats);
+			}
+		}
+
+		let mut sizes = Vec::with_capacity(BLOCK_SIZE_STATS_WINDOW as usize);
+		let start_height = head.height.saturating_sub(BLOCK_SIZE_STATS_WINDOW - 1);
+		for height in start_height..=head.height {
+			let hash = self.get_header_hash_by_height(height)?;
+			match self.store.get_block_size(&hash) {
+				Ok(size) => sizes.push(size),
+				// Size not known, not part of the stats.
+				Err(NotFoundErr(_)) => continue,
+				Err(e) => {
+					return Err(ErrorKind::StoreErr(e, "chain get block size".to_owned()).into())
+				}
+			}
+		}
+
+		let stats = BlockSizeStats::from_sizes(sizes);
+		*self.block_size_stats.write() = Some((head.last_block_h, stats));
+		Ok(stats)
+	}
+
+	/// The n largest blocks of the current chain by serialized size (with the
+	/// local protocol version), largest first, as (hash, size) pairs.
+	/// At most MAX_LARGEST_BLOCKS are returned.
+	/// Pruned blocks and blocks restored from a txhashset archive are not known.
+	pub fn get_n_largest_blocks(&self, n: usize) -> Result<Vec<(Hash, u64)>, Error> {
+		let n = cmp::min(n, MAX_LARGEST_BLOCKS);
+		if n == 0 {
+			return Ok(vec![]);
+		}
+		let sizes = self
+			.store
+			.block_sizes_iter()
+			.map_err(|e| ErrorKind::StoreErr(e, "chain get block sizes".to_owned()))?;
+
+		// Min-heap of the n largest blocks seen so far, smallest on top.
+		let mut largest: BinaryHeap<Reverse<(u64, Hash)>> = BinaryHeap::with_capacity(n + 1);
+		for (hash, size) in sizes {
+			if largest.len() >= n {
+				if let Some(Reverse((min_size, _))) = largest.peek() {
+					if size <= *min_size {
+						continue;
+					}
+				}
+			}
+			// skip the blocks on a fork
+			let header = self.get_block_header(&hash)?;
+			if self.is_on_current_chain(&header).is_err() {
+				continue;
+			}
+			largest.push(Reverse((size, hash)));
+			if largest.len() > n {
+				largest.pop();
+			}
+		}
+
+		let mut res: Vec<(Hash, u64)> = largest
+			.into_iter()
+			.map(|Reverse((size, hash))| (hash, size))
+			.collect();
+		res.sort_by(|a, b| b.1.cmp(&a.1));
+		Ok(res)
+	}
+
+	/// Time between the block timestamp and the reception of the full block by
+	/// this node. Zero if the block timestamp is ahead of our clock.
+	/// Only known for the last PROPAGATION_STATS_WINDOW blocks received (or mined)
+	/// by this node on top of its chain head, not for blocks received during sync
+	/// or restored from a txhashset archive.
+	pub fn get_block_propagation_time(&self, h: &Hash) -> Result<Duration, Error> {
+		let header = self.get_block_header(h)?;
+		let received_ms = self
+			.store
+			.get_block_received_time(h)
+			.map_err(|e| ErrorKind::StoreErr(e, "chain get block received time".to_owned()))?;
+		let delay_ms = received_ms - header.timestamp.timestamp_millis();
+		Ok(Duration::from_millis(delay_ms.max(0) as u64))
+	}
+
+	/// Account for the block being relayed to the provided number of peers.
+	pub fn record_block_relay(&self, h: &Hash, peer_count: u64) -> Result<(), Error> {
+		let batch = self.store.batch()?;
+		let count = match batch.get_block_relay_count(h) {
+			Ok(count) => count,
+			Err(NotFoundErr(_)) => 0,
+			Err(e) => return Err(ErrorKind::StoreErr(e, "chain get relay count".to_owned()).into()),
+		};
+		batch.save_block_relay_count(h, count + peer_count)?;
+		batch.commit()?;
+		Ok(())
+	}
+
+	/// Number of peers this node relayed the block to, zero if it never did
+	/// (e.g. blocks received during sync are not relayed).
+	pub fn get_block_relay_count(&self, h: &Hash) -> Result<u64, Error> {
+		match self.store.get_block_relay_count(h) {
+			Ok(count) => Ok(count),
+			Err(NotFoundErr(_)) => {
+				// make sure we know about this block at all
+				self.get_block_header(h)?;
+				Ok(0)
+			}
+			Err(e) => Err(ErrorKind::StoreErr(e, "chain get relay count".to_owned()).into()),
+		}
+	}
+
+	/// Min/avg/max propagation time of the last PROPAGATION_STATS_WINDOW blocks.
+	/// Blocks without a known reception time are skipped.
+	pub fn get_block_propagation_stats(&self) -> Result<PropagationStats, Error> {
+		let head = self.head()?;
+		let mut times = Vec::with_capacity(PROPAGATION_STATS_WINDOW as usize);
+		let start_height = head.height.saturating_sub(PROPAGATION_STATS_WINDOW - 1);
+		for height in start_height..=head.height {
+			let hash = self.get_header_hash_by_height(height)?;
+			match self.get_block_propagation_time(&hash) {
+				Ok(time) => times.push(time.as_millis() as u64),
+				Err(e) => match e.kind() {
+					ErrorKind::StoreErr(NotFoundErr(_), _) => continue,
+					_ => return Err(e),
+				},
+			}
+		}
+		Ok(PropagationStats::from_times(&times))
+	}
+
+	/// Number of blocks per header version over the last DIFFICULTY_ADJUST_WINDOW
+	/// blocks, to monitor the readiness of the network for an upcoming hard fork.
+	pub fn get_block_version_distribution(&self) -> Result<HashMap<u16, u64>, Error> {
+		let mut distribution = HashMap::new();
+		let mut header = self.head_header()?;
+		for _ in 0..consensus::DIFFICULTY_ADJUST_WINDOW {
+			*distribution.entry(header.version.0).or_insert(0) += 1;
+			if header.is_genesis() {
+				break;
+			}
+			header = self.get_previous_header(&header)?;
+		}
+		Ok(distribution)
+	}
+
+	/// Disk space used by the chain db and the txhashset (including the header MMR).
+	/// The peer db is owned by p2p, callers can account for it with DiskUsage::with_peer_db.
+	pub fn estimate_disk_usage(&self) -> Result<DiskUsage, Error> {
+		let block_db_bytes = self.store.disk_size()?;
+		let header_pmmr = self.header_pmmr.read();
+		let txhashset = self.txhashset.read();
+		let txhashset_bytes = header_pmmr.backend.disk_size()? + txhashset.disk_size()?;
+		Ok(DiskUsage::new(block_db_bytes, txhashset_bytes))
+	}
+
+	/// Hash identifying a block template before its proof of work is found
+	/// (the header hash only commits to the proof, so it can't be used until the
+	/// block is solved). Preimage hash of the header with the nonce zeroed.
+	pub fn get_block_template_hash(&self, template: &Block) -> Hash {
+		let mut header = template.header.clone();
+		header.pow.nonce = 0;
+		header.preimage_hash()
+	}
+
+	/// Difficulty actually achieved by the proof of work of the given block
+	/// (derived from the proof hash, same scaling as the header difficulty).
+	/// The proof itself is verified first, fails with InvalidPow if it doesn't verify.
+	pub fn get_block_difficulty_actual(&self, h: &Hash) -> Result<u64, Error> {
+		let header = self.get_block_header(h)?;
+		if (self.pow_verifier)(&header).is_err() {
+			return Err(ErrorKind::InvalidPow.into());
+		}
+		Ok(header.pow.to_difficulty(header.height).to_num())
+	}
+
+	/// Expected difficulty of the next block on top of the current head.
+	/// Applies the difficulty adjustment algorithm over the last
+	/// DIFFICULTY_ADJUST_WINDOW headers, same as block template construction.
+	pub fn estimate_next_difficulty(&self) -> Result<pow::Difficulty, Error> {
+		let head = self.head()?;
+		let next_header_info = consensus::next_difficulty(head.height + 1, self.difficulty_iter()?);
+		Ok(next_header_info.difficulty)
+	}
+
 	/// Check whether we have a block without reading it
 	pub fn block_exists(&self, h: Hash) -> Result<bool, Error> {
 		self.store
@@ -1875,3 +2951,68 @@ fn setup_head(
 	batch.commit()?;
 	Ok(())
 }
+
+/// Iterator over the commitments spent by a range of blocks, see
+/// Chain::get_all_spent_commitments_since.
+struct SpentCommitmentIter<'a> {
+	chain: &'a Chain,
+	next_height: u64,
+	head_height: u64,
+	page: VecDeque<Commitment>,
+}
+
+impl<'a> SpentCommitmentIter<'a> {
+	fn page_size() -> usize {
+		if global::is_production_mode() {
+			SPENT_COMMITMENTS_PAGE_SIZE
+		} else {
+			TESTING_SPENT_COMMITMENTS_PAGE_SIZE
+		}
+	}
+
+	/// Commitments spent by the block at the provided height, from the spent
+	/// index and the output MMR. Falls back to the block inputs for the outputs
+	/// already compacted away (archival nodes still have the blocks).
+	fn spent_commitments(&self, height: u64) -> Result<Vec<Commitment>, Error> {
+		let chain = self.chain;
+		let hash = chain.get_header_hash_by_height(height)?;
+		let spent = chain
+			.store
+			.get_spent_index(&hash)
+			.map_err(|e| ErrorKind::StoreErr(e, "chain get spent index".to_owned()))?;
+		let commits: Option<Vec<Commitment>> = {
+			let txhashset = chain.txhashset.read();
+			spent
+				.iter()
+				.map(|pos| txhashset.get_output_commit_from_file(pos.pos))
+				.collect()
+		};
+		match commits {
+			Some(commits) => Ok(commits),
+			None => Ok(chain.get_block(&hash)?.inputs_committed()),
+		}
+	}
+
+	fn load_page(&mut self) -> Result<(), Error> {
+		while self.page.len() < Self::page_size() && self.next_height <= self.head_height {
+			let commits = self.spent_commitments(self.next_height)?;
+			self.page.extend(commits);
+			self.next_height += 1;
+		}
+		Ok(())
+	}
+}
+
+impl<'a> Iterator for SpentCommitmentIter<'a> {
+	type Item = Result<Commitment, Error>;
+
+	fn next(&mut self) -> Option<Result<Commitment, Error>> {
+		if self.page.is_empty() {
+			if let Err(e) = self.load_page() {
+				self.next_height = self.head_height + 1;
+				return Some(Err(e));
+			}
+		}
+		self.page.pop_front().map(Ok)
+	}
+}
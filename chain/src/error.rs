@@ -104,6 +104,12 @@ pub enum ErrorKind {
 	/// output spent
 	#[fail(display = "Output is spent")]
 	OutputSpent,
+	/// A block failed validation while validating a segment of the chain
+	#[fail(display = "Invalid block at height {}, {}", _0, _1)]
+	InvalidChainSegment(u64, String),
+	/// The block spending the output is below the horizon and has been pruned
+	#[fail(display = "Output already pruned, {}", _0)]
+	OutputAlreadyPruned(String),
 	/// Invalid block version, either a mistake or outdated software
 	#[fail(display = "Invalid Block Version: {:?}", _0)]
 	InvalidBlockVersion(block::HeaderVersion),
@@ -155,6 +161,39 @@ pub enum ErrorKind {
 	/// Error during chain sync
 	#[fail(display = "Sync error")]
 	SyncError(String),
+	/// Peer is banned, its events are not processed
+	#[fail(display = "Peer {} banned", _0)]
+	PeerBanned(String),
+	/// Peer refused because the connection limit is reached
+	#[fail(display = "Peer {} refused, connection limit reached", _0)]
+	PeerConnectionLimit(String),
+	/// The chain was opened read-only, nothing can be written to it
+	#[fail(display = "Chain is read-only")]
+	ReadOnly,
+	/// Another compaction of the chain is running
+	#[fail(display = "Chain compaction already in progress")]
+	CompactionInProgress,
+	/// Full blocks this node doesn't keep, only archival nodes have them
+	#[fail(display = "Only available on archival nodes")]
+	ArchivalNodeRequired,
+	/// Hole in a chunk of synced headers, the header at this height is missing or
+	/// doesn't follow the previous one. Headers from there on need to be requested again.
+	#[fail(display = "Header chain discontinuity at height {}", at_height)]
+	HeaderChainDiscontinuity {
+		/// Height of the first header that doesn't connect to the chain
+		at_height: u64,
+	},
+	/// The chain db was written by a newer version of the node and can't be used as is
+	#[fail(
+		display = "Chain db version {} is newer than version {} supported by this node, upgrade the node or resync the chain",
+		current_version, required_version
+	)]
+	DatabaseMigrationRequired {
+		/// Version of the chain db on disk
+		current_version: u32,
+		/// Version of the chain db this node works with
+		required_version: u32,
+	},
 }
 
 impl Display for Error {
@@ -199,10 +238,39 @@ impl Error {
 			| ErrorKind::SerErr(_)
 			| ErrorKind::TxHashSetErr(_)
 			| ErrorKind::GenesisBlockRequired
+			| ErrorKind::DatabaseMigrationRequired { .. }
+			| ErrorKind::SyncError(_)
+			| ErrorKind::Bitmap
+			| ErrorKind::PeerBanned(_)
+			| ErrorKind::PeerConnectionLimit(_)
+			| ErrorKind::ReadOnly
+			| ErrorKind::CompactionInProgress
+			| ErrorKind::ArchivalNodeRequired
+			| ErrorKind::HeaderChainDiscontinuity { .. }
 			| ErrorKind::Other(_) => false,
 			_ => true,
 		}
 	}
+
+	/// HTTP status code the API serves this error with. 404 for data we don't
+	/// have, 400 for invalid data sent to us, 503 while a compaction is already
+	/// running or the chain is stopping, 500 for everything else.
+	/// A plain code, the chain doesn't depend on http.
+	pub fn to_api_status_code(&self) -> u16 {
+		match self.kind() {
+			ErrorKind::OutputNotFound(_)
+			| ErrorKind::OutputSpent
+			| ErrorKind::OutputAlreadyPruned(_)
+			| ErrorKind::RangeproofNotFound(_)
+			| ErrorKind::TxKernelNotFound
+			| ErrorKind::ArchivalNodeRequired
+			| ErrorKind::StoreErr(store::Error::NotFoundErr(_), _) => 404,
+			ErrorKind::InvalidPow | ErrorKind::AlreadySpent(_) => 400,
+			ErrorKind::ReadOnly | ErrorKind::StoreErr(store::Error::ReadOnly(_), _) => 403,
+			ErrorKind::CompactionInProgress | ErrorKind::Stopped => 503,
+			_ => 500,
+		}
+	}
 }
 
 impl From<ErrorKind> for Error {
@@ -48,10 +48,14 @@ pub mod types;
 
 // Re-export the base interface
 
-pub use crate::chain::{Chain, BLOCK_TO_BAN, MAX_ORPHAN_SIZE};
+pub use crate::chain::{
+	Chain, BLOCK_TO_BAN, MAX_BLOCKS_SINCE_CHECKPOINT, MAX_LARGEST_BLOCKS, MAX_ORPHAN_SIZE,
+	RECENT_ORPHANS_SIZE,
+};
 pub use crate::error::{Error, ErrorKind};
 pub use crate::store::ChainStore;
 pub use crate::types::{
-	BlockStatus, ChainAdapter, Options, SyncState, SyncStatus, Tip, TxHashsetDownloadStats,
+	BlockSizeStats, BlockStatus, ChainAdapter, DiskUsage, Options, PropagationStats,
+	SimulatedBlockResult, SyncState, SyncStatus, Tip, TipInfo, TxHashsetDownloadStats,
 	TxHashsetWriteStatus,
 };
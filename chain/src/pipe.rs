@@ -14,20 +14,21 @@
 
 //! Implementation of the chain block acceptance (or refusal) pipeline.
 
+use crate::chain::PROPAGATION_STATS_WINDOW;
 use crate::core::consensus;
 use crate::core::core::hash::Hashed;
 use crate::core::core::verifier_cache::VerifierCache;
 use crate::core::core::Committed;
-use crate::core::core::{
-	block, Block, BlockHeader, BlockSums, HeaderVersion, OutputIdentifier, TransactionBody,
-};
+use crate::core::core::{block, Block, BlockHeader, BlockSums, OutputIdentifier, TransactionBody};
 use crate::core::global;
 use crate::core::pow;
+use crate::core::ser::{self, ProtocolVersion};
 use crate::error::{Error, ErrorKind};
 use crate::store;
 use crate::txhashset;
 use crate::types::{CommitPos, Options, Tip};
 use crate::util::RwLock;
+use chrono::Utc;
 use grin_core::core::hash::Hash;
 use std::collections::HashSet;
 use std::iter::FromIterator;
@@ -245,6 +246,12 @@ pub fn process_block(
 	// We want to save the block to the db regardless.
 	add_block(b, &ctx.batch)?;
 
+	// Only blocks received on top of our chain head tell us how fast blocks
+	// propagate, not the ones received during sync or on a fork.
+	if !ctx.opts.contains(Options::SYNC) && b.header.prev_hash == head.last_block_h {
+		save_received_time(b, ctx)?;
+	}
+
 	// If we have no "tail" then set it now.
 	if ctx.batch.tail().is_err() {
 		update_body_tail(&b.header, &ctx.batch)?;
@@ -267,7 +274,7 @@ pub fn replay_attack_check(
 	ext: &txhashset::ExtensionPair<'_>,
 	batch: &store::Batch<'_>,
 ) -> Result<(), Error> {
-	if b.header.version >= HeaderVersion(3) {
+	if !b.header.is_before_hf(2) {
 		check_against_spent_output(
 			&b.body,
 			Some(fork_point_height),
@@ -305,6 +312,10 @@ pub fn sync_block_headers(
 		}
 	}
 
+	// Make sure the chunk connects to a header we know and has no holes in it,
+	// so a gap can be re-requested instead of failing the whole chunk as invalid.
+	check_header_continuity(headers, &ctx.batch)?;
+
 	// Validate each header in the chunk and add to our db.
 	// Note: This batch may be rolled back later if the MMR does not validate successfully.
 	for header in headers {
@@ -417,6 +428,36 @@ fn prev_header_store(
 	Ok(prev)
 }
 
+/// Check a chunk of synced headers forms a single hash linked sequence on top of a
+/// header already in the db. Reports the height of the first header that doesn't connect.
+fn check_header_continuity(headers: &[BlockHeader], batch: &store::Batch<'_>) -> Result<(), Error> {
+	let first = match headers.first() {
+		Some(first) => first,
+		None => return Ok(()),
+	};
+	match batch.get_previous_header(first) {
+		Ok(_) => {}
+		Err(grin_store::Error::NotFoundErr(_)) => {
+			return Err(ErrorKind::HeaderChainDiscontinuity {
+				at_height: first.height,
+			}
+			.into());
+		}
+		Err(e) => return Err(e.into()),
+	}
+	// Heights are checked against the previous header by validate_header.
+	for pair in headers.windows(2) {
+		let (prev, header) = (&pair[0], &pair[1]);
+		if header.prev_hash != prev.hash() {
+			return Err(ErrorKind::HeaderChainDiscontinuity {
+				at_height: header.height,
+			}
+			.into());
+		}
+	}
+	Ok(())
+}
+
 fn check_bad_header(header: &BlockHeader) -> Result<(), Error> {
 	let bad_hashes = [Hash::from_hex(
 		"00020440a401086e57e1b7a92ebb0277c7f7fd47a38269ecc6789c2a80333725",
@@ -510,7 +551,11 @@ fn validate_header(header: &BlockHeader, ctx: &mut BlockContext<'_>) -> Result<(
 			return Err(ErrorKind::WrongTotalDifficulty.into());
 		}
 		// check the secondary PoW scaling factor if applicable
-		if header.pow.secondary_scaling != next_header_info.secondary_scaling {
+		if !consensus::verify_secondary_scaling(
+			header.height,
+			header.pow.secondary_scaling,
+			next_header_info.secondary_scaling,
+		) {
 			info!(
 				"validate_header: header secondary scaling {} != {}",
 				header.pow.secondary_scaling, next_header_info.secondary_scaling
@@ -590,6 +635,21 @@ fn apply_block_to_txhashset(
 /// Header must be added separately (assume this has been done previously).
 fn add_block(b: &Block, batch: &store::Batch<'_>) -> Result<(), Error> {
 	batch.save_block(b)?;
+	let size = ser::ser_size(b, ProtocolVersion::local())?;
+	batch.save_block_size(&b.hash(), size)?;
+	Ok(())
+}
+
+/// Record the time the block was received at, pruning the reception time of the
+/// block falling out of the propagation stats window.
+fn save_received_time(b: &Block, ctx: &BlockContext<'_>) -> Result<(), Error> {
+	ctx.batch
+		.save_block_received_time(&b.hash(), Utc::now().timestamp_millis())?;
+	if b.header.height >= PROPAGATION_STATS_WINDOW {
+		let height = b.header.height - PROPAGATION_STATS_WINDOW;
+		let hash = ctx.header_pmmr.get_header_hash_by_height(height)?;
+		let _ = ctx.batch.delete_block_received_time(&hash);
+	}
 	Ok(())
 }
 
@@ -647,7 +707,7 @@ pub fn rewind_and_apply_header_fork(
 ) -> Result<(), Error> {
 	let mut fork_hashes = vec![];
 	let mut current = header.clone();
-	while current.height > 0 && ext.is_on_current_chain(&current, batch).is_err() {
+	while !current.is_genesis() && ext.is_on_current_chain(&current, batch).is_err() {
 		fork_hashes.push(current.hash());
 		current = batch.get_previous_header(&current)?;
 	}
@@ -688,7 +748,7 @@ pub fn rewind_and_apply_fork(
 
 	// Rewind the txhashset extension back to common ancestor based on header MMR.
 	let mut current = batch.head_header()?;
-	while current.height > 0
+	while !current.is_genesis()
 		&& header_extension
 			.is_on_current_chain(&current, batch)
 			.is_err()
@@ -726,6 +786,29 @@ pub fn rewind_and_apply_fork(
 	Ok((fork_point, fork_hashes)) //change the signature so we can have the local branch information.
 }
 
+/// Fully re-validate a block we already have (PoW, block body, inputs against the utxo,
+/// coinbase maturity, kernel sums and MMR roots) and apply it to the extension.
+/// The extension is expected to be at the previous block.
+pub fn revalidate_block(
+	block: &Block,
+	ext: &mut txhashset::ExtensionPair<'_>,
+	batch: &store::Batch<'_>,
+	pow_verifier: fn(&BlockHeader) -> Result<(), pow::Error>,
+	verifier_cache: Arc<RwLock<dyn VerifierCache>>,
+) -> Result<(), Error> {
+	if pow_verifier(&block.header).is_err() {
+		return Err(ErrorKind::InvalidPow.into());
+	}
+	let prev = batch.get_previous_header(&block.header)?;
+	block
+		.validate(&prev.total_kernel_offset, verifier_cache)
+		.map_err(ErrorKind::InvalidBlockProof)?;
+	verify_coinbase_maturity(block, ext, batch)?;
+	validate_utxo(block, ext, batch)?;
+	verify_block_sums(block, batch)?;
+	apply_block_to_txhashset(block, ext, batch)
+}
+
 /// Validate block inputs and outputs against utxo.
 /// Every input must spend an unspent output.
 /// No duplicate outputs created.
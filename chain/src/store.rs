@@ -18,7 +18,7 @@ use crate::core::consensus::HeaderInfo;
 use crate::core::core::hash::{Hash, Hashed};
 use crate::core::core::{Block, BlockHeader, BlockSums, Inputs};
 use crate::core::pow::Difficulty;
-use crate::core::ser::ProtocolVersion;
+use crate::core::ser::{self, ProtocolVersion, Readable, Reader, Writeable, Writer};
 use crate::linked_list::MultiIndex;
 use crate::types::{CommitPos, HashHeight, Tip};
 use crate::util::secp::pedersen::Commitment;
@@ -49,6 +49,13 @@ const BLOCK_SUMS_PREFIX: u8 = b'M';
 const BLOCK_SPENT_PREFIX: u8 = b'S';
 const BLOCK_SPENT_COMMITMENT_PREFIX: u8 = b'C'; // deprecated
 const BLOCK_SPENT_ID_PREFIX: u8 = b's';
+const BLOCK_RECEIVED_PREFIX: u8 = b'R';
+const BLOCK_RELAY_COUNT_PREFIX: u8 = b'r';
+const BLOCK_SIZE_PREFIX: u8 = b'z';
+const DB_VERSION_PREFIX: u8 = b'V';
+
+/// Version of the chain db schema, bumped whenever a migration is added.
+pub const CHAIN_DB_VERSION: u32 = 1;
 
 /// All chain-related database operations
 pub struct ChainStore {
@@ -62,6 +69,12 @@ impl ChainStore {
 		Ok(ChainStore { db })
 	}
 
+	/// Open an existing chain store without write access.
+	pub fn new_readonly(db_root: &str) -> Result<ChainStore, Error> {
+		let db = store::Store::new_readonly(db_root, None, Some(STORE_SUBPATH), None)?;
+		Ok(ChainStore { db })
+	}
+
 	/// Create a new instance of the chain store based on this instance
 	/// but with the provided protocol version. This is used when migrating
 	/// data in the db to a different protocol version, reading using one version and
@@ -73,11 +86,37 @@ impl ChainStore {
 		}
 	}
 
+	/// Size (in bytes) of the chain db on disk.
+	pub fn disk_size(&self) -> Result<u64, Error> {
+		self.db.disk_size()
+	}
+
+	/// Version of the chain db schema, None for a new db or a db predating versioning.
+	pub fn db_version(&self) -> Result<Option<u32>, Error> {
+		Ok(self
+			.db
+			.get_ser::<DbVersion>(&[DB_VERSION_PREFIX])?
+			.map(|v| v.0))
+	}
+
 	/// The current chain head.
 	pub fn head(&self) -> Result<Tip, Error> {
 		option_to_not_found(self.db.get_ser(&[HEAD_PREFIX]), || "HEAD".to_owned())
 	}
 
+	/// Hash of the current chain head. Only the hash is read from the
+	/// serialized tip (height first, then last block hash), the rest of it
+	/// isn't deserialized.
+	pub fn head_hash(&self) -> Result<Hash, Error> {
+		let hash = option_to_not_found(
+			self.db.get_with(&[HEAD_PREFIX], |tip| {
+				tip.get(8..8 + Hash::LEN).map(Hash::from_vec)
+			}),
+			|| "HEAD".to_owned(),
+		)?;
+		hash.ok_or_else(|| Error::SerErr("HEAD is too short".to_owned()))
+	}
+
 	/// The current header head (may differ from chain head).
 	pub fn header_head(&self) -> Result<Tip, Error> {
 		option_to_not_found(self.db.get_ser(&[HEADER_HEAD_PREFIX]), || {
@@ -102,6 +141,19 @@ impl ChainStore {
 		})
 	}
 
+	/// Get full block as stored, serialized with the db protocol version.
+	pub fn get_block_bytes(&self, h: &Hash) -> Result<Vec<u8>, Error> {
+		let key = to_key(BLOCK_PREFIX, h);
+		option_to_not_found(self.db.get_with(&key, |data| data.to_vec()), || {
+			format!("BLOCK: {}", h)
+		})
+	}
+
+	/// Protocol version the db (de)serializes with.
+	pub fn protocol_version(&self) -> ProtocolVersion {
+		self.db.protocol_version()
+	}
+
 	/// Does this full block exist?
 	pub fn block_exists(&self, h: &Hash) -> Result<bool, Error> {
 		self.db.exists(&to_key(BLOCK_PREFIX, h))
@@ -119,6 +171,43 @@ impl ChainStore {
 		self.get_block_header(&header.prev_hash)
 	}
 
+	/// Wall-clock time (milliseconds since the epoch) the full block was received.
+	pub fn get_block_received_time(&self, h: &Hash) -> Result<i64, Error> {
+		option_to_not_found(
+			self.db
+				.get_ser::<ReceivedTime>(&to_key(BLOCK_RECEIVED_PREFIX, h)),
+			|| format!("Received time for block: {}", h),
+		)
+		.map(|t| t.0)
+	}
+
+	/// Number of peers the block was relayed to by this node.
+	pub fn get_block_relay_count(&self, h: &Hash) -> Result<u64, Error> {
+		option_to_not_found(
+			self.db
+				.get_ser::<RelayCount>(&to_key(BLOCK_RELAY_COUNT_PREFIX, h)),
+			|| format!("Relay count for block: {}", h),
+		)
+		.map(|c| c.0)
+	}
+
+	/// Serialized size of the full block, as recorded when it was processed.
+	pub fn get_block_size(&self, h: &Hash) -> Result<u64, Error> {
+		option_to_not_found(
+			self.db.get_ser::<BlockSize>(&to_key(BLOCK_SIZE_PREFIX, h)),
+			|| format!("Block size for block: {}", h),
+		)
+		.map(|s| s.0)
+	}
+
+	/// Iterator over the serialized size of every full block in the db,
+	/// as (hash, size) pairs.
+	pub fn block_sizes_iter(&self) -> Result<impl Iterator<Item = (Hash, u64)>, Error> {
+		let key = to_key(BLOCK_SIZE_PREFIX, "");
+		let iter: SerIterator<BlockSize> = self.db.iter(&key)?;
+		Ok(iter.map(move |(k, size)| (Hash::from_vec(&k[key.len()..]), size.0)))
+	}
+
 	/// Get block header.
 	pub fn get_block_header(&self, h: &Hash) -> Result<BlockHeader, Error> {
 		option_to_not_found(self.db.get_ser(&to_key(BLOCK_HEADER_PREFIX, h)), || {
@@ -142,6 +231,14 @@ impl ChainStore {
 		self.db.get_ser(&to_key(OUTPUT_ID_POS_PREFIX, output_id))
 	}
 
+	/// Get the "spent index" for the specified block, the positions and heights
+	/// of the outputs spent by its inputs.
+	pub fn get_spent_index(&self, bh: &Hash) -> Result<Vec<CommitPos>, Error> {
+		option_to_not_found(self.db.get_ser(&to_key(BLOCK_SPENT_PREFIX, bh)), || {
+			format!("spent index: {}", bh)
+		})
+	}
+
 	/// Builds a new batch to be used with this store.
 	pub fn batch(&self) -> Result<Batch<'_>, Error> {
 		Ok(Batch {
@@ -221,6 +318,46 @@ impl<'a> Batch<'a> {
 		Ok(())
 	}
 
+	/// Save the version of the chain db schema.
+	pub fn save_db_version(&self, version: u32) -> Result<(), Error> {
+		self.db.put_ser(&[DB_VERSION_PREFIX], &DbVersion(version))
+	}
+
+	/// Save the wall-clock time (milliseconds since the epoch) the full block was received.
+	pub fn save_block_received_time(&self, h: &Hash, time_ms: i64) -> Result<(), Error> {
+		self.db.put_ser(
+			&to_key(BLOCK_RECEIVED_PREFIX, h)[..],
+			&ReceivedTime(time_ms),
+		)
+	}
+
+	/// Delete the time the block was received at, the block stays in the db.
+	pub fn delete_block_received_time(&self, h: &Hash) -> Result<(), Error> {
+		self.db.delete(&to_key(BLOCK_RECEIVED_PREFIX, h)[..])
+	}
+
+	/// Number of peers the block was relayed to by this node.
+	pub fn get_block_relay_count(&self, h: &Hash) -> Result<u64, Error> {
+		option_to_not_found(
+			self.db
+				.get_ser::<RelayCount>(&to_key(BLOCK_RELAY_COUNT_PREFIX, h)),
+			|| format!("Relay count for block: {}", h),
+		)
+		.map(|c| c.0)
+	}
+
+	/// Save the number of peers the block was relayed to by this node.
+	pub fn save_block_relay_count(&self, h: &Hash, count: u64) -> Result<(), Error> {
+		self.db
+			.put_ser(&to_key(BLOCK_RELAY_COUNT_PREFIX, h)[..], &RelayCount(count))
+	}
+
+	/// Save the serialized size of the full block.
+	pub fn save_block_size(&self, h: &Hash, size: u64) -> Result<(), Error> {
+		self.db
+			.put_ser(&to_key(BLOCK_SIZE_PREFIX, h)[..], &BlockSize(size))
+	}
+
 	/// We maintain a "spent" index for each full block to allow the output_pos
 	/// to be easily reverted during rewind.
 	pub fn save_spent_index(&self, h: &Hash, spent: &[CommitPos]) -> Result<(), Error> {
@@ -252,6 +389,31 @@ impl<'a> Batch<'a> {
 		self.db.get_ser(&to_key(BLOCK_SPENT_ID_PREFIX, spent))
 	}
 
+	/// Delete every entry of the spent ids index.
+	/// Used when rebuilding the index from the full blocks in the db.
+	pub fn clear_spent_ids(&self) -> Result<usize, Error> {
+		let start_key = to_key(BLOCK_SPENT_ID_PREFIX, "");
+
+		let mut deleted_count = 0;
+		let spent_iter: SerIterator<Vec<HashHeight>> = self.db.iter(&start_key)?;
+		for (key, _) in spent_iter {
+			self.db.delete(&key)?;
+			deleted_count += 1;
+		}
+		Ok(deleted_count)
+	}
+
+	/// Delete every entry of the output_pos index.
+	/// Used when rebuilding the index from scratch.
+	pub fn clear_output_pos_index(&self) -> Result<usize, Error> {
+		let mut deleted_count = 0;
+		for (key, _) in self.output_pos_iter()? {
+			self.db.delete(&key)?;
+			deleted_count += 1;
+		}
+		Ok(deleted_count)
+	}
+
 	/// Migrate a block stored in the db by serializing it using the provided protocol version.
 	/// Block may have been read using a previous protocol version but we do not actually care.
 	pub fn migrate_block(&self, b: &Block, version: ProtocolVersion) -> Result<(), Error> {
@@ -327,6 +489,9 @@ impl<'a> Batch<'a> {
 		{
 			let _ = self.delete_block_sums(bh);
 			let _ = self.delete_spent_index(bh);
+			let _ = self.db.delete(&to_key(BLOCK_RECEIVED_PREFIX, bh));
+			let _ = self.db.delete(&to_key(BLOCK_RELAY_COUNT_PREFIX, bh));
+			let _ = self.db.delete(&to_key(BLOCK_SIZE_PREFIX, bh));
 		}
 
 		self.db.delete(&to_key(BLOCK_PREFIX, bh)[..])?;
@@ -598,3 +763,63 @@ impl<'a> Iterator for DifficultyIter<'a> {
 pub fn nrd_recent_kernel_index() -> MultiIndex<CommitPos> {
 	MultiIndex::init(NRD_KERNEL_LIST_PREFIX, NRD_KERNEL_ENTRY_PREFIX)
 }
+
+/// Wall-clock time a full block was received, milliseconds since the epoch.
+struct ReceivedTime(i64);
+
+impl Writeable for ReceivedTime {
+	fn write<W: Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		writer.write_i64(self.0)
+	}
+}
+
+impl Readable for ReceivedTime {
+	fn read<R: Reader>(reader: &mut R) -> Result<ReceivedTime, ser::Error> {
+		Ok(ReceivedTime(reader.read_i64()?))
+	}
+}
+
+/// Number of peers a block was relayed to.
+struct RelayCount(u64);
+
+impl Writeable for RelayCount {
+	fn write<W: Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		writer.write_u64(self.0)
+	}
+}
+
+impl Readable for RelayCount {
+	fn read<R: Reader>(reader: &mut R) -> Result<RelayCount, ser::Error> {
+		Ok(RelayCount(reader.read_u64()?))
+	}
+}
+
+/// Serialized size of a block in bytes.
+struct BlockSize(u64);
+
+impl Writeable for BlockSize {
+	fn write<W: Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		writer.write_u64(self.0)
+	}
+}
+
+impl Readable for BlockSize {
+	fn read<R: Reader>(reader: &mut R) -> Result<BlockSize, ser::Error> {
+		Ok(BlockSize(reader.read_u64()?))
+	}
+}
+
+/// Version of the chain db schema.
+struct DbVersion(u32);
+
+impl Writeable for DbVersion {
+	fn write<W: Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		writer.write_u32(self.0)
+	}
+}
+
+impl Readable for DbVersion {
+	fn read<R: Reader>(reader: &mut R) -> Result<DbVersion, ser::Error> {
+		Ok(DbVersion(reader.read_u32()?))
+	}
+}
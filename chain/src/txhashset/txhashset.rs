@@ -15,8 +15,7 @@
 //! Utility structs to handle the 3 MMRs (output, rangeproof,
 //! kernel) along the overall header MMR conveniently and transactionally.
 
-use crate::core::consensus::WEEK_HEIGHT;
-use crate::core::core::committed::Committed;
+use crate::core::core::committed::{self, Committed};
 use crate::core::core::hash::{Hash, Hashed};
 use crate::core::core::merkle_proof::MerkleProof;
 use crate::core::core::pmmr::{self, Backend, ReadonlyPMMR, RewindablePMMR, PMMR};
@@ -69,6 +68,18 @@ impl<T: PMMRable> PMMRHandle<T> {
 		let last_pos = backend.unpruned_size();
 		Ok(PMMRHandle { backend, last_pos })
 	}
+
+	/// Open a PMMR handle from an existing directory structure on disk
+	/// without write access. Nothing gets created.
+	pub fn new_readonly<P: AsRef<Path>>(
+		path: P,
+		prunable: bool,
+		version: ProtocolVersion,
+	) -> Result<PMMRHandle<T>, Error> {
+		let backend = PMMRBackend::new_readonly(&path, prunable, version)?;
+		let last_pos = backend.unpruned_size();
+		Ok(PMMRHandle { backend, last_pos })
+	}
 }
 
 impl PMMRHandle<BlockHeader> {
@@ -161,22 +172,56 @@ impl TxHashSet {
 		commit_index: Arc<ChainStore>,
 		header: Option<&BlockHeader>,
 	) -> Result<TxHashSet, Error> {
-		let output_pmmr_h = PMMRHandle::new(
+		TxHashSet::open_with(root_dir, commit_index, header, false)
+	}
+
+	/// Open an existing set of backends for the TxHashSet without write access.
+	/// Nothing gets created on disk.
+	pub fn open_readonly(
+		root_dir: String,
+		commit_index: Arc<ChainStore>,
+	) -> Result<TxHashSet, Error> {
+		TxHashSet::open_with(root_dir, commit_index, None, true)
+	}
+
+	fn open_with(
+		root_dir: String,
+		commit_index: Arc<ChainStore>,
+		header: Option<&BlockHeader>,
+		readonly: bool,
+	) -> Result<TxHashSet, Error> {
+		fn handle<T: PMMRable>(
+			path: PathBuf,
+			prunable: bool,
+			version: ProtocolVersion,
+			header: Option<&BlockHeader>,
+			readonly: bool,
+		) -> Result<PMMRHandle<T>, Error> {
+			if readonly {
+				PMMRHandle::new_readonly(path, prunable, version)
+			} else {
+				PMMRHandle::new(path, prunable, version, header)
+			}
+		}
+
+		let output_pmmr_h = handle(
 			Path::new(&root_dir)
 				.join(TXHASHSET_SUBDIR)
 				.join(OUTPUT_SUBDIR),
 			true,
 			ProtocolVersion(1),
 			header,
+			readonly,
 		)?;
 
-		let rproof_pmmr_h = PMMRHandle::new(
+		let rproof_pmmr_h = handle(
 			Path::new(&root_dir)
 				.join(TXHASHSET_SUBDIR)
 				.join(RANGE_PROOF_SUBDIR),
 			true,
 			ProtocolVersion(1),
 			header,
+			readonly,
 		)?;
 
 		// Initialize the bitmap accumulator from the current output PMMR.
@@ -185,13 +230,14 @@ impl TxHashSet {
 		let mut maybe_kernel_handle: Option<PMMRHandle<TxKernel>> = None;
 		let versions = vec![ProtocolVersion(2), ProtocolVersion(1)];
 		for version in versions {
-			let handle = PMMRHandle::new(
+			let handle = handle(
 				Path::new(&root_dir)
 					.join(TXHASHSET_SUBDIR)
 					.join(KERNEL_SUBDIR),
 				false, // not prunable
 				version,
 				None,
+				readonly,
 			)?;
 			if handle.last_pos == 0 {
 				debug!(
@@ -280,6 +326,16 @@ impl TxHashSet {
 		}
 	}
 
+	/// Commitment of the output at the provided MMR position, spent or not,
+	/// unless it was already compacted away.
+	pub fn get_output_commit_from_file(&self, pos: u64) -> Option<Commitment> {
+		let output_pmmr: ReadonlyPMMR<'_, OutputIdentifier, _> =
+			ReadonlyPMMR::at(&self.output_pmmr_h.backend, self.output_pmmr_h.last_pos);
+		output_pmmr
+			.get_data_from_file(pos)
+			.map(|out| out.commitment())
+	}
+
 	/// returns the last N nodes inserted into the tree (i.e. the 'bottom'
 	/// nodes at level 0
 	/// TODO: These need to return the actual data from the flat-files instead
@@ -319,6 +375,30 @@ impl TxHashSet {
 			.elements_from_pmmr_index(start_index, max_count, max_index)
 	}
 
+	/// Counts the unspent outputs falling in each of the provided output MMR
+	/// size ranges. The sizes must be in ascending order, range i covers the
+	/// positions after mmr_sizes[i - 1] up to (and including) mmr_sizes[i].
+	/// Only walks the leaf set, the outputs themselves are never read.
+	pub fn count_unspent_outputs_by_mmr_size(&self, mmr_sizes: &[u64]) -> Vec<u64> {
+		let mut counts = vec![0; mmr_sizes.len()];
+		let pmmr = ReadonlyPMMR::at(&self.output_pmmr_h.backend, self.output_pmmr_h.last_pos);
+		for pos in pmmr.leaf_pos_iter() {
+			let idx = match mmr_sizes.binary_search(&pos) {
+				Ok(idx) => idx,
+				Err(idx) => idx,
+			};
+			if let Some(count) = counts.get_mut(idx) {
+				*count += 1;
+			}
+		}
+		counts
+	}
+
+	/// Number of unspent outputs, the leaves of the output MMR not yet spent.
+	pub fn unspent_output_count(&self) -> u64 {
+		self.output_pmmr_h.backend.n_unpruned_leaves()
+	}
+
 	/// highest output insertion index available
 	pub fn highest_output_insertion_index(&self) -> u64 {
 		self.output_pmmr_h.last_pos
@@ -419,14 +499,57 @@ impl TxHashSet {
 		Ok(())
 	}
 
-	/// (Re)build the NRD kernel_pos index based on 2 weeks of recent kernel history.
+	/// Compact the output and rangeproof MMRs up to the provided height, which
+	/// must be at or below the current cut-through horizon.
+	/// Not allowed in production mode. Returns the number of bytes freed on disk.
+	pub fn compact_to_height(
+		&mut self,
+		height: u64,
+		header_pmmr: &PMMRHandle<BlockHeader>,
+		batch: &Batch<'_>,
+	) -> Result<u64, Error> {
+		if global::is_production_mode() {
+			return Err(ErrorKind::Other(
+				"compact_to_height is not allowed in production mode".to_string(),
+			)
+			.into());
+		}
+
+		let head_header = batch.head_header()?;
+		let horizon_height = head_header
+			.height
+			.saturating_sub(global::cut_through_horizon().into());
+		if height > horizon_height {
+			return Err(ErrorKind::Other(format!(
+				"compact_to_height: height {} is above the cut-through horizon {}",
+				height, horizon_height
+			))
+			.into());
+		}
+
+		let hash = header_pmmr.get_header_hash_by_height(height)?;
+		let header = batch.get_block_header(&hash)?;
+
+		let size_before =
+			self.output_pmmr_h.backend.disk_size()? + self.rproof_pmmr_h.backend.disk_size()?;
+		self.compact(&header, batch)?;
+		let size_after =
+			self.output_pmmr_h.backend.disk_size()? + self.rproof_pmmr_h.backend.disk_size()?;
+
+		Ok(size_before.saturating_sub(size_after))
+	}
+
+	/// (Re)build the NRD kernel_pos index based on recent kernel history,
+	/// twice the max NRD relative height.
 	pub fn init_recent_kernel_pos_index(
 		&self,
 		header_pmmr: &PMMRHandle<BlockHeader>,
 		batch: &Batch<'_>,
 	) -> Result<(), Error> {
 		let head = batch.head()?;
-		let cutoff = head.height.saturating_sub(WEEK_HEIGHT * 2);
+		let cutoff = head
+			.height
+			.saturating_sub(global::max_nrd_relative_height() * 2);
 		let cutoff_hash = header_pmmr.get_header_hash_by_height(cutoff)?;
 		let cutoff_header = batch.get_block_header(&cutoff_hash)?;
 		self.verify_kernel_pos_index(&cutoff_header, header_pmmr, batch)
@@ -447,7 +570,7 @@ impl TxHashSet {
 		let kernel_index = store::nrd_recent_kernel_index();
 		kernel_index.clear(batch)?;
 
-		let prev_size = if from_header.height == 0 {
+		let prev_size = if from_header.is_genesis() {
 			0
 		} else {
 			let prev_header = batch.get_previous_header(&from_header)?;
@@ -470,21 +593,18 @@ impl TxHashSet {
 		while current_pos <= self.kernel_pmmr_h.last_pos {
 			if pmmr::is_leaf(current_pos) {
 				if let Some(kernel) = kernel_pmmr.get_data(current_pos) {
-					match kernel.features {
-						KernelFeatures::NoRecentDuplicate { .. } => {
-							while current_pos > current_header.kernel_mmr_size {
-								let hash = header_pmmr
-									.get_header_hash_by_height(current_header.height + 1)?;
-								current_header = batch.get_block_header(&hash)?;
-							}
-							let new_pos = CommitPos {
-								pos: current_pos,
-								height: current_header.height,
-							};
-							apply_kernel_rules(&kernel, new_pos, batch)?;
-							count += 1;
+					if kernel.is_nrd() {
+						while current_pos > current_header.kernel_mmr_size {
+							let hash =
+								header_pmmr.get_header_hash_by_height(current_header.height + 1)?;
+							current_header = batch.get_block_header(&hash)?;
 						}
-						_ => {}
+						let new_pos = CommitPos {
+							pos: current_pos,
+							height: current_header.height,
+						};
+						apply_kernel_rules(&kernel, new_pos, batch)?;
+						count += 1;
 					}
 				}
 			}
@@ -499,14 +619,22 @@ impl TxHashSet {
 		Ok(())
 	}
 
+	/// Total size (in bytes) of the output, rangeproof and kernel MMR files on disk.
+	pub fn disk_size(&self) -> Result<u64, Error> {
+		Ok(self.output_pmmr_h.backend.disk_size()?
+			+ self.rproof_pmmr_h.backend.disk_size()?
+			+ self.kernel_pmmr_h.backend.disk_size()?)
+	}
+
 	/// (Re)build the output_pos index to be consistent with the current UTXO set.
 	/// Remove any "stale" index entries that do not correspond to outputs in the UTXO set.
 	/// Add any missing index entries based on UTXO set.
+	/// Returns the number of index entries added.
 	pub fn init_output_pos_index(
 		&self,
 		header_pmmr: &PMMRHandle<BlockHeader>,
 		batch: &Batch<'_>,
-	) -> Result<(), Error> {
+	) -> Result<u64, Error> {
 		let now = Instant::now();
 
 		let output_pmmr =
@@ -555,7 +683,7 @@ impl TxHashSet {
 		);
 
 		if outputs_pos.is_empty() {
-			return Ok(());
+			return Ok(0);
 		}
 
 		let total_outputs = outputs_pos.len();
@@ -565,6 +693,12 @@ impl TxHashSet {
 		for search_height in 0..max_height {
 			let hash = header_pmmr.get_header_hash_by_height(search_height + 1)?;
 			let h = batch.get_block_header(&hash)?;
+			if h.height % 10_000 == 0 {
+				debug!(
+					"init_output_pos_index: {} blocks, {} of {} entries so far",
+					h.height, i, total_outputs
+				);
+			}
 			while i < total_outputs {
 				let (output_id, pos) = outputs_pos[i];
 				if pos > h.output_mmr_size {
@@ -586,7 +720,21 @@ impl TxHashSet {
 			total_outputs,
 			now.elapsed().as_secs(),
 		);
-		Ok(())
+		Ok(total_outputs as u64)
+	}
+
+	/// Pedersen sum of the excesses of all the kernels in the kernel MMR.
+	/// Kernels are never pruned, so every leaf is there. Only needs read access.
+	pub fn kernel_excess_sum(&self) -> Result<Commitment, Error> {
+		let kernel_pmmr =
+			ReadonlyPMMR::at(&self.kernel_pmmr_h.backend, self.kernel_pmmr_h.last_pos);
+		let excesses = (1..=kernel_pmmr.unpruned_size())
+			.filter(|pos| pmmr::is_leaf(*pos))
+			.filter_map(|pos| kernel_pmmr.get_data(pos))
+			.map(|kernel| kernel.excess())
+			.collect();
+		let kernel_sum = committed::sum_commits(excesses, vec![])?;
+		Ok(kernel_sum)
 	}
 }
 
@@ -986,7 +1134,7 @@ impl<'a> HeaderExtension<'a> {
 	pub fn validate_root(&self, header: &BlockHeader) -> Result<(), Error> {
 		// If we are validating the genesis block then we have no prev_root.
 		// So we are done here.
-		if header.height == 0 {
+		if header.is_genesis() {
 			return Ok(());
 		}
 		let root = self.root()?;
@@ -1354,7 +1502,7 @@ impl<'a> Extension<'a> {
 			bitmap.iter().map(|x| x.into()).collect()
 		};
 
-		if header.height == 0 {
+		if header.is_genesis() {
 			self.rewind_mmrs_to_pos(0, 0, &spent_pos)?;
 		} else {
 			let prev = batch.get_previous_header(header)?;
@@ -1388,7 +1536,7 @@ impl<'a> Extension<'a> {
 		if global::is_nrd_enabled() {
 			let kernel_index = store::nrd_recent_kernel_index();
 			for kernel in block.kernels() {
-				if let KernelFeatures::NoRecentDuplicate { .. } = kernel.features {
+				if kernel.is_nrd() {
 					kernel_index.rewind(batch, kernel.excess(), prev_header.kernel_mmr_size)?;
 				}
 			}
@@ -1454,7 +1602,7 @@ impl<'a> Extension<'a> {
 
 	/// Validate the MMR (output, rangeproof, kernel) roots against the latest header.
 	pub fn validate_roots(&self, header: &BlockHeader) -> Result<(), Error> {
-		if header.height == 0 {
+		if header.is_genesis() {
 			return Ok(());
 		}
 		self.roots()?.validate(header)
@@ -1462,7 +1610,7 @@ impl<'a> Extension<'a> {
 
 	/// Validate the header, output and kernel MMR sizes against the block header.
 	pub fn validate_sizes(&self, header: &BlockHeader) -> Result<(), Error> {
-		if header.height == 0 {
+		if header.is_genesis() {
 			return Ok(());
 		}
 		if (
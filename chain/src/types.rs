@@ -17,7 +17,7 @@
 use chrono::prelude::{DateTime, Utc};
 
 use crate::core::core::hash::{Hash, Hashed, ZERO_HASH};
-use crate::core::core::{Block, BlockHeader, HeaderVersion};
+use crate::core::core::{Block, BlockHeader};
 use crate::core::pow::Difficulty;
 use crate::core::ser::{self, PMMRIndexHashable, Readable, Reader, Writeable, Writer};
 use crate::error::{Error, ErrorKind};
@@ -279,11 +279,11 @@ pub struct OutputRoots {
 
 impl OutputRoots {
 	/// The root of our output PMMR. The rules here are block height specific.
-	/// We use the merged root here for header version 3 and later.
+	/// We use the merged root here from HF2 (header version 3) onwards.
 	/// We assume the header version is consistent with the block height, validated
 	/// as part of pipe::validate_header().
 	pub fn root(&self, header: &BlockHeader) -> Hash {
-		if header.version < HeaderVersion(3) {
+		if header.is_before_hf(2) {
 			self.output_root()
 		} else {
 			self.merged_root(header)
@@ -400,6 +400,121 @@ impl Default for Tip {
 	}
 }
 
+/// Size statistics (serialized bytes) over a window of recent full blocks.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct BlockSizeStats {
+	/// Smallest block size
+	pub min_bytes: u64,
+	/// Largest block size
+	pub max_bytes: u64,
+	/// Average block size
+	pub avg_bytes: f64,
+	/// Median block size
+	pub median_bytes: u64,
+	/// 95th percentile block size
+	pub p95_bytes: u64,
+}
+
+impl BlockSizeStats {
+	/// Compute the stats from a list of block sizes (in bytes).
+	pub fn from_sizes(mut sizes: Vec<u64>) -> BlockSizeStats {
+		if sizes.is_empty() {
+			return BlockSizeStats {
+				min_bytes: 0,
+				max_bytes: 0,
+				avg_bytes: 0.0,
+				median_bytes: 0,
+				p95_bytes: 0,
+			};
+		}
+		sizes.sort_unstable();
+		let len = sizes.len();
+		BlockSizeStats {
+			min_bytes: sizes[0],
+			max_bytes: sizes[len - 1],
+			avg_bytes: sizes.iter().sum::<u64>() as f64 / len as f64,
+			median_bytes: sizes[len / 2],
+			p95_bytes: sizes[(len * 95 / 100).min(len - 1)],
+		}
+	}
+}
+
+/// Propagation time statistics (time between the block timestamp and the
+/// reception of the full block by this node) over a window of recent blocks.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct PropagationStats {
+	/// Number of blocks with a known reception time
+	pub blocks: u64,
+	/// Shortest propagation time (ms)
+	pub min_ms: u64,
+	/// Average propagation time (ms)
+	pub avg_ms: u64,
+	/// Longest propagation time (ms)
+	pub max_ms: u64,
+}
+
+impl PropagationStats {
+	/// Compute the stats from a list of propagation times (in ms).
+	pub fn from_times(times: &[u64]) -> PropagationStats {
+		if times.is_empty() {
+			return PropagationStats {
+				blocks: 0,
+				min_ms: 0,
+				avg_ms: 0,
+				max_ms: 0,
+			};
+		}
+		PropagationStats {
+			blocks: times.len() as u64,
+			min_ms: *times.iter().min().unwrap_or(&0),
+			avg_ms: times.iter().sum::<u64>() / times.len() as u64,
+			max_ms: *times.iter().max().unwrap_or(&0),
+		}
+	}
+}
+
+/// Outcome of simulating a block with a set of transactions on top of the head.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatedBlockResult {
+	/// Weight of the block (as block, coinbase included) with the valid transactions
+	pub weight: u64,
+	/// Total fees of the valid transactions
+	pub fees: u64,
+	/// Index (in the provided transactions) and error of the invalid ones
+	pub invalid_txs: Vec<(usize, ErrorKind)>,
+}
+
+/// Summary of the chain head, all read from the head header at once.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TipInfo {
+	/// Height of the head
+	pub height: u64,
+	/// Hash of the head block
+	pub hash: Hash,
+	/// Total difficulty of the chain
+	pub total_difficulty: u64,
+	/// Timestamp of the head block, seconds since the UNIX epoch (UTC)
+	pub timestamp: i64,
+	/// Kernel MMR size at the head
+	pub kernel_mmr_size: u64,
+	/// Output MMR size at the head
+	pub output_mmr_size: u64,
+}
+
+impl TipInfo {
+	/// Tip info of the provided (head) header.
+	pub fn from_header(header: &BlockHeader) -> TipInfo {
+		TipInfo {
+			height: header.height,
+			hash: header.hash(),
+			total_difficulty: header.total_difficulty().to_num(),
+			timestamp: header.timestamp.timestamp(),
+			kernel_mmr_size: header.kernel_mmr_size,
+			output_mmr_size: header.output_mmr_size,
+		}
+	}
+}
+
 /// Serialization of a tip, required to save to datastore.
 impl ser::Writeable for Tip {
 	fn write<W: ser::Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
@@ -516,3 +631,37 @@ impl BlockStatus {
 		}
 	}
 }
+
+/// Disk space used by the node, per subsystem (bytes).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct DiskUsage {
+	/// Chain db (blocks, headers and indexes)
+	pub block_db_bytes: u64,
+	/// Header, output, rangeproof and kernel MMR files
+	pub txhashset_bytes: u64,
+	/// Peer db, the chain doesn't own it so it's 0 unless set with with_peer_db
+	pub peer_db_bytes: u64,
+	/// Sum of the above
+	pub total_bytes: u64,
+}
+
+impl DiskUsage {
+	/// Disk usage of the chain db and the txhashset.
+	pub fn new(block_db_bytes: u64, txhashset_bytes: u64) -> DiskUsage {
+		DiskUsage {
+			block_db_bytes,
+			txhashset_bytes,
+			peer_db_bytes: 0,
+			total_bytes: block_db_bytes + txhashset_bytes,
+		}
+	}
+
+	/// Account for the peer db as well.
+	pub fn with_peer_db(self, peer_db_bytes: u64) -> DiskUsage {
+		DiskUsage {
+			peer_db_bytes,
+			total_bytes: self.block_db_bytes + self.txhashset_bytes + peer_db_bytes,
+			..self
+		}
+	}
+}
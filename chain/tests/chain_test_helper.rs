@@ -80,13 +80,15 @@ pub fn mine_chain(dir_name: &str, chain_length: u64) -> Chain {
 	chain
 }
 
+/// Mine chain_length - 1 blocks on top of the current head of the chain.
 #[allow(dead_code)]
-fn mine_some_on_top<K>(chain: &mut Chain, chain_length: u64, keychain: &K)
+pub fn mine_some_on_top<K>(chain: &mut Chain, chain_length: u64, keychain: &K)
 where
 	K: Keychain,
 {
-	for n in 1..chain_length {
+	for _ in 1..chain_length {
 		let prev = chain.head_header().unwrap();
+		let n = prev.height + 1;
 		let next_header_info = consensus::next_difficulty(1, chain.difficulty_iter().unwrap());
 		let pk = ExtKeychainPath::new(1, n as u32, 0, 0, 0).to_identifier();
 		let reward = libtx::reward::output(
@@ -12,16 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use self::chain::store::CHAIN_DB_VERSION;
 use self::chain::types::{NoopAdapter, Tip};
 use self::chain::Chain;
-use self::core::core::hash::Hashed;
+use self::core::core::hash::{Hashed, ZERO_HASH};
+use self::core::core::pmmr;
 use self::core::core::verifier_cache::LruVerifierCache;
 use self::core::core::{Block, BlockHeader, KernelFeatures, Transaction};
 use self::core::global::ChainTypes;
 use self::core::libtx::{self, build, ProofBuilder};
 use self::core::pow::Difficulty;
+use self::core::ser::{self, ProtocolVersion};
 use self::core::{consensus, global, pow};
 use self::keychain::{ExtKeychain, ExtKeychainPath, Keychain};
+use self::util::secp::pedersen::Commitment;
 use self::util::RwLock;
 use chrono::Duration;
 use grin_chain as chain;
@@ -29,11 +33,16 @@ use grin_chain::{BlockStatus, ChainAdapter, Options};
 use grin_core as core;
 use grin_keychain as keychain;
 use grin_util as util;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::thread;
 
 mod chain_test_helper;
 
-use self::chain_test_helper::{clean_output_dir, init_chain, mine_chain};
+use self::chain_test_helper::{
+	clean_output_dir, genesis_block, init_chain, mine_chain, mine_some_on_top,
+};
 
 /// Adapter to retrieve last status
 pub struct StatusAdapter {
@@ -88,6 +97,738 @@ fn mine_short_chain() {
 	clean_output_dir(chain_dir);
 }
 
+#[test]
+fn block_subsidy_plus_fees() {
+	let chain_dir = ".mwc.subsidy_plus_fees";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 4);
+	for height in 1..4 {
+		let header = chain.get_header_by_height(height).unwrap();
+		assert_eq!(
+			chain.get_block_subsidy_plus_fees(&header.hash()).unwrap(),
+			consensus::reward(0, height)
+		);
+	}
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn tip_hash() {
+	let chain_dir = ".mwc.tip_hash";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 4);
+	assert_eq!(
+		chain.get_tip_hash().unwrap(),
+		chain.head().unwrap().last_block_h
+	);
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn next_kernel_mmr_pos() {
+	let chain_dir = ".mwc.next_kernel_mmr_pos";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 4);
+	let head = chain.head_header().unwrap();
+	let pos = chain.get_next_kernel_mmr_pos().unwrap();
+	assert_eq!(pos, head.kernel_mmr_size + 1);
+	assert!(pmmr::is_leaf(pos));
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn db_version_newer_than_node() {
+	let chain_dir = ".mwc.db_version_newer_than_node";
+	clean_output_dir(chain_dir);
+	{
+		let chain = mine_chain(chain_dir, 1);
+		let store = chain.store();
+		assert_eq!(store.db_version().unwrap(), Some(CHAIN_DB_VERSION));
+		let batch = store.batch().unwrap();
+		batch.save_db_version(CHAIN_DB_VERSION + 1).unwrap();
+		batch.commit().unwrap();
+	}
+	let verifier_cache = Arc::new(RwLock::new(LruVerifierCache::new()));
+	let res = chain::Chain::init(
+		chain_dir.to_string(),
+		Arc::new(NoopAdapter {}),
+		pow::mine_genesis_block().unwrap(),
+		pow::verify_size,
+		verifier_cache,
+		false,
+	);
+	match res.map_err(|e| e.kind()) {
+		Err(chain::ErrorKind::DatabaseMigrationRequired {
+			current_version,
+			required_version,
+		}) => {
+			assert_eq!(current_version, CHAIN_DB_VERSION + 1);
+			assert_eq!(required_version, CHAIN_DB_VERSION);
+		}
+		_ => panic!("expected DatabaseMigrationRequired"),
+	}
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn kernel_lock_height() {
+	let chain_dir = ".mwc.kernel_lock_height";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 4);
+	let head = chain.head().unwrap();
+	let block = chain.get_block(&head.last_block_h).unwrap();
+	// coinbase kernels are not height locked
+	let excess = block.kernels()[0].excess();
+	assert_eq!(chain.get_kernel_lock_height(&excess).unwrap(), None);
+	let unknown = Commitment::from_vec(vec![0; 33]);
+	assert!(chain.get_kernel_lock_height(&unknown).is_err());
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn block_propagation_time() {
+	let chain_dir = ".mwc.block_propagation_time";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 4);
+	let head = chain.head().unwrap();
+	assert!(chain.get_block_propagation_time(&head.last_block_h).is_ok());
+	// genesis is not received from anyone
+	let genesis = chain.get_header_by_height(0).unwrap();
+	assert!(chain.get_block_propagation_time(&genesis.hash()).is_err());
+	let stats = chain.get_block_propagation_stats().unwrap();
+	assert_eq!(stats.blocks, head.height);
+	assert!(stats.min_ms <= stats.avg_ms && stats.avg_ms <= stats.max_ms);
+
+	// blocks received during sync are not accounted for
+	let kc = ExtKeychain::from_random_seed(false).unwrap();
+	let b = prepare_block(&kc, &chain.head_header().unwrap(), &chain, 5);
+	chain
+		.process_block(b.clone(), chain::Options::SKIP_POW | chain::Options::SYNC)
+		.unwrap();
+	assert!(chain.get_block_propagation_time(&b.hash()).is_err());
+	let stats = chain.get_block_propagation_stats().unwrap();
+	assert_eq!(stats.blocks, head.height);
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn estimate_disk_usage() {
+	let chain_dir = ".mwc.estimate_disk_usage";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 4);
+	let usage = chain.estimate_disk_usage().unwrap();
+	assert!(usage.block_db_bytes > 0);
+	assert!(usage.txhashset_bytes > 0);
+	assert_eq!(usage.peer_db_bytes, 0);
+	assert_eq!(
+		usage.total_bytes,
+		usage.block_db_bytes + usage.txhashset_bytes
+	);
+	let usage = usage.with_peer_db(100);
+	assert_eq!(
+		usage.total_bytes,
+		usage.block_db_bytes + usage.txhashset_bytes + 100
+	);
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn utxo_age_distribution() {
+	let chain_dir = ".mwc.utxo_age_distribution";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 4);
+	let head = chain.head_header().unwrap();
+	// A short chain fits in a single epoch, with all outputs unspent.
+	assert_eq!(
+		chain.get_utxo_age_distribution().unwrap(),
+		vec![(0, pmmr::n_leaves(head.output_mmr_size))]
+	);
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn output_leaf_pos() {
+	let chain_dir = ".mwc.output_leaf_pos";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 4);
+	let head = chain.head_header().unwrap();
+	let block = chain.get_block(&head.hash()).unwrap();
+	let commit = block.outputs()[0].commitment();
+	let pos = chain.get_output_leaf_pos(&commit).unwrap();
+	assert!(pmmr::is_leaf(pos));
+	assert_eq!(
+		chain.get_unspent_output_at(pos).unwrap().commitment(),
+		commit
+	);
+
+	let unknown = Commitment::from_vec(vec![1; 33]);
+	match chain.get_output_leaf_pos(&unknown) {
+		Err(e) => match e.kind() {
+			chain::ErrorKind::OutputNotFound(_) => {}
+			_ => panic!("unexpected error {:?}", e),
+		},
+		Ok(_) => panic!("unknown output should not have a position"),
+	}
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn output_merkle_proof() {
+	let chain_dir = ".mwc.output_merkle_proof";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 4);
+	let head = chain.head_header().unwrap();
+	let block = chain.get_block(&head.hash()).unwrap();
+	let output = block.outputs()[0].identifier();
+	let pos = chain.get_output_leaf_pos(&output.commit).unwrap();
+	let proof = chain.get_merkle_proof_for_pos(output.id()).unwrap();
+	assert_eq!(proof.mmr_size, head.output_mmr_size);
+
+	let root = chain.txhashset().read().roots().output_roots.pmmr_root;
+	assert!(proof.verify(root, &output, pos).is_ok());
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn output_at_leaf_pos() {
+	let chain_dir = ".mwc.output_at_leaf_pos";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 4);
+	let head = chain.head_header().unwrap();
+	let block = chain.get_block(&head.hash()).unwrap();
+	let output = block.outputs()[0];
+	let pos = chain.get_output_leaf_pos(&output.commitment()).unwrap();
+	assert_eq!(chain.get_output_at_leaf_pos(pos).unwrap(), output);
+
+	// pos 3 is the parent of the first two leaves
+	for pos in &[0, 3, head.output_mmr_size + 1] {
+		match chain.get_output_at_leaf_pos(*pos) {
+			Err(e) => match e.kind() {
+				chain::ErrorKind::OutputNotFound(_) => {}
+				_ => panic!("unexpected error at {}: {}", pos, e),
+			},
+			Ok(_) => panic!("expected no output at {}", pos),
+		}
+	}
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn spent_output_at_leaf_pos() {
+	global::set_local_chain_type(ChainTypes::AutomatedTesting);
+	util::init_test_logger();
+	let chain_dir = ".mwc.spent_output_at_leaf_pos";
+	clean_output_dir(chain_dir);
+	{
+		let chain = init_chain(chain_dir, pow::mine_genesis_block().unwrap());
+		let kc = ExtKeychain::from_random_seed(false).unwrap();
+		let pb = ProofBuilder::new(&kc);
+
+		let mut head = chain.head_header().unwrap();
+		let mut coinbase = None;
+		for n in 2..6 {
+			let b = prepare_block_key_idx(&kc, &head, &chain, n, n as u32 - 1);
+			coinbase.get_or_insert(b.outputs()[0].commitment());
+			head = b.header.clone();
+			chain.process_block(b, chain::Options::SKIP_POW).unwrap();
+		}
+		let pos = chain.get_output_leaf_pos(&coinbase.unwrap()).unwrap();
+
+		let key_id1 = ExtKeychainPath::new(1, 1, 0, 0, 0).to_identifier();
+		let key_id30 = ExtKeychainPath::new(1, 30, 0, 0, 0).to_identifier();
+		let tx = build::transaction(
+			KernelFeatures::Plain { fee: 20000 },
+			&[
+				build::coinbase_input(consensus::MWC_FIRST_GROUP_REWARD, key_id1),
+				build::output(consensus::MWC_FIRST_GROUP_REWARD - 20000, key_id30),
+			],
+			&kc,
+			&pb,
+		)
+		.unwrap();
+		let b = prepare_block_tx(&kc, &head, &chain, 6, &[tx]);
+		head = b.header.clone();
+		chain.process_block(b, chain::Options::SKIP_POW).unwrap();
+
+		// spent, but still in the output MMR until the next compaction
+		match chain.get_output_at_leaf_pos(pos) {
+			Err(e) => assert_eq!(e.kind(), chain::ErrorKind::OutputSpent),
+			Ok(_) => panic!("expected the output at {} to be spent", pos),
+		}
+
+		// go past the test horizon and compact
+		for n in 7..32 {
+			let b = prepare_block_key_idx(&kc, &head, &chain, n, n as u32 - 1);
+			head = b.header.clone();
+			chain.process_block(b, chain::Options::SKIP_POW).unwrap();
+		}
+		chain.force_compact().unwrap();
+		match chain.get_output_at_leaf_pos(pos) {
+			Err(e) => match e.kind() {
+				chain::ErrorKind::OutputAlreadyPruned(_) => {}
+				_ => panic!("unexpected error at {}: {}", pos, e),
+			},
+			Ok(_) => panic!("expected the output at {} to be pruned", pos),
+		}
+	}
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn validate_chain_segment() {
+	let chain_dir = ".mwc.validate_chain_segment";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 4);
+	let head = chain.head().unwrap();
+	assert_eq!(
+		chain.validate_chain_segment(1, head.height).unwrap(),
+		head.height
+	);
+	assert_eq!(chain.validate_chain_segment(2, 2).unwrap(), 1);
+	assert!(chain.validate_chain_segment(0, head.height).is_err());
+	assert!(chain.validate_chain_segment(1, head.height + 1).is_err());
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn validate_blocks() {
+	let chain_dir = ".mwc.validate_blocks";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 5);
+	let head = chain.head().unwrap();
+	let mut heights = vec![];
+	let last_height = chain
+		.validate_blocks(1, head.height, |header, res| {
+			assert!(res.is_ok());
+			heights.push(header.height);
+			true
+		})
+		.unwrap();
+	assert_eq!(last_height, head.height);
+	assert_eq!(heights, (1..=head.height).collect::<Vec<_>>());
+
+	// stops as soon as the callback says so
+	let mut count = 0;
+	let last_height = chain
+		.validate_blocks(1, head.height, |_, _| {
+			count += 1;
+			count < 2
+		})
+		.unwrap();
+	assert_eq!(count, 2);
+	assert_eq!(last_height, 2);
+
+	// a single block
+	chain.validate_block(&head.last_block_h).unwrap();
+	let header = chain.get_header_by_height(2).unwrap();
+	chain.validate_block(&header.hash()).unwrap();
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn largest_blocks() {
+	let chain_dir = ".mwc.largest_blocks";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 5);
+	let head = chain.head().unwrap();
+
+	let largest = chain.get_n_largest_blocks(3).unwrap();
+	assert_eq!(largest.len(), 3);
+	assert!(largest.windows(2).all(|w| w[0].1 >= w[1].1));
+	for (hash, size) in &largest {
+		let block = chain.get_block(hash).unwrap();
+		let bytes = ser::ser_vec(&block, ProtocolVersion::local()).unwrap();
+		assert_eq!(bytes.len() as u64, *size);
+	}
+
+	// every processed block when asking for more, the genesis isn't processed
+	let all = chain.get_n_largest_blocks(100).unwrap();
+	assert_eq!(all.len() as u64, head.height);
+	assert!(chain.get_n_largest_blocks(0).unwrap().is_empty());
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn block_size_stats() {
+	let chain_dir = ".mwc.block_size_stats";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 5);
+
+	// from the recorded sizes, the genesis isn't processed
+	let largest = chain.get_n_largest_blocks(100).unwrap();
+	let stats = chain.get_block_size_stats().unwrap();
+	assert_eq!(stats.max_bytes, largest[0].1);
+	assert_eq!(stats.min_bytes, largest[largest.len() - 1].1);
+	assert!(stats.min_bytes <= stats.median_bytes && stats.median_bytes <= stats.p95_bytes);
+
+	// cached until the head changes
+	assert_eq!(
+		chain.get_block_size_stats().unwrap().max_bytes,
+		stats.max_bytes
+	);
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn kernel_excess_sum() {
+	let chain_dir = ".mwc.kernel_excess_sum";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 5);
+	let head = chain.head().unwrap();
+
+	// same as the running kernel sum recorded with the head block
+	let sums = chain.get_block_sums(&head.last_block_h).unwrap();
+	let (tip, kernel_sum) = chain.get_kernel_excess_sum().unwrap();
+	assert_eq!(tip, head);
+	assert_eq!(kernel_sum, sums.kernel_sum);
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn audit_balance() {
+	let chain_dir = ".mwc.audit_balance";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 5);
+	let head = chain.head().unwrap();
+
+	// full validation gives the same sums as recorded with the head block
+	let (header, sums) = chain.audit_balance().unwrap();
+	assert_eq!(header.hash(), head.last_block_h);
+	let block_sums = chain.get_block_sums(&head.last_block_h).unwrap();
+	assert_eq!(sums, Some((block_sums.utxo_sum, block_sums.kernel_sum)));
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn rebuild_output_index() {
+	let chain_dir = ".mwc.rebuild_output_index";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 4);
+	let head = chain.head_header().unwrap();
+	let block = chain.get_block(&head.hash()).unwrap();
+	let output_id = block.outputs()[0].id();
+	let pos = chain.get_output_pos(&output_id).unwrap();
+
+	let unspent = pmmr::n_leaves(head.output_mmr_size);
+	assert_eq!(chain.rebuild_output_index().unwrap(), unspent);
+	assert_eq!(chain.get_output_pos(&output_id).unwrap(), pos);
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn block_version_distribution() {
+	let chain_dir = ".mwc.block_version_distribution";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 4);
+	let head = chain.head_header().unwrap();
+	let distribution = chain.get_block_version_distribution().unwrap();
+	// genesis included, the chain is shorter than the window
+	assert_eq!(distribution.values().sum::<u64>(), head.height + 1);
+	assert!(distribution.contains_key(&head.version.0));
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn tip_info() {
+	let chain_dir = ".mwc.tip_info";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 4);
+	let head = chain.head_header().unwrap();
+	let info = chain.get_tip_info().unwrap();
+	assert_eq!(info.height, head.height);
+	assert_eq!(info.hash, head.hash());
+	assert_eq!(info.total_difficulty, head.total_difficulty().to_num());
+	assert_eq!(info.timestamp, head.timestamp.timestamp());
+	assert_eq!(info.kernel_mmr_size, head.kernel_mmr_size);
+	assert_eq!(info.output_mmr_size, head.output_mmr_size);
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn raw_block_bytes() {
+	let chain_dir = ".mwc.raw_block_bytes";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 4);
+	let head = chain.head_header().unwrap();
+	let block = chain.get_block(&head.hash()).unwrap();
+	let bytes = chain.get_raw_block_bytes(&head.hash()).unwrap();
+	assert_eq!(
+		bytes,
+		ser::ser_vec(&block, ProtocolVersion::local()).unwrap()
+	);
+	assert!(chain.get_raw_block_bytes(&ZERO_HASH).is_err());
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn unspent_output_count() {
+	let chain_dir = ".mwc.unspent_output_count";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 4);
+	let (_, _, outputs) = chain.unspent_outputs_by_pmmr_index(1, 1000, None).unwrap();
+	assert!(!outputs.is_empty());
+	assert_eq!(chain.unspent_output_count(), outputs.len() as u64);
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn blocks_since_checkpoint() {
+	let chain_dir = ".mwc.blocks_since_checkpoint";
+	clean_output_dir(chain_dir);
+	global::set_local_chain_type(ChainTypes::AutomatedTesting);
+	let kc = ExtKeychain::from_random_seed(false).unwrap();
+	let mut chain = chain::Chain::init(
+		chain_dir.to_string(),
+		Arc::new(NoopAdapter {}),
+		genesis_block(&kc),
+		pow::verify_size,
+		Arc::new(RwLock::new(LruVerifierCache::new())),
+		true,
+	)
+	.unwrap();
+	mine_some_on_top(&mut chain, 6, &kc);
+
+	let checkpoint = chain.get_header_by_height(2).unwrap();
+	let blocks = chain
+		.get_blocks_since_checkpoint(&checkpoint.hash(), 100)
+		.unwrap();
+	let heights: Vec<u64> = blocks.iter().map(|b| b.header.height).collect();
+	assert_eq!(heights, vec![2, 3, 4, 5]);
+	assert_eq!(blocks[0].hash(), checkpoint.hash());
+
+	// in pages, each one starting at the last block of the previous one
+	let page = chain
+		.get_blocks_since_checkpoint(&checkpoint.hash(), 2)
+		.unwrap();
+	let heights: Vec<u64> = page.iter().map(|b| b.header.height).collect();
+	assert_eq!(heights, vec![2, 3]);
+	let page = chain
+		.get_blocks_since_checkpoint(&page[1].hash(), 2)
+		.unwrap();
+	let heights: Vec<u64> = page.iter().map(|b| b.header.height).collect();
+	assert_eq!(heights, vec![3, 4]);
+	assert!(chain
+		.get_blocks_since_checkpoint(&checkpoint.hash(), 0)
+		.unwrap()
+		.is_empty());
+
+	// a hash not on the chain
+	match chain.get_blocks_since_checkpoint(&ZERO_HASH, 100) {
+		Err(e) => assert_eq!(e.kind(), chain::ErrorKind::InvalidHash),
+		Ok(_) => panic!("unknown checkpoint accepted"),
+	}
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn blocks_since_checkpoint_not_archival() {
+	let chain_dir = ".mwc.blocks_since_checkpoint_not_archival";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 4);
+	let checkpoint = chain.get_header_by_height(1).unwrap();
+	match chain.get_blocks_since_checkpoint(&checkpoint.hash(), 100) {
+		Err(e) => assert_eq!(e.kind(), chain::ErrorKind::ArchivalNodeRequired),
+		Ok(_) => panic!("blocks served by a non-archival node"),
+	}
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn chain_is_syncing() {
+	let chain_dir = ".mwc.chain_is_syncing";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 4);
+	assert!(!chain.is_syncing());
+
+	// a header with a lot more work than the last few blocks, but not its block
+	let kc = ExtKeychain::from_random_seed(false).unwrap();
+	let head = chain.head_header().unwrap();
+	let threshold = chain.sync_difficulty_threshold().unwrap().to_num();
+	let b = prepare_block(&kc, &head, &chain, threshold + 10);
+	process_header(&chain, &b.header);
+	assert!(chain.is_syncing());
+
+	process_block(&chain, &b);
+	assert!(!chain.is_syncing());
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn peer_best_header() {
+	let chain_dir = ".mwc.peer_best_header";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 4);
+	assert_eq!(chain.get_peer_best_header().unwrap(), None);
+
+	// a header from a peer, ahead of our head until we get its block
+	let kc = ExtKeychain::from_random_seed(false).unwrap();
+	let head = chain.head_header().unwrap();
+	let b = prepare_block(&kc, &head, &chain, 1);
+	process_header(&chain, &b.header);
+	let peer_best = chain.get_peer_best_header().unwrap().unwrap();
+	assert_eq!(peer_best.hash(), b.header.hash());
+	assert_eq!(peer_best.height, head.height + 1);
+	// only a block ahead with minimal difficulty, within the sync threshold
+	assert!(!chain.is_syncing());
+
+	process_block(&chain, &b);
+	assert_eq!(chain.get_peer_best_header().unwrap(), None);
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn header_chain_work() {
+	let chain_dir = ".mwc.header_chain_work";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 4);
+	let genesis = chain.get_header_by_height(0).unwrap();
+	let genesis_work = chain.get_header_chain_work(&genesis.hash()).unwrap();
+	assert_eq!(
+		genesis_work,
+		(genesis.total_difficulty().to_num() as u128) << global::base_edge_bits()
+	);
+
+	let head = chain.head_header().unwrap();
+	let prev = chain.get_previous_header(&head).unwrap();
+	let difficulty = (head.total_difficulty() - prev.total_difficulty()).to_num() as u128;
+	assert_eq!(
+		chain.get_header_chain_work(&head.hash()).unwrap(),
+		chain.get_header_chain_work(&prev.hash()).unwrap()
+			+ (difficulty << global::base_edge_bits())
+	);
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn readonly_chain() {
+	let chain_dir = ".mwc.readonly_chain";
+	clean_output_dir(chain_dir);
+	global::set_local_chain_type(ChainTypes::AutomatedTesting);
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let genesis = genesis_block(&keychain);
+	let head = {
+		let mut chain = init_chain(chain_dir, genesis.clone());
+		mine_some_on_top(&mut chain, 4, &keychain);
+		chain.head().unwrap()
+	};
+
+	let chain = Chain::init_readonly(
+		chain_dir.to_string(),
+		Arc::new(NoopAdapter {}),
+		genesis,
+		pow::verify_size,
+		Arc::new(RwLock::new(LruVerifierCache::new())),
+		false,
+	)
+	.unwrap();
+	assert!(chain.is_readonly());
+	assert_eq!(chain.head().unwrap(), head);
+	let header = chain.get_header_by_height(head.height).unwrap();
+	assert_eq!(header.hash(), head.last_block_h);
+	assert!(chain.get_block(&head.last_block_h).is_ok());
+
+	// nothing gets written
+	let block = chain.get_block(&head.last_block_h).unwrap();
+	match chain.process_block(block, chain::Options::SKIP_POW) {
+		Err(e) => assert_eq!(e.kind(), chain::ErrorKind::ReadOnly),
+		Ok(_) => panic!("read-only chain should refuse blocks"),
+	}
+	assert!(chain.compact().is_err());
+	clean_output_dir(chain_dir);
+}
+
+/// All the files and dirs under the provided dir, sorted.
+#[cfg(unix)]
+fn list_dir_recursive(dir: &Path) -> Vec<PathBuf> {
+	let mut paths = vec![];
+	for entry in fs::read_dir(dir).unwrap() {
+		let path = entry.unwrap().path();
+		if path.is_dir() {
+			paths.extend(list_dir_recursive(&path));
+		}
+		paths.push(path);
+	}
+	paths.sort();
+	paths
+}
+
+/// Removes (or restores) the write permission on everything under the dir.
+/// Except the lmdb lock file, lmdb still updates it in read-only mode.
+#[cfg(unix)]
+fn set_write_protected(dir: &Path, protected: bool) {
+	use std::os::unix::fs::PermissionsExt;
+	let mut paths = list_dir_recursive(dir);
+	paths.push(dir.to_path_buf());
+	for path in paths {
+		if path.ends_with("lock.mdb") {
+			continue;
+		}
+		let mode = match (path.is_dir(), protected) {
+			(true, true) => 0o555,
+			(true, false) => 0o755,
+			(false, true) => 0o444,
+			(false, false) => 0o644,
+		};
+		fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+	}
+}
+
+#[cfg(unix)]
+#[test]
+fn readonly_chain_write_protected_dir() {
+	let chain_dir = ".mwc.readonly_chain_write_protected_dir";
+	clean_output_dir(chain_dir);
+	global::set_local_chain_type(ChainTypes::AutomatedTesting);
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let genesis = genesis_block(&keychain);
+	let head = {
+		let mut chain = init_chain(chain_dir, genesis.clone());
+		mine_some_on_top(&mut chain, 4, &keychain);
+		chain.head().unwrap()
+	};
+
+	let files = list_dir_recursive(Path::new(chain_dir));
+	set_write_protected(Path::new(chain_dir), true);
+	let res = Chain::init_readonly(
+		chain_dir.to_string(),
+		Arc::new(NoopAdapter {}),
+		genesis,
+		pow::verify_size,
+		Arc::new(RwLock::new(LruVerifierCache::new())),
+		false,
+	)
+	.map(|chain| {
+		let header = chain.get_header_by_height(head.height).unwrap();
+		(chain.head().unwrap(), header.hash())
+	});
+	set_write_protected(Path::new(chain_dir), false);
+
+	let (tip, hash) = res.unwrap();
+	assert_eq!(tip, head);
+	assert_eq!(hash, head.last_block_h);
+	// no file nor dir created, even when running as root
+	assert_eq!(list_dir_recursive(Path::new(chain_dir)), files);
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn block_relay_count() {
+	let chain_dir = ".mwc.block_relay_count";
+	clean_output_dir(chain_dir);
+	let chain = mine_chain(chain_dir, 4);
+	let head = chain.head_header().unwrap();
+	assert_eq!(chain.get_block_relay_count(&head.hash()).unwrap(), 0);
+
+	chain.record_block_relay(&head.hash(), 3).unwrap();
+	chain.record_block_relay(&head.hash(), 5).unwrap();
+	assert_eq!(chain.get_block_relay_count(&head.hash()).unwrap(), 8);
+	assert_eq!(chain.get_block_relay_count(&head.prev_hash).unwrap(), 0);
+
+	// unknown block
+	assert!(chain.get_block_relay_count(&ZERO_HASH).is_err());
+	clean_output_dir(chain_dir);
+}
+
 // Convenience wrapper for processing a full block on the test chain.
 fn process_header(chain: &Chain, header: &BlockHeader) {
 	chain
@@ -531,6 +1272,145 @@ fn longer_fork() {
 	clean_output_dir(".mwc4");
 }
 
+#[test]
+fn simulate_block() {
+	global::set_local_chain_type(ChainTypes::AutomatedTesting);
+	util::init_test_logger();
+	let chain_dir = ".mwc_simulate_block";
+	clean_output_dir(chain_dir);
+	let chain = init_chain(chain_dir, pow::mine_genesis_block().unwrap());
+	let kc = ExtKeychain::from_random_seed(false).unwrap();
+	let pb = ProofBuilder::new(&kc);
+
+	// a coinbase, mature after three further blocks
+	let mut head = chain.head_header().unwrap();
+	for n in 2..6 {
+		let b = prepare_block_key_idx(&kc, &head, &chain, n, n as u32 - 1);
+		head = b.header.clone();
+		chain.process_block(b, chain::Options::SKIP_POW).unwrap();
+	}
+
+	let empty = chain.simulate_block(&[]).unwrap();
+	assert_eq!(empty.fees, 0);
+	assert!(empty.invalid_txs.is_empty());
+
+	let key_id_coinbase = ExtKeychainPath::new(1, 1, 0, 0, 0).to_identifier();
+	let spend = |fee: u64, key_idx: u32| {
+		let key_id = ExtKeychainPath::new(1, key_idx, 0, 0, 0).to_identifier();
+		build::transaction(
+			KernelFeatures::Plain { fee },
+			&[
+				build::coinbase_input(consensus::MWC_FIRST_GROUP_REWARD, key_id_coinbase.clone()),
+				build::output(consensus::MWC_FIRST_GROUP_REWARD - fee, key_id),
+			],
+			&kc,
+			&pb,
+		)
+		.unwrap()
+	};
+	let tx1 = spend(20000, 30);
+	// the same coinbase spent again
+	let tx2 = spend(30000, 31);
+	// an input that doesn't exist
+	let tx3 = build::transaction(
+		KernelFeatures::Plain { fee: 20000 },
+		&[
+			build::input(
+				100_000,
+				ExtKeychainPath::new(1, 40, 0, 0, 0).to_identifier(),
+			),
+			build::output(80_000, ExtKeychainPath::new(1, 41, 0, 0, 0).to_identifier()),
+		],
+		&kc,
+		&pb,
+	)
+	.unwrap();
+
+	let res = chain.simulate_block(&[tx1.clone(), tx2, tx3]).unwrap();
+	assert_eq!(res.fees, 20000);
+	assert_eq!(res.weight, empty.weight + tx1.body.body_weight_as_block());
+	let invalid: Vec<usize> = res.invalid_txs.iter().map(|(idx, _)| *idx).collect();
+	assert_eq!(invalid, vec![1, 2]);
+	assert_eq!(res.invalid_txs[0].1, chain::ErrorKind::OutputSpent);
+
+	// nothing was added to the chain
+	assert_eq!(chain.head_header().unwrap().hash(), head.hash());
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn spent_commitments_paging() {
+	global::set_local_chain_type(ChainTypes::AutomatedTesting);
+	util::init_test_logger();
+	let chain_dir = ".mwc_spent_commitments_paging";
+	clean_output_dir(chain_dir);
+	let chain = init_chain(chain_dir, pow::mine_genesis_block().unwrap());
+	let kc = ExtKeychain::from_random_seed(false).unwrap();
+	let pb = ProofBuilder::new(&kc);
+
+	// four coinbases, all mature after three further blocks
+	let mut head = chain.head_header().unwrap();
+	let mut coinbases = vec![];
+	for n in 2..9 {
+		let b = prepare_block_key_idx(&kc, &head, &chain, n, n as u32 - 1);
+		coinbases.push(b.outputs()[0].commitment());
+		head = b.header.clone();
+		chain.process_block(b, chain::Options::SKIP_POW).unwrap();
+	}
+
+	let spend = |key_idx: u32| {
+		let key_id_coinbase = ExtKeychainPath::new(1, key_idx, 0, 0, 0).to_identifier();
+		let key_id = ExtKeychainPath::new(1, 30 + key_idx, 0, 0, 0).to_identifier();
+		build::transaction(
+			KernelFeatures::Plain { fee: 20000 },
+			&[
+				build::coinbase_input(consensus::MWC_FIRST_GROUP_REWARD, key_id_coinbase),
+				build::output(consensus::MWC_FIRST_GROUP_REWARD - 20000, key_id),
+			],
+			&kc,
+			&pb,
+		)
+		.unwrap()
+	};
+
+	// heights 9 to 11, spending one, one and then two coinbases,
+	// more than a page of spent commitments in the testing chain types
+	for (n, keys) in [(9, vec![1]), (10, vec![2]), (11, vec![3, 4])].iter() {
+		let txs: Vec<Transaction> = keys.iter().map(|k| spend(*k)).collect();
+		let b = prepare_block_tx(&kc, &head, &chain, *n, &txs);
+		head = b.header.clone();
+		chain.process_block(b, chain::Options::SKIP_POW).unwrap();
+	}
+
+	let mut spent = chain
+		.get_all_spent_commitments_since(0)
+		.unwrap()
+		.collect::<Result<Vec<_>, _>>()
+		.unwrap();
+	spent.sort();
+	let mut expected = coinbases[0..4].to_vec();
+	expected.sort();
+	assert_eq!(spent, expected);
+
+	// only the block at height 11
+	let mut spent = chain
+		.get_all_spent_commitments_since(11)
+		.unwrap()
+		.collect::<Result<Vec<_>, _>>()
+		.unwrap();
+	spent.sort();
+	let mut expected = coinbases[2..4].to_vec();
+	expected.sort();
+	assert_eq!(spent, expected);
+
+	// nothing above the head
+	assert_eq!(
+		chain.get_all_spent_commitments_since(12).unwrap().count(),
+		0
+	);
+	clean_output_dir(chain_dir);
+}
+
 #[test]
 fn spend_rewind_spend() {
 	global::set_local_chain_type(ChainTypes::AutomatedTesting);
@@ -550,6 +1430,7 @@ fn spend_rewind_spend() {
 		// so we can spend the coinbase later
 		let b = prepare_block_key_idx(&kc, &head, &chain, 2, 1);
 		assert!(b.outputs()[0].is_coinbase());
+		let coinbase_commit = b.outputs()[0].commitment();
 		head = b.header.clone();
 		chain
 			.process_block(b.clone(), chain::Options::SKIP_POW)
@@ -586,6 +1467,27 @@ fn spend_rewind_spend() {
 			.unwrap();
 		chain.validate(false).unwrap();
 
+		// the spent coinbase shows up in the inputs of the spending block
+		let inputs = chain.get_inputs_for_output(&coinbase_commit).unwrap();
+		assert_eq!(inputs, vec![coinbase_commit]);
+		let unspent = tx1.outputs()[0].commitment();
+		assert!(chain.get_inputs_for_output(&unspent).unwrap().is_empty());
+
+		// the coinbase was created at height 2, once spent it's only found on request
+		match chain.get_block_by_output_commit(&coinbase_commit, false) {
+			Err(e) => match e.kind() {
+				chain::ErrorKind::OutputNotFound(_) => {}
+				_ => panic!("unexpected error: {}", e),
+			},
+			Ok(_) => panic!("spent output found without include_spent"),
+		}
+		let block = chain
+			.get_block_by_output_commit(&coinbase_commit, true)
+			.unwrap();
+		assert_eq!(block.header.height, 2);
+		let block = chain.get_block_by_output_commit(&unspent, false).unwrap();
+		assert_eq!(block.hash(), head.hash());
+
 		// Now mine another block, reusing the private key for the coinbase we just spent.
 		{
 			let b = prepare_block_key_idx(&kc, &head, &chain, 7, 1);
@@ -741,6 +1643,84 @@ fn spend_in_fork_and_compact() {
 	clean_output_dir(".mwc6");
 }
 
+#[test]
+fn compact_to_height() {
+	global::set_local_chain_type(ChainTypes::AutomatedTesting);
+	util::init_test_logger();
+	let chain_dir = ".mwc_compact_to_height";
+	clean_output_dir(chain_dir);
+	{
+		let chain = init_chain(chain_dir, pow::mine_genesis_block().unwrap());
+		let kc = ExtKeychain::from_random_seed(false).unwrap();
+		let pb = ProofBuilder::new(&kc);
+
+		let mut head = chain.head_header().unwrap();
+		let mut coinbases = vec![];
+		for n in 2..6 {
+			let b = prepare_block_key_idx(&kc, &head, &chain, n, n as u32 - 1);
+			coinbases.push(b.outputs()[0].id());
+			head = b.header.clone();
+			chain.process_block(b, chain::Options::SKIP_POW).unwrap();
+		}
+		let (_, spent_pos) = chain.get_unspent(coinbases[0]).unwrap().unwrap();
+
+		// spend the first coinbase at height 5
+		let key_id1 = ExtKeychainPath::new(1, 1, 0, 0, 0).to_identifier();
+		let key_id30 = ExtKeychainPath::new(1, 30, 0, 0, 0).to_identifier();
+		let tx = build::transaction(
+			KernelFeatures::Plain { fee: 20000 },
+			&[
+				build::coinbase_input(consensus::MWC_FIRST_GROUP_REWARD, key_id1),
+				build::output(consensus::MWC_FIRST_GROUP_REWARD - 20000, key_id30),
+			],
+			&kc,
+			&pb,
+		)
+		.unwrap();
+		let b = prepare_block_tx(&kc, &head, &chain, 6, &[tx.clone()]);
+		head = b.header.clone();
+		chain.process_block(b, chain::Options::SKIP_POW).unwrap();
+
+		// go past the test horizon
+		for n in 7..32 {
+			let b = prepare_block_key_idx(&kc, &head, &chain, n, n as u32 - 1);
+			head = b.header.clone();
+			chain.process_block(b, chain::Options::SKIP_POW).unwrap();
+		}
+		let horizon = head.height - global::cut_through_horizon() as u64;
+		assert!(horizon > 5);
+
+		// the spent coinbase is still there before compacting
+		let txhashset = chain.txhashset();
+		assert!(txhashset
+			.read()
+			.get_output_commit_from_file(spent_pos.pos)
+			.is_some());
+
+		// not allowed above the horizon
+		assert!(chain.compact_to_height(horizon + 1).is_err());
+
+		let tail = chain.tail().unwrap();
+		let freed = chain.compact_to_height(horizon).unwrap();
+		assert!(freed > 0);
+
+		// only the txhashset is compacted, the blocks and so the tail are kept
+		assert_eq!(chain.tail().unwrap(), tail);
+
+		// the spent output below the horizon is pruned, the unspent ones are not
+		assert!(txhashset
+			.read()
+			.get_output_commit_from_file(spent_pos.pos)
+			.is_none());
+		for id in &coinbases[1..] {
+			assert!(chain.get_unspent(*id).unwrap().is_some());
+		}
+		assert!(chain.get_unspent(tx.outputs()[0].id()).unwrap().is_some());
+		chain.validate(false).unwrap();
+	}
+	clean_output_dir(chain_dir);
+}
+
 /// Test ability to retrieve block headers for a given output
 #[test]
 fn output_header_mappings() {
@@ -810,6 +1790,161 @@ fn output_header_mappings() {
 	clean_output_dir(".mwc_header_for_output");
 }
 
+/// Mine blocks 1 to 3 and block 4 spending the block 1 coinbase into a plain output
+fn mine_coinbase_spend<K>(chain: &Chain, kc: &K) -> (Block, Transaction, Block)
+where
+	K: Keychain,
+{
+	let pb = ProofBuilder::new(kc);
+	let genesis = chain.head_header().unwrap();
+	let b1 = prepare_block(kc, &genesis, chain, 2);
+	chain
+		.process_block(b1.clone(), chain::Options::SKIP_POW)
+		.unwrap();
+	let mut prev = b1.header.clone();
+	for n in 3..5 {
+		let b = prepare_block(kc, &prev, chain, n);
+		prev = b.header.clone();
+		chain.process_block(b, chain::Options::SKIP_POW).unwrap();
+	}
+
+	let key_id2 = ExtKeychainPath::new(1, 2, 0, 0, 0).to_identifier();
+	let key_id30 = ExtKeychainPath::new(1, 30, 0, 0, 0).to_identifier();
+	let tx = build::transaction(
+		KernelFeatures::Plain { fee: 20000 },
+		&[
+			build::coinbase_input(consensus::MWC_FIRST_GROUP_REWARD, key_id2),
+			build::output(consensus::MWC_FIRST_GROUP_REWARD - 20000, key_id30),
+		],
+		kc,
+		&pb,
+	)
+	.unwrap();
+	let b4 = prepare_block_tx(kc, &prev, chain, 7, &[tx.clone()]);
+	chain
+		.process_block(b4.clone(), chain::Options::SKIP_POW)
+		.unwrap();
+	(b1, tx, b4)
+}
+
+#[test]
+fn block_header_by_commit() {
+	let chain_dir = ".mwc.block_header_by_commit";
+	clean_output_dir(chain_dir);
+	global::set_local_chain_type(ChainTypes::AutomatedTesting);
+	let chain = init_chain(chain_dir, pow::mine_genesis_block().unwrap());
+	let kc = ExtKeychain::from_random_seed(false).unwrap();
+	let (b1, tx, b4) = mine_coinbase_spend(&chain, &kc);
+
+	// coinbase and plain outputs are found the same way
+	let coinbase = b4.outputs().iter().find(|out| out.is_coinbase()).unwrap();
+	let header = chain
+		.get_block_header_by_commit(&coinbase.commitment())
+		.unwrap();
+	assert_eq!(header.hash(), b4.hash());
+	let plain = &tx.outputs()[0];
+	assert!(!plain.is_coinbase());
+	let header = chain
+		.get_block_header_by_commit(&plain.commitment())
+		.unwrap();
+	assert_eq!(header.hash(), b4.hash());
+	assert_eq!(
+		chain.get_header_for_output(plain.id()).unwrap().hash(),
+		b4.hash()
+	);
+
+	// the block 1 coinbase is spent
+	match chain.get_block_header_by_commit(&b1.outputs()[0].commitment()) {
+		Err(e) => match e.kind() {
+			chain::ErrorKind::OutputNotFound(_) => (),
+			kind => panic!("unexpected error {:?}", kind),
+		},
+		Ok(_) => panic!("spent output found"),
+	}
+
+	// a non archive node prunes the old blocks, the headers are enough
+	{
+		let batch = chain.store().batch().unwrap();
+		batch.delete_block(&b4.hash()).unwrap();
+		batch.commit().unwrap();
+	}
+	assert!(chain.get_block(&b4.hash()).is_err());
+	let header = chain
+		.get_block_header_by_commit(&plain.commitment())
+		.unwrap();
+	assert_eq!(header.hash(), b4.hash());
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn block_header_by_commit_header_fork() {
+	let chain_dir = ".mwc.block_header_by_commit_header_fork";
+	clean_output_dir(chain_dir);
+	global::set_local_chain_type(ChainTypes::AutomatedTesting);
+	let chain = init_chain(chain_dir, pow::mine_genesis_block().unwrap());
+	let kc = ExtKeychain::from_random_seed(false).unwrap();
+	let (_, tx, b4) = mine_coinbase_spend(&chain, &kc);
+
+	// a header with more work at the same height, its block not received yet
+	let prev = chain.get_previous_header(&b4.header).unwrap();
+	let fork = prepare_block(&kc, &prev, &chain, 20);
+	chain
+		.process_block_header(&fork.header, chain::Options::SKIP_POW)
+		.unwrap();
+	assert_eq!(chain.header_head().unwrap().last_block_h, fork.hash());
+	assert_eq!(chain.head().unwrap().last_block_h, b4.hash());
+
+	// the output is still in the block on the txhashset fork
+	let plain = &tx.outputs()[0];
+	let header = chain
+		.get_block_header_by_commit(&plain.commitment())
+		.unwrap();
+	assert_eq!(header.hash(), b4.hash());
+
+	// below the fork point the header MMR is used
+	let b2 = chain
+		.get_block(&chain.get_header_by_height(2).unwrap().hash())
+		.unwrap();
+	let header = chain
+		.get_block_header_by_commit(&b2.outputs()[0].commitment())
+		.unwrap();
+	assert_eq!(header.hash(), b2.hash());
+	clean_output_dir(chain_dir);
+}
+
+#[test]
+fn block_header_by_commit_concurrent() {
+	let chain_dir = ".mwc.block_header_by_commit_concurrent";
+	clean_output_dir(chain_dir);
+	global::set_local_chain_type(ChainTypes::AutomatedTesting);
+	let chain = Arc::new(init_chain(chain_dir, pow::mine_genesis_block().unwrap()));
+	let kc = ExtKeychain::from_random_seed(false).unwrap();
+	let b1 = prepare_block(&kc, &chain.head_header().unwrap(), &chain, 2);
+	chain
+		.process_block(b1.clone(), chain::Options::SKIP_POW)
+		.unwrap();
+	let commit = b1.outputs()[0].commitment();
+
+	// look the output up while blocks are being accepted
+	let reader = {
+		let chain = chain.clone();
+		let hash = b1.hash();
+		thread::spawn(move || {
+			for _ in 0..200 {
+				let header = chain.get_block_header_by_commit(&commit).unwrap();
+				assert_eq!(header.hash(), hash);
+			}
+		})
+	};
+	for n in 3..12 {
+		let b = prepare_block(&kc, &chain.head_header().unwrap(), &chain, n);
+		chain.process_block(b, chain::Options::SKIP_POW).unwrap();
+	}
+	reader.join().unwrap();
+	assert_eq!(chain.head().unwrap().height, 10);
+	clean_output_dir(chain_dir);
+}
+
 // Use diff as both diff *and* key_idx for convenience (deterministic private key for test blocks)
 fn prepare_block<K>(kc: &K, prev: &BlockHeader, chain: &Chain, diff: u64) -> Block
 where
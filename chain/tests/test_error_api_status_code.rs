@@ -0,0 +1,76 @@
+// Copyright 2020 The Grin Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grin_chain as chain;
+use grin_core as core;
+use grin_store as store;
+
+use self::chain::{Error, ErrorKind};
+use self::core::core::hash::ZERO_HASH;
+
+fn status_code(kind: ErrorKind) -> u16 {
+	Error::from(kind).to_api_status_code()
+}
+
+#[test]
+fn test_error_api_status_code() {
+	assert_eq!(
+		status_code(ErrorKind::OutputNotFound("output".to_owned())),
+		404
+	);
+	assert_eq!(status_code(ErrorKind::OutputSpent), 404);
+	assert_eq!(
+		status_code(ErrorKind::OutputAlreadyPruned("output".to_owned())),
+		404
+	);
+	assert_eq!(
+		status_code(ErrorKind::RangeproofNotFound("rp".to_owned())),
+		404
+	);
+	assert_eq!(status_code(ErrorKind::TxKernelNotFound), 404);
+	assert_eq!(status_code(ErrorKind::ArchivalNodeRequired), 404);
+	assert_eq!(
+		status_code(ErrorKind::StoreErr(
+			store::Error::NotFoundErr("key".to_owned()),
+			"store".to_owned()
+		)),
+		404
+	);
+
+	assert_eq!(status_code(ErrorKind::InvalidPow), 400);
+	assert_eq!(status_code(ErrorKind::AlreadySpent(ZERO_HASH)), 400);
+
+	assert_eq!(status_code(ErrorKind::ReadOnly), 403);
+	assert_eq!(
+		status_code(ErrorKind::StoreErr(
+			store::Error::ReadOnly("db".to_owned()),
+			"store".to_owned()
+		)),
+		403
+	);
+
+	assert_eq!(status_code(ErrorKind::CompactionInProgress), 503);
+
+	assert_eq!(status_code(ErrorKind::Stopped), 503);
+
+	assert_eq!(
+		status_code(ErrorKind::StoreErr(
+			store::Error::OtherErr("db".to_owned()),
+			"store".to_owned()
+		)),
+		500
+	);
+	assert_eq!(status_code(ErrorKind::Other("other".to_owned())), 500);
+	assert_eq!(status_code(ErrorKind::InvalidHash), 500);
+}
@@ -0,0 +1,173 @@
+// Copyright 2020 The Grin Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grin_chain as chain;
+use grin_core as core;
+use grin_keychain as keychain;
+use grin_store as store;
+use grin_util as util;
+
+use self::chain::{Error, ErrorKind};
+use self::core::core::hash::ZERO_HASH;
+use self::core::core::{block, committed, transaction};
+use self::core::ser;
+use self::util::secp;
+
+/// Whether each kind of error is the block's fault, so the peer sending it can be banned.
+/// Exhaustive on purpose, a new ErrorKind has to be added here.
+fn expected_bad_data(kind: &ErrorKind) -> bool {
+	match kind {
+		ErrorKind::Unfit(_)
+		| ErrorKind::Orphan(_)
+		| ErrorKind::StoreErr(_, _)
+		| ErrorKind::SerErr(_)
+		| ErrorKind::TxHashSetErr(_)
+		| ErrorKind::GenesisBlockRequired
+		| ErrorKind::DatabaseMigrationRequired { .. }
+		| ErrorKind::SyncError(_)
+		| ErrorKind::Bitmap
+		| ErrorKind::PeerBanned(_)
+		| ErrorKind::PeerConnectionLimit(_)
+		| ErrorKind::ReadOnly
+		| ErrorKind::CompactionInProgress
+		| ErrorKind::ArchivalNodeRequired
+		| ErrorKind::HeaderChainDiscontinuity { .. }
+		| ErrorKind::Other(_) => false,
+		ErrorKind::DifficultyTooLow
+		| ErrorKind::WrongTotalDifficulty
+		| ErrorKind::LowEdgebits
+		| ErrorKind::InvalidHash
+		| ErrorKind::InvalidScaling
+		| ErrorKind::InvalidPow
+		| ErrorKind::OldBlock
+		| ErrorKind::InvalidBlockProof(_)
+		| ErrorKind::InvalidBlockTime
+		| ErrorKind::InvalidBlockHeight
+		| ErrorKind::InvalidRoot(_)
+		| ErrorKind::InvalidMMRSize
+		| ErrorKind::Keychain(_)
+		| ErrorKind::Secp(_)
+		| ErrorKind::AlreadySpent(_)
+		| ErrorKind::DuplicateOutputId(_)
+		| ErrorKind::ImmatureCoinbase
+		| ErrorKind::MerkleProof(_)
+		| ErrorKind::OutputNotFound(_)
+		| ErrorKind::RangeproofNotFound(_)
+		| ErrorKind::TxKernelNotFound
+		| ErrorKind::OutputSpent
+		| ErrorKind::InvalidChainSegment(_, _)
+		| ErrorKind::OutputAlreadyPruned(_)
+		| ErrorKind::InvalidBlockVersion(_)
+		| ErrorKind::InvalidTxHashSet(_)
+		| ErrorKind::FileReadErr(_)
+		| ErrorKind::TxLockHeight
+		| ErrorKind::NRDRelativeHeight
+		| ErrorKind::Transaction(_)
+		| ErrorKind::Block(_)
+		| ErrorKind::Committed(_)
+		| ErrorKind::Stopped => true,
+	}
+}
+
+#[test]
+fn is_bad_data_for_all_kinds() {
+	let kinds = vec![
+		ErrorKind::Unfit("unfit".to_owned()),
+		ErrorKind::Orphan("orphan".to_owned()),
+		ErrorKind::DifficultyTooLow,
+		ErrorKind::WrongTotalDifficulty,
+		ErrorKind::LowEdgebits,
+		ErrorKind::InvalidHash,
+		ErrorKind::InvalidScaling,
+		ErrorKind::InvalidPow,
+		ErrorKind::OldBlock,
+		ErrorKind::InvalidBlockProof(block::Error::CoinbaseSumMismatch),
+		ErrorKind::InvalidBlockTime,
+		ErrorKind::InvalidBlockHeight,
+		ErrorKind::InvalidRoot("root".to_owned()),
+		ErrorKind::InvalidMMRSize,
+		ErrorKind::Keychain(keychain::Error::GenericError("keychain".to_owned())),
+		ErrorKind::Secp(secp::Error::IncorrectCommitSum),
+		ErrorKind::AlreadySpent(ZERO_HASH),
+		ErrorKind::DuplicateOutputId(ZERO_HASH),
+		ErrorKind::ImmatureCoinbase,
+		ErrorKind::MerkleProof("proof".to_owned()),
+		ErrorKind::OutputNotFound("output".to_owned()),
+		ErrorKind::RangeproofNotFound("rangeproof".to_owned()),
+		ErrorKind::TxKernelNotFound,
+		ErrorKind::OutputSpent,
+		ErrorKind::InvalidChainSegment(1, "segment".to_owned()),
+		ErrorKind::OutputAlreadyPruned("output".to_owned()),
+		ErrorKind::InvalidBlockVersion(block::HeaderVersion(1)),
+		ErrorKind::InvalidTxHashSet("txhashset".to_owned()),
+		ErrorKind::StoreErr(
+			store::Error::NotFoundErr("key".to_owned()),
+			"store".to_owned(),
+		),
+		ErrorKind::FileReadErr("file".to_owned()),
+		ErrorKind::SerErr(ser::Error::CorruptedData("data".to_owned())),
+		ErrorKind::TxHashSetErr("txhashset".to_owned()),
+		ErrorKind::TxLockHeight,
+		ErrorKind::NRDRelativeHeight,
+		ErrorKind::GenesisBlockRequired,
+		ErrorKind::Transaction(transaction::Error::IncorrectSignature),
+		ErrorKind::Block(block::Error::CoinbaseSumMismatch),
+		ErrorKind::Other("other".to_owned()),
+		ErrorKind::Committed(committed::Error::KernelSumMismatch),
+		ErrorKind::Stopped,
+		ErrorKind::Bitmap,
+		ErrorKind::SyncError("sync".to_owned()),
+		ErrorKind::PeerBanned("127.0.0.1:3414".to_owned()),
+		ErrorKind::PeerConnectionLimit("127.0.0.1:3414".to_owned()),
+		ErrorKind::ReadOnly,
+		ErrorKind::CompactionInProgress,
+		ErrorKind::ArchivalNodeRequired,
+		ErrorKind::HeaderChainDiscontinuity { at_height: 5 },
+		ErrorKind::DatabaseMigrationRequired {
+			current_version: 2,
+			required_version: 1,
+		},
+	];
+	for kind in kinds {
+		let expected = expected_bad_data(&kind);
+		assert_eq!(
+			Error::from(kind.clone()).is_bad_data(),
+			expected,
+			"{:?}",
+			kind
+		);
+	}
+}
+
+#[test]
+fn peer_error_kinds_from() {
+	let addr = "127.0.0.1:3414".to_owned();
+
+	let e = Error::from(ErrorKind::PeerBanned(addr.clone()));
+	assert_eq!(e.kind(), ErrorKind::PeerBanned(addr.clone()));
+	assert!(!e.is_bad_data());
+	assert_eq!(e.kind().to_string(), "Peer 127.0.0.1:3414 banned");
+
+	let e: Error = ErrorKind::PeerConnectionLimit(addr.clone()).into();
+	assert_eq!(e.kind(), ErrorKind::PeerConnectionLimit(addr));
+	assert!(!e.is_bad_data());
+	assert_eq!(
+		e.kind().to_string(),
+		"Peer 127.0.0.1:3414 refused, connection limit reached"
+	);
+	assert_ne!(
+		Error::from(ErrorKind::PeerBanned("a".to_owned())).kind(),
+		Error::from(ErrorKind::PeerConnectionLimit("a".to_owned())).kind()
+	);
+}
@@ -0,0 +1,135 @@
+// Copyright 2020 The Grin Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod chain_test_helper;
+
+use self::chain_test_helper::{clean_output_dir, genesis_block, init_chain, mine_some_on_top};
+use self::core::consensus;
+use self::core::core::BlockHeader;
+use self::core::global::{self, ChainTypes};
+use grin_chain::{Chain, ErrorKind, Options};
+use grin_core as core;
+use grin_keychain as keychain;
+
+fn sync_headers(chain: &Chain, peer_chain: &Chain, from: u64, to: u64) {
+	let headers: Vec<BlockHeader> = (from..=to)
+		.map(|h| peer_chain.get_header_by_height(h).unwrap())
+		.collect();
+	chain.sync_block_headers(&headers, Options::SYNC).unwrap();
+}
+
+#[test]
+fn test_state_sync_threshold_values() {
+	global::set_local_chain_type(ChainTypes::AutomatedTesting);
+	assert_eq!(
+		global::state_sync_threshold(),
+		global::TESTING_STATE_SYNC_THRESHOLD
+	);
+	global::set_local_chain_type(ChainTypes::UserTesting);
+	assert_eq!(
+		global::state_sync_threshold(),
+		global::TESTING_STATE_SYNC_THRESHOLD
+	);
+	global::set_local_chain_type(ChainTypes::Mainnet);
+	assert_eq!(
+		global::state_sync_threshold(),
+		consensus::STATE_SYNC_THRESHOLD
+	);
+	global::set_local_chain_type(ChainTypes::AutomatedTesting);
+}
+
+// A node with only the genesis block syncs headers from a peer. As long as the
+// peer tip is within the state sync threshold the missing blocks are requested
+// one by one, past it the node has to ask for a txhashset instead.
+#[test]
+fn test_txhashset_needed_past_threshold() {
+	let peer_dir = ".state_sync_threshold_peer";
+	let chain_dir = ".state_sync_threshold";
+	clean_output_dir(peer_dir);
+	clean_output_dir(chain_dir);
+	global::set_local_chain_type(ChainTypes::AutomatedTesting);
+	let threshold = global::state_sync_threshold() as u64;
+	// the full block fallback is bounded by the horizon, same as the threshold here
+	assert_eq!(threshold, global::cut_through_horizon() as u64);
+
+	let keychain = keychain::ExtKeychain::from_random_seed(false).unwrap();
+	let genesis = genesis_block(&keychain);
+	let mut peer_chain = init_chain(peer_dir, genesis.clone());
+	mine_some_on_top(&mut peer_chain, threshold + 6, &keychain);
+	let chain = init_chain(chain_dir, genesis);
+
+	// exactly threshold blocks behind, body sync
+	sync_headers(&chain, &peer_chain, 1, threshold);
+	assert_eq!(chain.header_head().unwrap().height, threshold);
+	let mut hashes = Some(vec![]);
+	assert!(!chain
+		.check_txhashset_needed("test".to_owned(), &mut hashes)
+		.unwrap());
+	assert_eq!(hashes.unwrap().len() as u64, threshold);
+
+	// further behind than the threshold, txhashset download
+	sync_headers(&chain, &peer_chain, threshold + 1, threshold + 5);
+	assert_eq!(chain.header_head().unwrap().height, threshold + 5);
+	assert!(chain
+		.check_txhashset_needed("test".to_owned(), &mut None)
+		.unwrap());
+
+	clean_output_dir(peer_dir);
+	clean_output_dir(chain_dir);
+}
+
+// A chunk of headers with a hole in it, or one that doesn't connect to anything
+// we know, is reported with the height to request headers from again.
+#[test]
+fn test_header_chain_discontinuity() {
+	let peer_dir = ".header_chain_discontinuity_peer";
+	let chain_dir = ".header_chain_discontinuity";
+	clean_output_dir(peer_dir);
+	clean_output_dir(chain_dir);
+	global::set_local_chain_type(ChainTypes::AutomatedTesting);
+
+	let keychain = keychain::ExtKeychain::from_random_seed(false).unwrap();
+	let genesis = genesis_block(&keychain);
+	let mut peer_chain = init_chain(peer_dir, genesis.clone());
+	mine_some_on_top(&mut peer_chain, 10, &keychain);
+	let chain = init_chain(chain_dir, genesis);
+
+	let headers: Vec<BlockHeader> = (1..=10)
+		.map(|h| peer_chain.get_header_by_height(h).unwrap())
+		.collect();
+
+	// header 5 missing from the chunk
+	let mut with_gap = headers[..4].to_vec();
+	with_gap.extend_from_slice(&headers[5..]);
+	let res = chain.sync_block_headers(&with_gap, Options::SYNC);
+	assert_eq!(
+		res.map_err(|e| e.kind()),
+		Err(ErrorKind::HeaderChainDiscontinuity { at_height: 6 })
+	);
+	assert_eq!(chain.header_head().unwrap().height, 0);
+
+	// chunk starting past what we have
+	let res = chain.sync_block_headers(&headers[3..], Options::SYNC);
+	assert_eq!(
+		res.map_err(|e| e.kind()),
+		Err(ErrorKind::HeaderChainDiscontinuity { at_height: 4 })
+	);
+
+	// re-requesting from the reported height fills the hole
+	sync_headers(&chain, &peer_chain, 1, 10);
+	assert_eq!(chain.header_head().unwrap().height, 10);
+
+	clean_output_dir(peer_dir);
+	clean_output_dir(chain_dir);
+}
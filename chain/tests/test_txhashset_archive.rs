@@ -14,7 +14,13 @@
 
 mod chain_test_helper;
 
-use self::chain_test_helper::{clean_output_dir, mine_chain};
+use self::chain_test_helper::{
+	clean_output_dir, genesis_block, init_chain, mine_chain, mine_some_on_top,
+};
+use self::core::core::hash::Hashed;
+use self::core::global::{self, ChainTypes};
+use grin_core as core;
+use grin_keychain as keychain;
 
 #[test]
 fn test() {
@@ -25,3 +31,28 @@ fn test() {
 	assert_eq!(10, header.height);
 	clean_output_dir(chain_dir);
 }
+
+#[test]
+fn test_archive_interval() {
+	let chain_dir = ".txhashset_archive_interval_test";
+	clean_output_dir(chain_dir);
+	global::set_local_chain_type(ChainTypes::AutomatedTesting);
+	let interval = global::TESTING_TXHASHSET_ARCHIVE_INTERVAL;
+	let threshold = global::TESTING_STATE_SYNC_THRESHOLD as u64;
+	let keychain = keychain::ExtKeychain::from_random_seed(false).unwrap();
+	let mut chain = init_chain(chain_dir, genesis_block(&keychain));
+
+	// the archive trails the head by the state sync threshold, rounded down to the interval
+	mine_some_on_top(&mut chain, threshold + interval + 1, &keychain);
+	let header = chain.txhashset_archive_header().unwrap();
+	assert_eq!(interval, header.height);
+	assert!(chain.txhashset_read(header.hash()).is_ok());
+
+	// one interval later the archive moves up by one interval
+	mine_some_on_top(&mut chain, interval + 1, &keychain);
+	let header = chain.txhashset_archive_header().unwrap();
+	assert_eq!(2 * interval, header.height);
+	assert!(chain.txhashset_read(header.hash()).is_ok());
+
+	clean_output_dir(chain_dir);
+}
@@ -107,6 +107,10 @@ fn comments() -> HashMap<String, String> {
 	retval.insert(
 		"archive_mode".to_string(),
 		"
+#number of blocks between two compactions of the chain on average, the compaction
+#is randomly triggered on new blocks (default 1440, a day)
+#compaction_check_interval = 1440
+
 #run the node in \"full archive\" mode (default is fast-sync, pruned node)
 "
 		.to_string(),
@@ -226,6 +230,74 @@ fn comments() -> HashMap<String, String> {
 		.to_string(),
 	);
 
+	retval.insert(
+		"[server.api_config]".to_string(),
+		"
+#########################################
+### API CONFIGURATION                 ###
+#########################################
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"cors_allowed_origins".to_string(),
+		"
+#Origins allowed to query the REST API from a browser (CORS), e.g. block explorers.
+#Disabled by default, list the origins explicitly. \"*\" allows any website to
+#query every API of the node, owner and admin ones included.
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"request_timeout_secs".to_string(),
+		"
+#Time (in seconds) a request to the read-only v1 REST API endpoints may take before
+#a 504 Gateway Timeout is returned. The request keeps running in the background until
+#it completes, it is not cancelled. 0 disables the timeout.
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"[server.admin_config]".to_string(),
+		"
+#########################################
+### ADMIN CONFIGURATION               ###
+#########################################
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"allow_reindex".to_string(),
+		"
+#Allow the db index rebuild endpoints under /v1/admin (reindex_spent etc.)
+#These are expensive and lock the chain while running. Default: false
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"allow_expensive_queries".to_string(),
+		"
+#Allow the expensive read only queries, /v1/chain/audit (sum of all the
+#kernel excesses) and /v1/mining/simulate (builds a block from the posted
+#transactions without mining it). Default: false
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"allow_txhashset_download".to_string(),
+		"
+#Allow downloading the current txhashset archive over http at
+#/v1/txhashset/txhashset.zip, one download at a time. Default: false
+"
+		.to_string(),
+	);
+
 	retval.insert(
 		"[server.dandelion_config]".to_string(),
 		"
@@ -346,6 +418,20 @@ fn comments() -> HashMap<String, String> {
 #amount of incoming connections temporarily allowed to exceed peer_max_inbound_count
 #peer_listener_buffer_count = 8
 
+#seconds to connect to a peer and complete the handshake with it
+#peer_connect_timeout_secs = 10
+
+#seconds to complete the handshake with a peer when running over Tor
+#tor_peer_connect_timeout_secs = 60
+
+#milliseconds without a difficulty update after which a peer behind us is considered
+#stuck and disconnected (default 2 hours)
+#stuck_peer_kick_time = 7200000
+
+#seconds after which a defunct peer is forgotten and removed from the peer store
+#(default 2 weeks)
+#peer_expiration_remove_time = 1209600
+
 # 15 = Bit flags for FULL_NODE
 #This structure needs to be changed internally, to make it more configurable
 
@@ -406,6 +492,33 @@ fn comments() -> HashMap<String, String> {
 		.to_string(),
 	);
 
+	retval.insert(
+		"max_tx_age_hours".to_string(),
+		"
+#maximum age in hours of a transaction in the pool, older transactions are
+#pruned during the periodic compaction check, default 24
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"accept_non_standard".to_string(),
+		"
+#accept transactions that are valid but don't follow the standard policy
+#(zero fee kernels, too many kernels...), default false
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"max_tx_per_minute".to_string(),
+		"
+#maximum number of transactions a single peer IP can relay per minute,
+#0 disables the limit. Transactions pushed through the local API are not limited
+"
+		.to_string(),
+	);
+
 	retval.insert(
 		"[server.stratum_mining_config]".to_string(),
 		"
@@ -612,6 +725,16 @@ fn comments() -> HashMap<String, String> {
 		.to_string(),
 	);
 
+	retval.insert(
+		"log_json".to_string(),
+		"
+#whether to write stdout and file logs as JSON lines (for log aggregators)
+#log levels per module, overriding the levels above for these modules, can be set with
+#log_module_levels = \"chain=debug,p2p=info,api=warn\"
+"
+		.to_string(),
+	);
+
 	retval
 }
 
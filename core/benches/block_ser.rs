@@ -0,0 +1,112 @@
+// Copyright 2020 The Grin Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Block (de)serialization benchmarks, the path every block received from
+//! the network goes through.
+//!
+//! Run with `cargo bench -p grin_core --bench block_ser`. To compare against
+//! master, run `cargo bench -p grin_core --bench block_ser -- --save-baseline
+//! master` there first, then `-- --baseline master` on the branch.
+//!
+//! Regression thresholds: changes within 5% are treated as noise (see
+//! `noise_threshold` below). CI should flag any benchmark more than 10%
+//! slower than the master baseline, and a slowdown over 25% on the full
+//! block read should block the merge, deserialization being the first thing
+//! done with untrusted blocks.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use grin_core::core::{Block, BlockHeader, KernelFeatures, Transaction};
+use grin_core::global;
+use grin_core::libtx::build::{self, input, output};
+use grin_core::libtx::{reward, ProofBuilder};
+use grin_core::pow::Difficulty;
+use grin_core::ser::{self, ProtocolVersion};
+use keychain::{ExtKeychain, Keychain};
+use std::time::Duration;
+
+/// Number of txs in the benchmarked block, each with 1 input, 1 output and
+/// 1 kernel. The coinbase adds one more output and kernel.
+const BLOCK_TXS: u32 = 100;
+
+fn full_block() -> Block {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+
+	let txs: Vec<Transaction> = (0..BLOCK_TXS)
+		.map(|n| {
+			let key_in = ExtKeychain::derive_key_id(1, 2 * n, 0, 0, 0);
+			let key_out = ExtKeychain::derive_key_id(1, 2 * n + 1, 0, 0, 0);
+			build::transaction(
+				KernelFeatures::Plain { fee: 2 },
+				&[input(10, key_in), output(8, key_out)],
+				&keychain,
+				&builder,
+			)
+			.unwrap()
+		})
+		.collect();
+
+	let prev = BlockHeader::default();
+	let fees = txs.iter().map(|tx| tx.fee()).sum();
+	let key_id = ExtKeychain::derive_key_id(1, 2 * BLOCK_TXS, 0, 0, 0);
+	let reward = reward::output(&keychain, &builder, &key_id, fees, false, 1).unwrap();
+	Block::new(&prev, &txs, Difficulty::min(), reward).unwrap()
+}
+
+fn bench_block_ser(c: &mut Criterion) {
+	let block = full_block();
+	assert_eq!(block.inputs().len(), BLOCK_TXS as usize);
+	let version = ProtocolVersion::local();
+	let bytes = ser::ser_vec(&block, version).unwrap();
+	let read: Block = ser::deserialize(&mut &bytes[..], version).unwrap();
+	assert_eq!(read.kernels().len(), BLOCK_TXS as usize + 1);
+
+	let mut group = c.benchmark_group("block_ser");
+	group.throughput(Throughput::Bytes(bytes.len() as u64));
+	group.bench_function("write", |b| {
+		b.iter(|| ser::ser_vec(black_box(&block), version).unwrap())
+	});
+	group.bench_function("read", |b| {
+		b.iter(|| ser::deserialize::<Block, _>(&mut black_box(&bytes[..]), version))
+	});
+	group.finish();
+}
+
+fn bench_header_ser(c: &mut Criterion) {
+	let header = full_block().header;
+	let version = ProtocolVersion::local();
+	let bytes = ser::ser_vec(&header, version).unwrap();
+	let read: BlockHeader = ser::deserialize(&mut &bytes[..], version).unwrap();
+	assert_eq!(read, header);
+
+	let mut group = c.benchmark_group("header_ser");
+	group.throughput(Throughput::Bytes(bytes.len() as u64));
+	group.bench_function("write", |b| {
+		b.iter(|| ser::ser_vec(black_box(&header), version).unwrap())
+	});
+	group.bench_function("read", |b| {
+		b.iter(|| ser::deserialize::<BlockHeader, _>(&mut black_box(&bytes[..]), version))
+	});
+	group.finish();
+}
+
+criterion_group! {
+	name = benches;
+	config = Criterion::default()
+		.noise_threshold(0.05)
+		.measurement_time(Duration::from_secs(10));
+	targets = bench_block_ser, bench_header_ser
+}
+criterion_main!(benches);
@@ -65,14 +65,24 @@ pub const YEAR_HEIGHT: u64 = 52 * WEEK_HEIGHT;
 /// Number of blocks before a coinbase matures and can be spent
 pub const COINBASE_MATURITY: u64 = DAY_HEIGHT;
 
+/// Initial target ratio (in percent) of secondary proof of work to primary
+/// proof of work.
+pub const INITIAL_SECONDARY_POW_RATIO: u32 = 45;
+
 /// Target ratio of secondary proof of work to primary proof of work,
 /// as a function of block height (time). Starts at 90% losing a percent
 /// approximately every week. Represented as an integer between 0 and 100.
 /// MWC: note we are changing this to an initial 45% (since we launch
 /// approximately 1 year after grin) and we also make it go to 0
 /// over the course of 1 year. This will roughly keep us inline with grin.
+/// The initial ratio comes from global::secondary_pow_ratio() so it can be
+/// overridden for testing.
 pub fn secondary_pow_ratio(height: u64) -> u64 {
-	45u64.saturating_sub(height / (YEAR_HEIGHT / 45))
+	let initial_ratio = global::secondary_pow_ratio() as u64;
+	if initial_ratio == 0 {
+		return 0;
+	}
+	initial_ratio.saturating_sub(height / (YEAR_HEIGHT / initial_ratio))
 }
 
 /// The AR scale damping factor to use. Dependent on block height
@@ -145,58 +155,61 @@ pub const TESTING_SECOND_HARD_FORK: u64 = 6;
 /// AutomatedTesting and UserTesting HF3 height.
 pub const TESTING_THIRD_HARD_FORK: u64 = 9;
 
-/// Check whether the block version is valid at a given height
-/// MWC doesn't want like grin change the algorithms for mining. So version is constant
-pub fn header_version(height: u64) -> HeaderVersion {
-	let chain_type = global::get_chain_type();
-	match chain_type {
-		global::ChainTypes::Mainnet | global::ChainTypes::Floonet => {
-			if height < get_c31_hard_fork_block_height() {
-				HeaderVersion(1)
-			} else {
-				HeaderVersion(2)
-			}
-		}
+/// Mainnet hard fork heights, HF1 (C31 phase out) first.
+pub const MAINNET_HARD_FORK_HEIGHTS: [u64; 1] = [202_500];
+/// Floonet hard fork heights, HF1 (C31 phase out) first.
+pub const FLOONET_HARD_FORK_HEIGHTS: [u64; 1] = [270_000];
+/// AutomatedTesting and UserTesting hard fork heights, HF1 first.
+pub const TESTING_HARD_FORK_HEIGHTS: [u64; 3] = [
+	TESTING_FIRST_HARD_FORK,
+	TESTING_SECOND_HARD_FORK,
+	TESTING_THIRD_HARD_FORK,
+];
+
+/// Hard fork heights for the current chain type, HF1 first.
+pub fn hard_fork_heights() -> &'static [u64] {
+	match global::get_chain_type() {
+		global::ChainTypes::Mainnet => &MAINNET_HARD_FORK_HEIGHTS,
+		global::ChainTypes::Floonet => &FLOONET_HARD_FORK_HEIGHTS,
 		// Note!!!! We need that to cover NRD tests.
 		global::ChainTypes::AutomatedTesting | global::ChainTypes::UserTesting => {
-			if height < TESTING_FIRST_HARD_FORK {
-				HeaderVersion(1)
-			} else if height < TESTING_SECOND_HARD_FORK {
-				HeaderVersion(2)
-			} else if height < TESTING_THIRD_HARD_FORK {
-				HeaderVersion(3)
-			} else {
-				HeaderVersion(4)
-			}
+			&TESTING_HARD_FORK_HEIGHTS
 		}
 	}
 }
 
+/// Height of the given hard fork (1 for HF1) on the current chain type.
+/// None if that hard fork isn't scheduled on this chain.
+pub fn hard_fork_height(hf_number: u8) -> Option<u64> {
+	if hf_number == 0 {
+		return None;
+	}
+	hard_fork_heights().get(hf_number as usize - 1).cloned()
+}
+
+/// Whether the given height is before the given hard fork (1 for HF1).
+/// A hard fork that isn't scheduled on this chain is never reached.
+pub fn is_before_hf(height: u64, hf_number: u8) -> bool {
+	match hard_fork_height(hf_number) {
+		Some(hf_height) => height < hf_height,
+		None => true,
+	}
+}
+
+/// Check whether the block version is valid at a given height
+/// MWC doesn't want like grin change the algorithms for mining. So version is constant
+/// Header version is bumped at every hard fork, HF1 is version 2.
+pub fn header_version(height: u64) -> HeaderVersion {
+	let forks_passed = (1..=hard_fork_heights().len() as u8)
+		.filter(|hf| !is_before_hf(height, *hf))
+		.count();
+	HeaderVersion(1 + forks_passed as u16)
+}
+
 /// Check whether the block version is valid at a given height.
 /// Currently we only use the default version. No hard forks planned.
 pub fn valid_header_version(height: u64, version: HeaderVersion) -> bool {
-	let chain_type = global::get_chain_type();
-	match chain_type {
-		global::ChainTypes::Mainnet | global::ChainTypes::Floonet => {
-			if height < get_c31_hard_fork_block_height() {
-				version == HeaderVersion(1)
-			} else {
-				version == HeaderVersion(2)
-			}
-		}
-		// Note!!!! We need that to cover NRD tests.
-		global::ChainTypes::AutomatedTesting | global::ChainTypes::UserTesting => {
-			if height < TESTING_FIRST_HARD_FORK {
-				version == HeaderVersion(1)
-			} else if height < TESTING_SECOND_HARD_FORK {
-				version == HeaderVersion(2)
-			} else if height < TESTING_THIRD_HARD_FORK {
-				version == HeaderVersion(3)
-			} else {
-				version == HeaderVersion(4)
-			}
-		}
-	}
+	version == header_version(height)
 }
 
 /// Number of blocks used to calculate difficulty adjustments
@@ -395,14 +408,26 @@ pub fn secondary_pow_scaling(height: u64, diff_data: &[HeaderInfo]) -> u32 {
 	max(MIN_AR_SCALE, scale) as u32
 }
 
+/// Whether the secondary PoW scaling factor of a header at the provided height
+/// is valid, given the scaling expected from the previous headers (as computed
+/// by next_difficulty). The scaling is consensus critical, so there is no slack
+/// around the expected value, and it can't go below MIN_AR_SCALE.
+/// The genesis header isn't adjusted, it carries the initial graph weight.
+pub fn verify_secondary_scaling(height: u64, scaling: u32, expected: u32) -> bool {
+	if height == 0 {
+		return scaling == global::initial_graph_weight();
+	}
+	scaling as u64 >= MIN_AR_SCALE && scaling == expected
+}
+
 /// Hard fork modifications:
 
 fn get_c31_hard_fork_block_height() -> u64 {
 	// return 202_500 for mainnet and 270_000 for floonet
 	if global::get_chain_type() == global::ChainTypes::Floonet {
-		270_000
+		FLOONET_HARD_FORK_HEIGHTS[0]
 	} else {
-		202_500
+		MAINNET_HARD_FORK_HEIGHTS[0]
 	}
 }
 
@@ -28,6 +28,7 @@ use crate::pow::{verify_size, Difficulty, Proof, ProofOfWork};
 use crate::ser::{
 	self, deserialize_default, serialize_default, PMMRable, Readable, Reader, Writeable, Writer,
 };
+use blake2::blake2b::blake2b;
 use chrono::naive::{MAX_DATE, MIN_DATE};
 use chrono::prelude::{DateTime, NaiveDateTime, Utc};
 use chrono::Duration;
@@ -334,6 +335,18 @@ impl Readable for BlockHeader {
 }
 
 impl BlockHeader {
+	/// Whether this is the header of the genesis block (height 0), which has
+	/// no previous header.
+	pub fn is_genesis(&self) -> bool {
+		self.height == 0
+	}
+
+	/// Whether this header is before the given hard fork (1 for HF1) on the
+	/// current chain type. See consensus::hard_fork_heights.
+	pub fn is_before_hf(&self, hf_number: u8) -> bool {
+		consensus::is_before_hf(self.height, hf_number)
+	}
+
 	/// Write the pre-hash portion of the header
 	pub fn write_pre_pow<W: Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
 		self.version.write(writer)?;
@@ -368,6 +381,15 @@ impl BlockHeader {
 		Ok(header_buf)
 	}
 
+	/// Hash of the pre-pow (every header field but the proof itself), the
+	/// challenge the cuck(at)oo solver and verifier derive their siphash keys from.
+	pub fn preimage_hash(&self) -> Hash {
+		let pre_pow = self
+			.pre_pow()
+			.expect("serializing a header to a vec can't fail");
+		Hash::from_vec(blake2b(32, &[], &pre_pow).as_bytes())
+	}
+
 	/// Constructs a header given pre_pow string, nonce, and proof
 	pub fn from_pre_pow_and_proof(
 		pre_pow: String,
@@ -798,6 +820,12 @@ impl Block {
 	/// Check the sum of coinbase-marked outputs match
 	/// the sum of coinbase-marked kernels accounting for fees.
 	pub fn verify_coinbase(&self) -> Result<(), Error> {
+		self.verify_coinbase_reward(self.header.height)
+	}
+
+	/// Verify the coinbase outputs commit to the block subsidy at the given height
+	/// plus the total fees of the block (the coinbase kernels cover the difference).
+	pub fn verify_coinbase_reward(&self, height: u64) -> Result<(), Error> {
 		let cb_outs = self
 			.body
 			.outputs
@@ -815,7 +843,7 @@ impl Block {
 		{
 			let secp = static_secp_instance();
 			let secp = secp.lock();
-			let over_commit = secp.commit_value(reward(self.total_fees(), self.header.height))?;
+			let over_commit = secp.commit_value(reward(self.total_fees(), height))?;
 
 			let out_adjust_sum = secp::Secp256k1::commit_sum(
 				map_vec!(cb_outs, |x| x.commitment()),
@@ -856,7 +884,7 @@ impl Block {
 			if !global::is_nrd_enabled() {
 				return Err(Error::NRDKernelNotEnabled);
 			}
-			if self.header.version < HeaderVersion(4) {
+			if self.header.is_before_hf(3) {
 				return Err(Error::NRDKernelPreHF3);
 			}
 		}
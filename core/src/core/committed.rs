@@ -83,29 +83,31 @@ pub trait Committed {
 		Ok((kernel_sum, kernel_sum_plus_offset))
 	}
 
+	/// Sum of the output commitments minus the sum of the input commitments.
+	fn sum_outputs_minus_inputs(&self) -> Result<Commitment, Error> {
+		sum_commits(self.outputs_committed(), self.inputs_committed())
+	}
+
 	/// Gathers commitments and sum them.
 	fn sum_commitments(&self, overage: i64) -> Result<Commitment, Error> {
-		// gather the commitments
-		let mut input_commits = self.inputs_committed();
-		let mut output_commits = self.outputs_committed();
+		let utxo_sum = self.sum_outputs_minus_inputs()?;
+		if overage == 0 {
+			return Ok(utxo_sum);
+		}
 
 		// add the overage as output commitment if positive,
 		// or as an input commitment if negative
-		if overage != 0 {
-			let over_commit = {
-				let secp = static_secp_instance();
-				let secp = secp.lock();
-				let overage_abs = overage.checked_abs().ok_or_else(|| Error::InvalidValue)? as u64;
-				secp.commit_value(overage_abs)?
-			};
-			if overage < 0 {
-				input_commits.push(over_commit);
-			} else {
-				output_commits.push(over_commit);
-			}
+		let overage_abs = overage.checked_abs().ok_or_else(|| Error::InvalidValue)? as u64;
+		let over_commit = {
+			let secp = static_secp_instance();
+			let secp = secp.lock();
+			secp.commit_value(overage_abs)?
+		};
+		if overage < 0 {
+			sum_commits(vec![utxo_sum], vec![over_commit])
+		} else {
+			sum_commits(vec![utxo_sum, over_commit], vec![])
 		}
-
-		sum_commits(output_commits, input_commits)
 	}
 
 	/// Vector of input commitments to verify.
@@ -17,7 +17,7 @@
 use crate::core::block::{Block, BlockHeader, Error, UntrustedBlockHeader};
 use crate::core::hash::{DefaultHashable, Hashed};
 use crate::core::id::ShortIdentifiable;
-use crate::core::{Output, ShortId, TxKernel};
+use crate::core::{Output, ShortId, Transaction, TxKernel};
 use crate::ser::{self, read_multi, Readable, Reader, VerifySortedAndUnique, Writeable, Writer};
 use rand::{thread_rng, Rng};
 
@@ -182,6 +182,44 @@ impl CompactBlock {
 	pub fn out_full(&self) -> &[Output] {
 		&self.body.out_full
 	}
+
+	/// Expand the compact block into a full block, resolving the kern_ids
+	/// against the kernels of the provided transactions (transactions matching
+	/// none of them are ignored). Fails if any kern_id is left unresolved.
+	/// Note: the resulting block is not validated, caller must validate it.
+	pub fn expand(&self, transactions: &[Transaction]) -> Result<Block, Error> {
+		let hash = self.hash();
+		let mut resolved = vec![];
+		let mut txs = vec![];
+		for tx in transactions {
+			let mut matched = false;
+			for k in tx.kernels() {
+				let short_id = k.short_id(&hash, self.nonce);
+				if self.kern_ids().contains(&short_id) {
+					resolved.push(short_id);
+					matched = true;
+				}
+			}
+			if matched {
+				txs.push(tx.clone());
+			}
+		}
+
+		let missing = self
+			.kern_ids()
+			.iter()
+			.filter(|id| !resolved.contains(id))
+			.collect::<Vec<_>>();
+		if !missing.is_empty() {
+			return Err(Error::Other(format!(
+				"missing transactions: {}, {:?}",
+				missing.len(),
+				missing
+			)));
+		}
+
+		Block::hydrate_from(self.clone(), &txs)
+	}
 }
 
 impl From<Block> for CompactBlock {
@@ -0,0 +1,172 @@
+// Copyright 2020 The Grin Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compact Transactions.
+
+use crate::core::hash::{Hash, Hashed, ZERO_HASH};
+use crate::core::id::ShortIdentifiable;
+use crate::core::{Committed, Output, ShortId, Transaction, TxKernel};
+use crate::libtx::secp_ser;
+use crate::ser::{self, read_multi, Readable, Reader, VerifySortedAndUnique, Writeable, Writer};
+use keychain::BlindingFactor;
+
+/// Compact representation of a transaction.
+/// Inputs spend outputs the receiver already knows about (utxo set) so they are
+/// represented as short_ids. Outputs and kernels are novel and sent in full.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactTransaction {
+	/// The kernel "offset" k2
+	#[serde(
+		serialize_with = "secp_ser::as_hex",
+		deserialize_with = "secp_ser::blind_from_hex"
+	)]
+	pub offset: BlindingFactor,
+	/// The stealth offset
+	pub stealth_offset: Option<BlindingFactor>,
+	/// Nonce for connection specific short_ids
+	pub nonce: u64,
+	/// Inputs as short_ids of the commitments being spent
+	input_ids: Vec<ShortId>,
+	/// Full outputs
+	outputs: Vec<Output>,
+	/// Full kernels
+	kernels: Vec<TxKernel>,
+}
+
+impl CompactTransaction {
+	/// Build the compact representation of a tx, short_ids are generated with
+	/// the provided (connection specific) nonce.
+	pub fn from_tx(tx: &Transaction, nonce: u64) -> CompactTransaction {
+		let key = short_id_key(tx.kernels());
+		let mut input_ids: Vec<ShortId> = tx
+			.inputs_committed()
+			.iter()
+			.map(|x| x.short_id(&key, nonce))
+			.collect();
+		input_ids.sort_unstable();
+
+		CompactTransaction {
+			offset: tx.offset.clone(),
+			stealth_offset: tx.stealth_offset.clone(),
+			nonce,
+			input_ids,
+			outputs: tx.outputs().to_vec(),
+			kernels: tx.kernels().to_vec(),
+		}
+	}
+
+	/// Hash the input short_ids are keyed on (along with the nonce).
+	/// Kernels are sent in full so the receiver can derive the same key.
+	pub fn short_id_key(&self) -> Hash {
+		short_id_key(&self.kernels)
+	}
+
+	/// Get input short_ids
+	pub fn input_ids(&self) -> &[ShortId] {
+		&self.input_ids
+	}
+
+	/// Get full outputs
+	pub fn outputs(&self) -> &[Output] {
+		&self.outputs
+	}
+
+	/// Get full kernels
+	pub fn kernels(&self) -> &[TxKernel] {
+		&self.kernels
+	}
+
+	// Verify everything is sorted in lexicographical order and no duplicates present.
+	fn verify_sorted(&self) -> Result<(), ser::Error> {
+		self.input_ids.verify_sorted_and_unique()?;
+		self.outputs.verify_sorted_and_unique()?;
+		self.kernels.verify_sorted_and_unique()?;
+		Ok(())
+	}
+}
+
+fn short_id_key(kernels: &[TxKernel]) -> Hash {
+	kernels.first().map(|k| k.hash()).unwrap_or(ZERO_HASH)
+}
+
+impl Writeable for CompactTransaction {
+	fn write<W: Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		if self.input_ids.len() > ser::READ_VEC_SIZE_LIMIT as usize
+			|| self.outputs.len() > ser::READ_VEC_SIZE_LIMIT as usize
+			|| self.kernels.len() > ser::READ_VEC_SIZE_LIMIT as usize
+		{
+			return Err(ser::Error::TooLargeWriteErr(format!(
+				"CompactTransaction has too many items: input ids {}, outputs {}, kernels {}",
+				self.input_ids.len(),
+				self.outputs.len(),
+				self.kernels.len()
+			)));
+		}
+
+		self.offset.write(writer)?;
+		if let Some(stealth_offset) = self.stealth_offset.as_ref() {
+			writer.write_u8(1)?;
+			stealth_offset.write(writer)?;
+		} else {
+			writer.write_u8(0)?;
+		}
+		ser_multiwrite!(
+			writer,
+			[write_u64, self.nonce],
+			[write_u64, self.input_ids.len() as u64],
+			[write_u64, self.outputs.len() as u64],
+			[write_u64, self.kernels.len() as u64]
+		);
+
+		self.input_ids.write(writer)?;
+		self.outputs.write(writer)?;
+		self.kernels.write(writer)?;
+
+		Ok(())
+	}
+}
+
+impl Readable for CompactTransaction {
+	fn read<R: Reader>(reader: &mut R) -> Result<CompactTransaction, ser::Error> {
+		let offset = BlindingFactor::read(reader)?;
+		let stealth_offset = if reader.read_u8()? == 1 {
+			Some(BlindingFactor::read(reader)?)
+		} else {
+			None
+		};
+		let (nonce, input_id_len, output_len, kernel_len) =
+			ser_multiread!(reader, read_u64, read_u64, read_u64, read_u64);
+
+		if input_id_len > ser::READ_VEC_SIZE_LIMIT
+			|| output_len > ser::READ_VEC_SIZE_LIMIT
+			|| kernel_len > ser::READ_VEC_SIZE_LIMIT
+		{
+			return Err(ser::Error::TooLargeReadErr(format!(
+				"CompactTransaction has too many items: input ids {}, outputs {}, kernels {}",
+				input_id_len, output_len, kernel_len
+			)));
+		}
+
+		let ctx = CompactTransaction {
+			offset,
+			stealth_offset,
+			nonce,
+			input_ids: read_multi(reader, input_id_len)?,
+			outputs: read_multi(reader, output_len)?,
+			kernels: read_multi(reader, kernel_len)?,
+		};
+		ctx.verify_sorted()?;
+		Ok(ctx)
+	}
+}
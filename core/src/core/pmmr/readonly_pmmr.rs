@@ -96,6 +96,16 @@ where
 		}
 	}
 
+	/// Get the data element at provided position from the underlying MMR file,
+	/// ignoring the leafset. Spent entries remain readable until compacted.
+	pub fn get_data_from_file(&self, pos: u64) -> Option<T::E> {
+		if pos > self.last_pos {
+			None
+		} else {
+			self.backend.get_data_from_file(pos)
+		}
+	}
+
 	/// Iterator over current (unpruned, unremoved) leaf positions.
 	pub fn leaf_pos_iter(&self) -> impl Iterator<Item = u64> + '_ {
 		self.backend.leaf_pos_iter()
@@ -14,10 +14,11 @@
 
 //! Transactions
 
+use crate::core::compact_transaction::CompactTransaction;
 use crate::core::hash::{DefaultHashable, Hash, Hashed};
 use crate::core::verifier_cache::VerifierCache;
 use crate::core::{committed, Committed};
-use crate::libtx::{aggsig, secp_ser};
+use crate::libtx::{self, aggsig, secp_ser};
 use crate::ser::{
 	self, read_multi, PMMRable, ProtocolVersion, Readable, Reader, VerifySortedAndUnique,
 	Writeable, Writer,
@@ -39,7 +40,7 @@ use util::ToHex;
 impl DefaultHashable for Commitment {}
 
 /// Relative height field on NRD kernel variant.
-/// u16 representing a height between 1 and MAX (consensus::WEEK_HEIGHT).
+/// u16 representing a height between 1 and global::max_nrd_relative_height().
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct NRDRelativeHeight(u16);
 
@@ -61,18 +62,14 @@ impl Readable for NRDRelativeHeight {
 }
 
 /// Conversion from a u16 to a valid NRDRelativeHeight.
-/// Valid height is between 1 and WEEK_HEIGHT inclusive.
+/// Valid height is between 1 and global::max_nrd_relative_height() inclusive.
 impl TryFrom<u16> for NRDRelativeHeight {
 	type Error = Error;
 
 	fn try_from(height: u16) -> Result<Self, Self::Error> {
 		if height == 0 {
 			Err(Error::InvalidNRDRelativeHeight)
-		} else if height
-			> NRDRelativeHeight::MAX
-				.try_into()
-				.expect("WEEK_HEIGHT const should fit in u16")
-		{
+		} else if height as u64 > global::max_nrd_relative_height() {
 			Err(Error::InvalidNRDRelativeHeight)
 		} else {
 			Ok(Self(height))
@@ -95,10 +92,8 @@ impl From<NRDRelativeHeight> for u64 {
 }
 
 impl NRDRelativeHeight {
-	const MAX: u64 = consensus::WEEK_HEIGHT;
-
 	/// Create a new NRDRelativeHeight from the provided height.
-	/// Checks height is valid (between 1 and WEEK_HEIGHT inclusive).
+	/// Checks height is valid (between 1 and global::max_nrd_relative_height() inclusive).
 	pub fn new(height: u64) -> Result<Self, Error> {
 		NRDRelativeHeight::try_from(height)
 	}
@@ -1212,6 +1207,10 @@ impl TransactionBody {
 	}
 }
 
+/// Max number of kernels of a "standard" transaction (mempool policy, not consensus).
+/// Leaves room for the aggregation of several txs during dandelion stem phase.
+pub const MAX_STANDARD_TX_KERNELS: usize = 100;
+
 /// A transaction
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Transaction {
@@ -1379,6 +1378,25 @@ impl Transaction {
 		}
 	}
 
+	/// Builds a new transaction without the outputs both created and spent
+	/// by this transaction, along with the inputs spending them.
+	/// Kernels and offsets are kept as they are.
+	pub fn cut_through(&self) -> Result<Transaction, Error> {
+		let mut inputs: Vec<CommitWrapper> = self.inputs().into();
+		let mut outputs = self.outputs().to_vec();
+		let (inputs, outputs, _, _) = cut_through(&mut inputs, &mut outputs)?;
+		let body = self
+			.body
+			.clone()
+			.replace_inputs(Inputs::from(inputs))
+			.replace_outputs(outputs);
+		Ok(Transaction {
+			offset: self.offset.clone(),
+			stealth_offset: self.stealth_offset.clone(),
+			body,
+		})
+	}
+
 	/// Get inputs
 	pub fn inputs(&self) -> Inputs {
 		self.body.inputs()
@@ -1409,6 +1427,35 @@ impl Transaction {
 		self.body.lock_height()
 	}
 
+	/// Is this a "standard" transaction, as relayed by default by the mempool?
+	/// This is policy and not consensus, see is_standard_with_fee_base.
+	pub fn is_standard(&self) -> bool {
+		self.is_standard_with_fee_base(libtx::DEFAULT_BASE_FEE)
+	}
+
+	/// A standard transaction has between 1 and MAX_STANDARD_TX_KERNELS kernels,
+	/// no coinbase output or kernel, a non zero fee on every kernel and a total fee
+	/// of at least its weight times the provided base fee.
+	pub fn is_standard_with_fee_base(&self, fee_base: u64) -> bool {
+		let kernels = self.kernels();
+		if kernels.is_empty() || kernels.len() > MAX_STANDARD_TX_KERNELS {
+			return false;
+		}
+		if self.body.verify_features().is_err() {
+			return false;
+		}
+		let zero_fee_kernel = kernels.iter().any(|k| match k.features {
+			KernelFeatures::Coinbase => true,
+			KernelFeatures::Plain { fee } => fee == 0,
+			KernelFeatures::HeightLocked { fee, .. } => fee == 0,
+			KernelFeatures::NoRecentDuplicate { fee, .. } => fee == 0,
+		});
+		if zero_fee_kernel {
+			return false;
+		}
+		self.fee() >= self.tx_weight().saturating_mul(fee_base)
+	}
+
 	/// "Lightweight" validation that we can perform quickly during read/deserialization.
 	/// Subset of full validation that skips expensive verification steps, specifically -
 	/// * rangeproof verification (on the body)
@@ -1434,6 +1481,12 @@ impl Transaction {
 		Ok(())
 	}
 
+	/// Compact representation of this tx, with inputs replaced by short_ids
+	/// generated using the provided (connection specific) nonce.
+	pub fn to_compact(&self, nonce: u64) -> CompactTransaction {
+		CompactTransaction::from_tx(self, nonce)
+	}
+
 	/// Can be used to compare txs by their fee/weight ratio.
 	/// Don't use these values for anything else though due to precision multiplier.
 	pub fn fee_to_weight(&self) -> u64 {
@@ -2368,7 +2421,7 @@ mod test {
 		};
 
 		// Test explicit protocol version.
-		for version in vec![ProtocolVersion(1), ProtocolVersion(2)] {
+		for version in ProtocolVersion::range(ProtocolVersion(1), ProtocolVersion(2)) {
 			let mut vec = vec![];
 			ser::serialize(&mut vec, version, &kernel).expect("serialized failed");
 			let kernel2: TxKernel = ser::deserialize(&mut &vec[..], version).unwrap();
@@ -2410,7 +2463,7 @@ mod test {
 		};
 
 		// Test explicit protocol version.
-		for version in vec![ProtocolVersion(1), ProtocolVersion(2)] {
+		for version in ProtocolVersion::range(ProtocolVersion(1), ProtocolVersion(2)) {
 			let mut vec = vec![];
 			ser::serialize(&mut vec, version, &kernel).expect("serialized failed");
 			let kernel2: TxKernel = ser::deserialize(&mut &vec[..], version).unwrap();
@@ -2454,7 +2507,7 @@ mod test {
 		};
 
 		// Test explicit protocol version.
-		for version in vec![ProtocolVersion(1), ProtocolVersion(2)] {
+		for version in ProtocolVersion::range(ProtocolVersion(1), ProtocolVersion(2)) {
 			let mut vec = vec![];
 			ser::serialize(&mut vec, version, &kernel).expect("serialized failed");
 			let kernel2: TxKernel = ser::deserialize(&mut &vec[..], version).unwrap();
@@ -2670,6 +2723,13 @@ mod test {
 			))
 		);
 
+		// NRD with relative height above the (testing) max is invalid.
+		vec.clear();
+		let invalid_height = global::max_nrd_relative_height() + 1;
+		ser::serialize_default(&mut vec, &(3u8, 10u64, invalid_height as u16))?;
+		let res: Result<KernelFeatures, _> = ser::deserialize_default(&mut &vec[..]);
+		assert!(res.is_err());
+
 		// Kernel variant 4 (and above) is invalid.
 		let mut vec = vec![];
 		ser::serialize_default(&mut vec, &(4u8))?;
@@ -34,6 +34,25 @@ pub trait VerifierCache: Sync + Send {
 	fn add_kernel_sig_verified(&mut self, kernels: Vec<TxKernel>);
 	/// Adds a vec of outputs to the cache (used in conjunction with the the filter above).
 	fn add_rangeproof_verified(&mut self, outputs: Vec<Output>);
+	/// Current occupancy and hit/miss counters of the cache.
+	fn stats(&self) -> Stats;
+}
+
+/// Verifier cache occupancy and hit/miss counters since the cache was created.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+	/// Number of cached rangeproofs
+	pub rangeproof_size: usize,
+	/// Number of cached kernel signatures
+	pub kernel_size: usize,
+	/// Rangeproofs found in the cache
+	pub rangeproof_hits: u64,
+	/// Rangeproofs not found in the cache (must verify)
+	pub rangeproof_misses: u64,
+	/// Kernel signatures found in the cache
+	pub kernel_hits: u64,
+	/// Kernel signatures not found in the cache (must verify)
+	pub kernel_misses: u64,
 }
 
 /// An implementation of verifier_cache using lru_cache.
@@ -42,15 +61,23 @@ pub trait VerifierCache: Sync + Send {
 pub struct LruVerifierCache {
 	kernel_sig_verification_cache: LruCache<Hash, ()>,
 	rangeproof_verification_cache: LruCache<Hash, ()>,
+	stats: Stats,
 }
 
 impl LruVerifierCache {
 	/// TODO how big should these caches be?
 	/// They need to be *at least* large enough to cover a maxed out block.
 	pub fn new() -> LruVerifierCache {
+		LruVerifierCache::with_capacity(50_000, 50_000)
+	}
+
+	/// Create a cache holding up to the given number of rangeproofs and kernel
+	/// signatures. Same as above, keep them large enough for a maxed out block.
+	pub fn with_capacity(rangeproof_cap: usize, kernel_cap: usize) -> LruVerifierCache {
 		LruVerifierCache {
-			kernel_sig_verification_cache: LruCache::new(50_000),
-			rangeproof_verification_cache: LruCache::new(50_000),
+			kernel_sig_verification_cache: LruCache::new(kernel_cap),
+			rangeproof_verification_cache: LruCache::new(rangeproof_cap),
+			stats: Stats::default(),
 		}
 	}
 }
@@ -62,6 +89,8 @@ impl VerifierCache for LruVerifierCache {
 			.filter(|x| !self.kernel_sig_verification_cache.contains_key(&x.hash()))
 			.cloned()
 			.collect::<Vec<_>>();
+		self.stats.kernel_hits += (kernels.len() - res.len()) as u64;
+		self.stats.kernel_misses += res.len() as u64;
 		trace!(
 			"lru_verifier_cache: kernel sigs: {}, not cached (must verify): {}",
 			kernels.len(),
@@ -80,6 +109,8 @@ impl VerifierCache for LruVerifierCache {
 			})
 			.cloned()
 			.collect::<Vec<_>>();
+		self.stats.rangeproof_hits += (outputs.len() - res.len()) as u64;
+		self.stats.rangeproof_misses += res.len() as u64;
 		trace!(
 			"lru_verifier_cache: rangeproofs: {}, not cached (must verify): {}",
 			outputs.len(),
@@ -100,4 +131,12 @@ impl VerifierCache for LruVerifierCache {
 				.insert(o.proof.hash(), ());
 		}
 	}
+
+	fn stats(&self) -> Stats {
+		Stats {
+			rangeproof_size: self.rangeproof_verification_cache.len(),
+			kernel_size: self.kernel_sig_verification_cache.len(),
+			..self.stats
+		}
+	}
 }
@@ -19,12 +19,17 @@
 use crate::consensus::{
 	graph_weight, HeaderInfo, BASE_EDGE_BITS, BLOCK_KERNEL_WEIGHT, BLOCK_OUTPUT_WEIGHT,
 	BLOCK_TIME_SEC, COINBASE_MATURITY, CUT_THROUGH_HORIZON, DAY_HEIGHT, DEFAULT_MIN_EDGE_BITS,
-	DIFFICULTY_ADJUST_WINDOW, INITIAL_DIFFICULTY, MAX_BLOCK_WEIGHT, PROOFSIZE,
-	SECOND_POW_EDGE_BITS, STATE_SYNC_THRESHOLD,
+	DIFFICULTY_ADJUST_WINDOW, INITIAL_DIFFICULTY, INITIAL_SECONDARY_POW_RATIO, MAX_BLOCK_WEIGHT,
+	PROOFSIZE, SECOND_POW_EDGE_BITS, STATE_SYNC_THRESHOLD, WEEK_HEIGHT,
 };
+use crate::core::HeaderVersion;
 use crate::pow::{self, new_cuckarood_ctx, new_cuckatoo_ctx, PoWContext};
 use crate::ser::ProtocolVersion;
+use serde::{de, Deserialize, Deserializer};
 use std::cell::Cell;
+use std::env;
+use std::fmt;
+use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use util::OneTime;
@@ -79,16 +84,6 @@ pub const TESTING_INITIAL_DIFFICULTY: u64 = 1;
 /// Testing max_block_weight (artifically low, just enough to support a few txs).
 pub const TESTING_MAX_BLOCK_WEIGHT: u64 = 250;
 
-/// If a peer's last updated difficulty is 2 hours ago and its difficulty's lower than ours,
-/// we're sure this peer is a stuck node, and we will kick out such kind of stuck peers.
-pub const STUCK_PEER_KICK_TIME: i64 = 2 * 3600 * 1000;
-
-/// If a peer's last seen time is 2 weeks ago we will forget such kind of defunct peers.
-const PEER_EXPIRATION_DAYS: i64 = 7 * 2;
-
-/// Constant that expresses defunct peer timeout in seconds to be used in checks.
-pub const PEER_EXPIRATION_REMOVE_TIME: i64 = PEER_EXPIRATION_DAYS * 24 * 3600;
-
 /// Trigger compaction check on average every day for all nodes.
 /// Randomized per node - roll the dice on every block to decide.
 /// Will compact the txhashset to remove pruned data.
@@ -96,6 +91,9 @@ pub const PEER_EXPIRATION_REMOVE_TIME: i64 = PEER_EXPIRATION_DAYS * 24 * 3600;
 /// For a node configured as "archival_mode = true" only the txhashset will be compacted.
 pub const COMPACTION_CHECK: u64 = DAY_HEIGHT;
 
+/// Max NRD kernel relative height (automated testing and user testing).
+pub const TESTING_MAX_NRD_RELATIVE_HEIGHT: u64 = DAY_HEIGHT;
+
 /// Number of blocks to reuse a txhashset zip for (automated testing and user testing).
 pub const TESTING_TXHASHSET_ARCHIVE_INTERVAL: u64 = 10;
 
@@ -127,7 +125,7 @@ pub const FLOONET_DNS_SEEDS: &'static [&'static str] = &[
 
 /// Types of chain a server can run with, dictates the genesis block and
 /// and mining parameters used.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
 pub enum ChainTypes {
 	/// For CI testing
 	AutomatedTesting,
@@ -140,14 +138,10 @@ pub enum ChainTypes {
 }
 
 impl ChainTypes {
-	/// Short name representing the chain type ("floo", "main", etc.)
+	/// Short name representing the chain type ("floo", "main", etc.), same as
+	/// the Display output.
 	pub fn shortname(&self) -> String {
-		match *self {
-			ChainTypes::AutomatedTesting => "auto".to_owned(),
-			ChainTypes::UserTesting => "user".to_owned(),
-			ChainTypes::Floonet => "floo".to_owned(),
-			ChainTypes::Mainnet => "main".to_owned(),
-		}
+		self.to_string()
 	}
 }
 
@@ -157,6 +151,49 @@ impl Default for ChainTypes {
 	}
 }
 
+impl FromStr for ChainTypes {
+	type Err = String;
+
+	/// Accepts both the short names ("auto", "user", "floo", "main") and the
+	/// variant names, case insensitive.
+	fn from_str(s: &str) -> Result<ChainTypes, String> {
+		match s.to_lowercase().as_str() {
+			"auto" | "automatedtesting" => Ok(ChainTypes::AutomatedTesting),
+			"user" | "usertesting" => Ok(ChainTypes::UserTesting),
+			"floo" | "floonet" => Ok(ChainTypes::Floonet),
+			"main" | "mainnet" => Ok(ChainTypes::Mainnet),
+			_ => Err(format!("unknown chain type {}", s)),
+		}
+	}
+}
+
+impl fmt::Display for ChainTypes {
+	/// The short name, as accepted by from_str.
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let name = match *self {
+			ChainTypes::AutomatedTesting => "auto",
+			ChainTypes::UserTesting => "user",
+			ChainTypes::Floonet => "floo",
+			ChainTypes::Mainnet => "main",
+		};
+		f.write_str(name)
+	}
+}
+
+/// Parsed with from_str, so the config file accepts the same names as the command line.
+impl<'de> Deserialize<'de> for ChainTypes {
+	fn deserialize<D>(deserializer: D) -> Result<ChainTypes, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		let s = String::deserialize(deserializer)?;
+		s.parse().map_err(de::Error::custom)
+	}
+}
+
+/// Environment variable overriding the chain type (testing only, see get_chain_type_from_env)
+pub const CHAIN_TYPE_ENV_VAR: &str = "MWC_CHAIN_TYPE";
+
 lazy_static! {
 	/// Global chain_type that must be initialized once on node startup.
 	/// This is accessed via get_chain_type() which allows the global value
@@ -179,10 +216,32 @@ thread_local! {
 
 	/// Local feature flag for NRD kernel support.
 	pub static NRD_FEATURE_ENABLED: Cell<Option<bool>> = Cell::new(None);
+
+	/// Local override of the initial secondary PoW ratio (testing only).
+	pub static SECONDARY_POW_RATIO: Cell<Option<u32>> = Cell::new(None);
+}
+
+/// Chain type from the MWC_CHAIN_TYPE environment variable, if set to a valid value.
+/// Intended for testing (CI) only: it takes priority over the chain type set by
+/// the code, unless that one is a production chain type (floonet or mainnet),
+/// in which case the environment is ignored.
+pub fn get_chain_type_from_env() -> Option<ChainTypes> {
+	env::var(CHAIN_TYPE_ENV_VAR).ok()?.parse().ok()
+}
+
+/// Apply the environment override (if any) to a non production chain type.
+fn chain_type_with_env_override(chain_type: ChainTypes) -> ChainTypes {
+	match chain_type {
+		ChainTypes::Floonet | ChainTypes::Mainnet => chain_type,
+		_ => get_chain_type_from_env().unwrap_or(chain_type),
+	}
 }
 
 /// Set the chain type on a per-thread basis via thread_local storage.
+/// Unless new_type is floonet or mainnet, a valid MWC_CHAIN_TYPE in the
+/// environment takes priority over it (see get_chain_type_from_env).
 pub fn set_local_chain_type(new_type: ChainTypes) {
+	let new_type = chain_type_with_env_override(new_type);
 	CHAIN_TYPE.with(|chain_type| chain_type.set(Some(new_type)))
 }
 
@@ -204,8 +263,16 @@ pub fn get_chain_type() -> ChainTypes {
 
 /// One time initialization of the global chain_type.
 /// Will panic if we attempt to re-initialize this (via OneTime).
+/// MWC_CHAIN_TYPE overrides new_type, as in set_local_chain_type.
 pub fn init_global_chain_type(new_type: ChainTypes) {
-	GLOBAL_CHAIN_TYPE.init(new_type)
+	GLOBAL_CHAIN_TYPE.init(chain_type_with_env_override(new_type))
+}
+
+/// As init_global_chain_type but doesn't panic if the global chain_type is
+/// already set. Returns true if it was initialized by this call.
+/// MWC_CHAIN_TYPE overrides new_type, as in set_local_chain_type.
+pub fn init_global_chain_type_once(new_type: ChainTypes) -> bool {
+	GLOBAL_CHAIN_TYPE.try_init(chain_type_with_env_override(new_type))
 }
 
 /// One time initialization of the global chain_type.
@@ -238,27 +305,50 @@ pub fn is_nrd_enabled() -> bool {
 	})
 }
 
+/// Proof of work algorithms a PoW context can be created for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PoWContextType {
+	/// Cuckarood, used for AR (edge_bits 29 and below) on floonet and mainnet
+	Cuckarood,
+	/// Cuckatoo, used for AF (edge_bits above 29) and on test chains
+	Cuckatoo,
+}
+
+/// Select the PoW algorithm for a chain type, header version and graph size.
+/// Header version is None when the header isn't known (e.g. standalone
+/// solution checks), in that case only edge_bits drives the selection.
+/// Single change point, an algorithm switch at a header version goes here.
+pub fn pow_context_type(
+	chain_type: ChainTypes,
+	header_version: Option<HeaderVersion>,
+	edge_bits: u8,
+) -> PoWContextType {
+	match (chain_type, header_version, edge_bits) {
+		// Mainnet has Cuckaroo(d)29 for AR and Cuckatoo31+ for AF
+		(ChainTypes::Mainnet, _, bits) if bits > 29 => PoWContextType::Cuckatoo,
+		(ChainTypes::Mainnet, _, _) => PoWContextType::Cuckarood,
+
+		// Same for Floonet
+		(ChainTypes::Floonet, _, bits) if bits > 29 => PoWContextType::Cuckatoo,
+		(ChainTypes::Floonet, _, _) => PoWContextType::Cuckarood,
+
+		// Everything else is Cuckatoo only
+		_ => PoWContextType::Cuckatoo,
+	}
+}
+
 /// Return either a cuckoo context or a cuckatoo context
 /// Single change point
 /// MWC: We modify this to launch with cuckarood only on both floonet and mainnet
 pub fn create_pow_context<T>(
-	_height: u64,
+	header_version: Option<HeaderVersion>,
 	edge_bits: u8,
 	proof_size: usize,
 	max_sols: u32,
 ) -> Result<Box<dyn PoWContext>, pow::Error> {
-	let chain_type = get_chain_type();
-	match chain_type {
-		// Mainnet has Cuckaroo(d)29 for AR and Cuckatoo31+ for AF
-		ChainTypes::Mainnet if edge_bits > 29 => new_cuckatoo_ctx(edge_bits, proof_size, max_sols),
-		ChainTypes::Mainnet => new_cuckarood_ctx(edge_bits, proof_size),
-
-		// Same for Floonet
-		ChainTypes::Floonet if edge_bits > 29 => new_cuckatoo_ctx(edge_bits, proof_size, max_sols),
-		ChainTypes::Floonet => new_cuckarood_ctx(edge_bits, proof_size),
-
-		// Everything else is Cuckatoo only
-		_ => new_cuckatoo_ctx(edge_bits, proof_size, max_sols),
+	match pow_context_type(get_chain_type(), header_version, edge_bits) {
+		PoWContextType::Cuckarood => new_cuckarood_ctx(edge_bits, proof_size),
+		PoWContextType::Cuckatoo => new_cuckatoo_ctx(edge_bits, proof_size, max_sols),
 	}
 }
 
@@ -335,6 +425,22 @@ pub fn max_tx_weight() -> u64 {
 	max_block_weight().saturating_sub(coinbase_weight) as u64
 }
 
+/// Override the initial secondary PoW ratio (in percent) on a per-thread basis.
+/// Allows tests to run with 100% secondary or 100% primary PoW.
+pub fn set_local_secondary_pow_ratio(ratio: u32) {
+	assert!(ratio <= 100, "secondary pow ratio is a percentage");
+	SECONDARY_POW_RATIO.with(|r| r.set(Some(ratio)))
+}
+
+/// Initial target ratio (in percent) of secondary PoW to primary PoW.
+/// Look at thread local override first, then fallback to the consensus value,
+/// the same on every chain type.
+pub fn secondary_pow_ratio() -> u32 {
+	SECONDARY_POW_RATIO
+		.with(|r| r.get())
+		.unwrap_or(INITIAL_SECONDARY_POW_RATIO)
+}
+
 /// Horizon at which we can cut-through and do full local pruning
 pub fn cut_through_horizon() -> u32 {
 	match get_chain_type() {
@@ -344,6 +450,16 @@ pub fn cut_through_horizon() -> u32 {
 	}
 }
 
+/// Max relative height of an NRD kernel, a week of blocks on mainnet and floonet.
+/// This is consensus, changing it for mainnet is a hard fork.
+pub fn max_nrd_relative_height() -> u64 {
+	match get_chain_type() {
+		ChainTypes::AutomatedTesting => TESTING_MAX_NRD_RELATIVE_HEIGHT,
+		ChainTypes::UserTesting => TESTING_MAX_NRD_RELATIVE_HEIGHT,
+		_ => WEEK_HEIGHT,
+	}
+}
+
 /// Threshold at which we can request a txhashset (and full blocks from)
 pub fn state_sync_threshold() -> u32 {
 	match get_chain_type() {
@@ -63,17 +63,15 @@ const MAX_SOLS: u32 = 10;
 /// satisfies the requirements of the header.
 pub fn verify_size(bh: &BlockHeader) -> Result<(), Error> {
 	let mut ctx = global::create_pow_context::<u64>(
-		bh.height,
+		Some(bh.version),
 		bh.pow.edge_bits(),
 		bh.pow.proof.nonces.len(),
 		MAX_SOLS,
 	)?;
-	ctx.set_header_nonce(
-		bh.pre_pow()
-			.map_err(|e| ErrorKind::PrePowError(format!("{}", e)))?,
-		None,
-		false,
-	)?;
+	ctx.set_header_preimage(&bh.preimage_hash())?;
+	// only the cycle itself is checked here, the difficulty the proof achieves
+	// is validated against the chain during block processing
+	ctx.set_difficulty(Difficulty::min());
 	ctx.verify(&bh.pow.proof)
 }
 
@@ -106,16 +104,18 @@ pub fn pow_size(
 	loop {
 		// if we found a cycle (not guaranteed) and the proof hash is higher that the
 		// diff, we're all good
-		let mut ctx = global::create_pow_context::<u32>(bh.height, sz, proof_size, MAX_SOLS)?;
+		let mut ctx =
+			global::create_pow_context::<u32>(Some(bh.version), sz, proof_size, MAX_SOLS)?;
 		ctx.set_header_nonce(
 			bh.pre_pow()
 				.map_err(|e| ErrorKind::PrePowError(format!("{}", e)))?,
 			None,
 			true,
 		)?;
+		ctx.set_difficulty(diff);
 		if let Ok(proofs) = ctx.find_cycles() {
 			bh.pow.proof = proofs[0].clone();
-			if bh.pow.to_difficulty(bh.height) >= diff {
+			if bh.pow.to_difficulty(bh.height) >= ctx.get_target_difficulty() {
 				return Ok(());
 			}
 		}
@@ -135,9 +135,10 @@ pub fn pow_size(
 #[cfg(test)]
 mod test {
 	use super::*;
+	use crate::core::HeaderVersion;
 	use crate::genesis;
 	use crate::global;
-	use crate::global::ChainTypes;
+	use crate::global::{ChainTypes, PoWContextType};
 
 	/// We'll be generating genesis blocks differently
 	#[test]
@@ -160,4 +161,72 @@ mod test {
 		assert!(b.header.pow.to_difficulty(0) >= Difficulty::min());
 		assert!(verify_size(&b.header).is_ok());
 	}
+
+	#[test]
+	fn context_target_difficulty() {
+		global::set_local_chain_type(ChainTypes::AutomatedTesting);
+
+		let mut ctx = global::create_pow_context::<u64>(
+			None,
+			global::min_edge_bits(),
+			global::proofsize(),
+			1,
+		)
+		.unwrap();
+		assert_eq!(ctx.get_target_difficulty(), Difficulty::min());
+		ctx.set_difficulty(Difficulty::from_num(1000));
+		assert_eq!(ctx.get_target_difficulty(), Difficulty::from_num(1000));
+	}
+
+	#[test]
+	fn pow_context_type_selection() {
+		let versions = [
+			None,
+			Some(HeaderVersion(1)),
+			Some(HeaderVersion(2)),
+			Some(HeaderVersion(3)),
+		];
+		let cases = [
+			(ChainTypes::Mainnet, 29, PoWContextType::Cuckarood),
+			(ChainTypes::Mainnet, 31, PoWContextType::Cuckatoo),
+			(ChainTypes::Mainnet, 32, PoWContextType::Cuckatoo),
+			(ChainTypes::Floonet, 29, PoWContextType::Cuckarood),
+			(ChainTypes::Floonet, 31, PoWContextType::Cuckatoo),
+			(ChainTypes::Floonet, 32, PoWContextType::Cuckatoo),
+			(ChainTypes::UserTesting, 15, PoWContextType::Cuckatoo),
+			(ChainTypes::UserTesting, 29, PoWContextType::Cuckatoo),
+			(ChainTypes::UserTesting, 31, PoWContextType::Cuckatoo),
+			(ChainTypes::AutomatedTesting, 10, PoWContextType::Cuckatoo),
+			(ChainTypes::AutomatedTesting, 29, PoWContextType::Cuckatoo),
+			(ChainTypes::AutomatedTesting, 31, PoWContextType::Cuckatoo),
+		];
+		for (chain_type, edge_bits, expected) in cases.iter() {
+			for version in versions.iter() {
+				assert_eq!(
+					global::pow_context_type(*chain_type, *version, *edge_bits),
+					*expected,
+					"{:?} {:?} edge_bits {}",
+					chain_type,
+					version,
+					edge_bits
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn context_with_and_without_header_version() {
+		global::set_local_chain_type(ChainTypes::AutomatedTesting);
+
+		for version in [None, Some(HeaderVersion(1)), Some(HeaderVersion(2))].iter() {
+			let ctx = global::create_pow_context::<u64>(
+				*version,
+				global::min_edge_bits(),
+				global::proofsize(),
+				1,
+			)
+			.unwrap();
+			assert_eq!(ctx.get_target_difficulty(), Difficulty::min());
+		}
+	}
 }
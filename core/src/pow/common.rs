@@ -17,6 +17,7 @@
 use crate::pow::error::Error;
 use crate::pow::num::{PrimInt, ToPrimitive};
 use crate::pow::siphash::siphash24;
+use crate::pow::Difficulty;
 use blake2::blake2b::blake2b;
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use std::fmt;
@@ -69,8 +70,12 @@ pub fn set_header_nonce(header: &[u8], nonce: Option<u32>) -> Result<[u64; 4], E
 
 pub fn create_siphash_keys(header: &[u8]) -> Result<[u64; 4], Error> {
 	let h = blake2b(32, &[], &header);
-	let hb = h.as_bytes();
-	let mut rdr = Cursor::new(hb);
+	siphash_keys_from_preimage(h.as_bytes())
+}
+
+/// Siphash keys from the already hashed header (see BlockHeader::preimage_hash)
+pub fn siphash_keys_from_preimage(preimage: &[u8]) -> Result<[u64; 4], Error> {
+	let mut rdr = Cursor::new(preimage);
 	Ok([
 		rdr.read_u64::<LittleEndian>()?,
 		rdr.read_u64::<LittleEndian>()?,
@@ -88,6 +93,7 @@ pub struct CuckooParams {
 	pub siphash_keys: [u64; 4],
 	pub edge_mask: u64,
 	pub node_mask: u64,
+	pub target_difficulty: Difficulty,
 }
 
 impl CuckooParams {
@@ -104,6 +110,7 @@ impl CuckooParams {
 			siphash_keys: [0; 4],
 			edge_mask,
 			node_mask,
+			target_difficulty: Difficulty::min(),
 		})
 	}
 
@@ -113,6 +120,12 @@ impl CuckooParams {
 		Ok(())
 	}
 
+	/// Reset the main keys used for siphash from the header preimage hash
+	pub fn reset_header_preimage(&mut self, preimage: &[u8]) -> Result<(), Error> {
+		self.siphash_keys = siphash_keys_from_preimage(preimage)?;
+		Ok(())
+	}
+
 	/// Return siphash masked for type
 	pub fn sipnode(&self, edge: u64, uorv: u64) -> Result<u64, Error> {
 		let hash_u64 = siphash24(&self.siphash_keys, 2 * edge + uorv);
@@ -23,11 +23,12 @@
 //! In Cuckaroo, edges are calculated by repeatedly hashing the seeds to
 //! obtain blocks of values. Nodes are then extracted from those edges.
 
+use crate::core::hash::Hash;
 use crate::global;
 use crate::pow::common::CuckooParams;
 use crate::pow::error::{Error, ErrorKind};
 use crate::pow::siphash::siphash_block;
-use crate::pow::{PoWContext, Proof};
+use crate::pow::{Difficulty, PoWContext, Proof};
 
 /// Instantiate a new CuckarooContext as a PowContext. Note that this can't
 /// be moved in the PoWContext trait as this particular trait needs to be
@@ -52,6 +53,18 @@ impl PoWContext for CuckarooContext {
 		self.params.reset_header_nonce(header, nonce)
 	}
 
+	fn set_header_preimage(&mut self, preimage: &Hash) -> Result<(), Error> {
+		self.params.reset_header_preimage(preimage.as_bytes())
+	}
+
+	fn set_difficulty(&mut self, target: Difficulty) {
+		self.params.target_difficulty = target;
+	}
+
+	fn get_target_difficulty(&self) -> Difficulty {
+		self.params.target_difficulty
+	}
+
 	fn find_cycles(&mut self) -> Result<Vec<Proof>, Error> {
 		unimplemented!()
 	}
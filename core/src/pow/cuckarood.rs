@@ -22,11 +22,12 @@
 //! a rotation by 25, halves the number of graph nodes in each partition,
 //! and requires cycles to alternate between even- and odd-indexed edges.
 
+use crate::core::hash::Hash;
 use crate::global;
 use crate::pow::common::CuckooParams;
 use crate::pow::error::{Error, ErrorKind};
 use crate::pow::siphash::siphash_block;
-use crate::pow::{PoWContext, Proof};
+use crate::pow::{Difficulty, PoWContext, Proof};
 
 /// Instantiate a new CuckaroodContext as a PowContext. Note that this can't
 /// be moved in the PoWContext trait as this particular trait needs to be
@@ -51,6 +52,18 @@ impl PoWContext for CuckaroodContext {
 		self.params.reset_header_nonce(header, nonce)
 	}
 
+	fn set_header_preimage(&mut self, preimage: &Hash) -> Result<(), Error> {
+		self.params.reset_header_preimage(preimage.as_bytes())
+	}
+
+	fn set_difficulty(&mut self, target: Difficulty) {
+		self.params.target_difficulty = target;
+	}
+
+	fn get_target_difficulty(&self) -> Difficulty {
+		self.params.target_difficulty
+	}
+
 	fn find_cycles(&mut self) -> Result<Vec<Proof>, Error> {
 		unimplemented!()
 	}
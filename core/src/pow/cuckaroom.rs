@@ -21,11 +21,12 @@
 //! states, reverts to standard siphash, and most importantly, identifies cycles
 //! in a mono-partite graph, from which it derives the letter 'm'.
 
+use crate::core::hash::Hash;
 use crate::global;
 use crate::pow::common::CuckooParams;
 use crate::pow::error::{Error, ErrorKind};
 use crate::pow::siphash::siphash_block;
-use crate::pow::{PoWContext, Proof};
+use crate::pow::{Difficulty, PoWContext, Proof};
 
 /// Instantiate a new CuckaroomContext as a PowContext. Note that this can't
 /// be moved in the PoWContext trait as this particular trait needs to be
@@ -50,6 +51,18 @@ impl PoWContext for CuckaroomContext {
 		self.params.reset_header_nonce(header, nonce)
 	}
 
+	fn set_header_preimage(&mut self, preimage: &Hash) -> Result<(), Error> {
+		self.params.reset_header_preimage(preimage.as_bytes())
+	}
+
+	fn set_difficulty(&mut self, target: Difficulty) {
+		self.params.target_difficulty = target;
+	}
+
+	fn get_target_difficulty(&self) -> Difficulty {
+		self.params.target_difficulty
+	}
+
 	fn find_cycles(&mut self) -> Result<Vec<Proof>, Error> {
 		unimplemented!()
 	}
@@ -12,10 +12,11 @@
 // limitations under the License.
 
 //! Implementation of Cuckatoo Cycle designed by John Tromp.
+use crate::core::hash::Hash;
 use crate::global;
 use crate::pow::common::{CuckooParams, Link};
 use crate::pow::error::{Error, ErrorKind};
-use crate::pow::{PoWContext, Proof};
+use crate::pow::{Difficulty, PoWContext, Proof};
 use byteorder::{BigEndian, WriteBytesExt};
 use croaring::Bitmap;
 use std::mem;
@@ -174,6 +175,18 @@ impl PoWContext for CuckatooContext {
 		self.set_header_nonce_impl(header, nonce, solve)
 	}
 
+	fn set_header_preimage(&mut self, preimage: &Hash) -> Result<(), Error> {
+		self.params.reset_header_preimage(preimage.as_bytes())
+	}
+
+	fn set_difficulty(&mut self, target: Difficulty) {
+		self.params.target_difficulty = target;
+	}
+
+	fn get_target_difficulty(&self) -> Difficulty {
+		self.params.target_difficulty
+	}
+
 	fn find_cycles(&mut self) -> Result<Vec<Proof>, Error> {
 		let num_edges = self.params.num_edges;
 		self.find_cycles_iter(0..num_edges)
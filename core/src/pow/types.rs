@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use crate::consensus::{graph_weight, MIN_DIFFICULTY, SECOND_POW_EDGE_BITS};
-use crate::core::hash::{DefaultHashable, Hashed};
+use crate::core::hash::{DefaultHashable, Hash, Hashed};
 use crate::global;
 use crate::pow::error::Error;
 use crate::ser::{self, Readable, Reader, Writeable, Writer};
@@ -36,6 +36,13 @@ pub trait PoWContext {
 		nonce: Option<u32>,
 		solve: bool,
 	) -> Result<(), Error>;
+	/// Sets the header from its preimage hash (see BlockHeader::preimage_hash),
+	/// only sets up for validation, not for a solve
+	fn set_header_preimage(&mut self, preimage: &Hash) -> Result<(), Error>;
+	/// Sets the target difficulty solutions are expected to meet
+	fn set_difficulty(&mut self, target: Difficulty);
+	/// Target difficulty currently set on this context (minimum if never set)
+	fn get_target_difficulty(&self) -> Difficulty;
 	/// find solutions using the stored parameters and header
 	fn find_cycles(&mut self) -> Result<Vec<Proof>, Error>;
 	/// Verify a solution with the stored parameters
@@ -328,6 +328,9 @@ impl ProtocolVersion {
 	/// The max protocol version supported.
 	pub const MAX: u32 = std::u32::MAX;
 
+	/// The oldest protocol version we are still able to serialize data for.
+	pub const MIN: u32 = 1;
+
 	/// Protocol version as u32 to allow for convenient exhaustive matching on values.
 	pub fn value(self) -> u32 {
 		self.0
@@ -347,6 +350,30 @@ impl ProtocolVersion {
 	pub fn local_db() -> ProtocolVersion {
 		ProtocolVersion(1)
 	}
+
+	/// All protocol versions between min and max (inclusive), in ascending order.
+	/// Convenient for exercising serialization across every supported version.
+	pub fn range(
+		min: ProtocolVersion,
+		max: ProtocolVersion,
+	) -> impl Iterator<Item = ProtocolVersion> {
+		(min.0..=max.0).map(ProtocolVersion)
+	}
+
+	/// Version to use when producing data for a peer speaking the target version.
+	/// We never go above our own version, so a newer target gives us back our own
+	/// version. A target older than MIN can't represent our data and is an error.
+	pub fn downgrade_to(self, target: ProtocolVersion) -> Result<ProtocolVersion, Error> {
+		if target.0 < ProtocolVersion::MIN {
+			return Err(Error::UnsupportedProtocolVersion(format!(
+				"can't downgrade from version {} to {}, oldest supported is {}",
+				self,
+				target,
+				ProtocolVersion::MIN
+			)));
+		}
+		Ok(std::cmp::min(self, target))
+	}
 }
 
 impl fmt::Display for ProtocolVersion {
@@ -422,6 +449,35 @@ pub fn ser_vec<W: Writeable>(thing: &W, version: ProtocolVersion) -> Result<Vec<
 	Ok(vec)
 }
 
+/// Size in bytes of the serialized writeable, counted without allocating the
+/// serialized bytes.
+pub fn ser_size<W: Writeable>(thing: &W, version: ProtocolVersion) -> Result<u64, Error> {
+	let mut writer = SizeWriter { size: 0, version };
+	thing.write(&mut writer)?;
+	Ok(writer.size)
+}
+
+/// Writer that only counts the bytes written to it.
+struct SizeWriter {
+	size: u64,
+	version: ProtocolVersion,
+}
+
+impl Writer for SizeWriter {
+	fn serialization_mode(&self) -> SerializationMode {
+		SerializationMode::Full
+	}
+
+	fn write_fixed_bytes<T: AsRef<[u8]>>(&mut self, bytes: T) -> Result<(), Error> {
+		self.size += bytes.as_ref().len() as u64;
+		Ok(())
+	}
+
+	fn protocol_version(&self) -> ProtocolVersion {
+		self.version
+	}
+}
+
 /// Utility to read from a binary source
 pub struct BinReader<'a, R: Read> {
 	source: &'a mut R,
@@ -583,6 +639,96 @@ impl<'a> Reader for StreamingReader<'a> {
 	}
 }
 
+/// Max number of bytes allowed to be read through a LimitedReader.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SizeLimit(pub usize);
+
+/// Reader wrapper failing with TooLargeReadErr as soon as the total bytes read
+/// would exceed its SizeLimit. Used when deserializing untrusted data, so a peer
+/// can't make us read (and allocate) an arbitrary amount of data.
+pub struct LimitedReader<'a, R: Reader> {
+	reader: &'a mut R,
+	limit: SizeLimit,
+	bytes_read: usize,
+}
+
+impl<'a, R: Reader> LimitedReader<'a, R> {
+	/// Wraps the provided reader, enforcing the provided limit.
+	pub fn new(reader: &'a mut R, limit: SizeLimit) -> LimitedReader<'a, R> {
+		LimitedReader {
+			reader,
+			limit,
+			bytes_read: 0,
+		}
+	}
+
+	/// Total bytes read via this reader so far.
+	pub fn bytes_read(&self) -> usize {
+		self.bytes_read
+	}
+
+	// Account for len more bytes, checked before reading (and allocating) them.
+	fn consume(&mut self, len: usize) -> Result<(), Error> {
+		let total = self.bytes_read.saturating_add(len);
+		if total > self.limit.0 {
+			return Err(Error::TooLargeReadErr(format!(
+				"Try to read {} bytes, limit is {}",
+				total, self.limit.0
+			)));
+		}
+		self.bytes_read = total;
+		Ok(())
+	}
+}
+
+impl<'a, R: Reader> Reader for LimitedReader<'a, R> {
+	fn read_u8(&mut self) -> Result<u8, Error> {
+		self.consume(1)?;
+		self.reader.read_u8()
+	}
+	fn read_u16(&mut self) -> Result<u16, Error> {
+		self.consume(2)?;
+		self.reader.read_u16()
+	}
+	fn read_u32(&mut self) -> Result<u32, Error> {
+		self.consume(4)?;
+		self.reader.read_u32()
+	}
+	fn read_i32(&mut self) -> Result<i32, Error> {
+		self.consume(4)?;
+		self.reader.read_i32()
+	}
+	fn read_u64(&mut self) -> Result<u64, Error> {
+		self.consume(8)?;
+		self.reader.read_u64()
+	}
+	fn read_i64(&mut self) -> Result<i64, Error> {
+		self.consume(8)?;
+		self.reader.read_i64()
+	}
+
+	/// Read a variable size vector, the len prefix is checked against the limit
+	/// before anything gets allocated.
+	fn read_bytes_len_prefix(&mut self) -> Result<Vec<u8>, Error> {
+		let len = self.read_u64()?;
+		self.read_fixed_bytes(len as usize)
+	}
+
+	fn read_fixed_bytes(&mut self, len: usize) -> Result<Vec<u8>, Error> {
+		self.consume(len)?;
+		self.reader.read_fixed_bytes(len)
+	}
+
+	fn expect_u8(&mut self, val: u8) -> Result<u8, Error> {
+		self.consume(1)?;
+		self.reader.expect_u8(val)
+	}
+
+	fn protocol_version(&self) -> ProtocolVersion {
+		self.reader.protocol_version()
+	}
+}
+
 impl Readable for Commitment {
 	fn read<R: Reader>(reader: &mut R) -> Result<Commitment, Error> {
 		let a = reader.read_fixed_bytes(PEDERSEN_COMMITMENT_SIZE)?;
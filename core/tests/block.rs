@@ -363,6 +363,53 @@ fn remove_coinbase_output_flag() {
 	);
 }
 
+#[test]
+// the coinbase only verifies against the reward at the block height
+fn verify_coinbase_reward_at_height() {
+	test_setup();
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let b = new_block(&[], &keychain, &builder, &prev, &key_id);
+
+	assert!(b.verify_coinbase_reward(b.header.height).is_ok());
+	assert_ne!(
+		consensus::calc_mwc_block_reward(0),
+		consensus::calc_mwc_block_reward(b.header.height)
+	);
+	assert_eq!(b.verify_coinbase_reward(0), Err(Error::CoinbaseSumMismatch));
+}
+
+#[test]
+fn header_is_before_hf() {
+	// automated testing - HF{1|2|3} at block heights {3, 6, 9}
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+
+	let header_at = |height| BlockHeader {
+		height,
+		version: consensus::header_version(height),
+		..BlockHeader::default()
+	};
+
+	assert!(header_at(0).is_before_hf(1));
+	assert!(header_at(consensus::TESTING_FIRST_HARD_FORK - 1).is_before_hf(1));
+	assert!(!header_at(consensus::TESTING_FIRST_HARD_FORK).is_before_hf(1));
+	assert!(header_at(consensus::TESTING_FIRST_HARD_FORK).is_before_hf(2));
+	assert!(header_at(consensus::TESTING_THIRD_HARD_FORK - 1).is_before_hf(3));
+	assert!(!header_at(consensus::TESTING_THIRD_HARD_FORK).is_before_hf(3));
+
+	// No HF4 scheduled, never reached.
+	assert!(header_at(u64::MAX).is_before_hf(4));
+	assert_eq!(consensus::hard_fork_height(0), None);
+
+	// Header version is bumped at every hard fork.
+	for (i, height) in consensus::hard_fork_heights().iter().enumerate() {
+		assert_eq!(header_at(*height).version, HeaderVersion(i as u16 + 2));
+		assert_eq!(header_at(height - 1).version, HeaderVersion(i as u16 + 1));
+	}
+}
+
 #[test]
 // test that flipping the COINBASE flag on the kernel features
 // invalidates the block and specifically it causes verify_coinbase to fail
@@ -725,6 +772,30 @@ fn hydrate_empty_compact_block() {
 	assert_eq!(hb.kernels(), b.kernels());
 }
 
+#[test]
+fn expand_compact_block() {
+	test_setup();
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let tx1 = tx1i2o();
+	let tx2 = tx2i1o();
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let b = new_block(&[tx1.clone()], &keychain, &builder, &prev, &key_id);
+	let cb: CompactBlock = b.clone().into();
+
+	// the tx kernel can't be resolved without its tx
+	assert!(cb.expand(&[]).is_err());
+	assert!(cb.expand(&[tx2.clone()]).is_err());
+
+	// unrelated txs are ignored
+	let eb = cb.expand(&[tx2, tx1]).unwrap();
+	assert_eq!(eb.header, b.header);
+	assert_eq!(eb.inputs(), b.inputs());
+	assert_eq!(eb.outputs(), b.outputs());
+	assert_eq!(eb.kernels(), b.kernels());
+}
+
 #[test]
 fn serialize_deserialize_compact_block() {
 	test_setup();
@@ -879,6 +950,37 @@ fn validate_header_proof() {
 	.is_err());
 }
 
+#[test]
+fn header_preimage_hash() {
+	test_setup();
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let prev = BlockHeader::default();
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let b = new_block(&[], &keychain, &builder, &prev, &key_id);
+
+	// the proof isn't part of the preimage
+	let mut header = b.header.clone();
+	header.pow.proof = pow::Proof::random(global::proofsize());
+	assert_eq!(header.preimage_hash(), b.header.preimage_hash());
+
+	// but the nonce is
+	header.pow.nonce += 1;
+	assert_ne!(header.preimage_hash(), b.header.preimage_hash());
+}
+
+#[test]
+fn header_is_genesis() {
+	test_setup();
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let prev = BlockHeader::default();
+	assert!(prev.is_genesis());
+	let key_id = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let b = new_block(&[], &keychain, &builder, &prev, &key_id);
+	assert!(!b.header.is_genesis());
+}
+
 // Test coverage for verifying cut-through during block validation.
 // It is not valid for a block to spend an output and produce a new output with the same commitment.
 // This test covers the case where a plain output is spent, producing a plain output with the same commitment.
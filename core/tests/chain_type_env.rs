@@ -0,0 +1,57 @@
+// Copyright 2020 The Grin Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grin_core::global::{self, ChainTypes, CHAIN_TYPE_ENV_VAR};
+use std::env;
+
+// The environment is shared by the whole process, so all the cases live in a
+// single test (and a test binary of their own) to keep them from racing.
+#[test]
+fn chain_type_env_override() {
+	// unset, the chain type set by the code is used
+	env::remove_var(CHAIN_TYPE_ENV_VAR);
+	assert_eq!(global::get_chain_type_from_env(), None);
+	global::set_local_chain_type(ChainTypes::AutomatedTesting);
+	assert_eq!(global::get_chain_type(), ChainTypes::AutomatedTesting);
+
+	// valid, takes priority over a testing chain type
+	env::set_var(CHAIN_TYPE_ENV_VAR, "user");
+	assert_eq!(
+		global::get_chain_type_from_env(),
+		Some(ChainTypes::UserTesting)
+	);
+	global::set_local_chain_type(ChainTypes::AutomatedTesting);
+	assert_eq!(global::get_chain_type(), ChainTypes::UserTesting);
+
+	// invalid, ignored
+	env::set_var(CHAIN_TYPE_ENV_VAR, "notachain");
+	assert_eq!(global::get_chain_type_from_env(), None);
+	global::set_local_chain_type(ChainTypes::AutomatedTesting);
+	assert_eq!(global::get_chain_type(), ChainTypes::AutomatedTesting);
+
+	// production chain types never get overridden
+	env::set_var(CHAIN_TYPE_ENV_VAR, "auto");
+	global::set_local_chain_type(ChainTypes::Floonet);
+	assert_eq!(global::get_chain_type(), ChainTypes::Floonet);
+	global::set_local_chain_type(ChainTypes::Mainnet);
+	assert_eq!(global::get_chain_type(), ChainTypes::Mainnet);
+
+	// the global chain type gets the same treatment
+	env::set_var(CHAIN_TYPE_ENV_VAR, "user");
+	assert!(global::init_global_chain_type_once(
+		ChainTypes::AutomatedTesting
+	));
+	assert_eq!(global::GLOBAL_CHAIN_TYPE.borrow(), ChainTypes::UserTesting);
+
+	env::remove_var(CHAIN_TYPE_ENV_VAR);
+}
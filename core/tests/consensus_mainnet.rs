@@ -535,6 +535,46 @@ fn test_secondary_pow_ratio() {
 	}
 }
 
+#[test]
+fn test_secondary_pow_ratio_override() {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	assert_eq!(global::secondary_pow_ratio(), INITIAL_SECONDARY_POW_RATIO);
+
+	// 100% secondary PoW
+	global::set_local_secondary_pow_ratio(100);
+	assert_eq!(secondary_pow_ratio(1), 100);
+	assert_eq!(secondary_pow_ratio(YEAR_HEIGHT / 100), 99);
+
+	// 100% primary PoW
+	global::set_local_secondary_pow_ratio(0);
+	assert_eq!(secondary_pow_ratio(1), 0);
+	assert_eq!(secondary_pow_ratio(YEAR_HEIGHT), 0);
+}
+
+#[test]
+fn test_verify_secondary_scaling() {
+	global::set_local_chain_type(global::ChainTypes::Mainnet);
+
+	let window = DIFFICULTY_ADJUST_WINDOW;
+	let hi = HeaderInfo::from_diff_scaling(Difficulty::from_num(10), 100);
+	let diff_data = (0..window).map(|_| hi.clone()).collect::<Vec<_>>();
+	let expected = secondary_pow_scaling(1, &diff_data);
+
+	// exact expected value
+	assert!(verify_secondary_scaling(1, expected, expected));
+	// one above, one below
+	assert!(!verify_secondary_scaling(1, expected + 1, expected));
+	assert!(!verify_secondary_scaling(1, expected - 1, expected));
+	// never below the minimum scale
+	let min_scale = MIN_AR_SCALE as u32;
+	assert!(verify_secondary_scaling(1, min_scale, min_scale));
+	assert!(!verify_secondary_scaling(1, min_scale - 1, min_scale - 1));
+	// genesis carries the initial graph weight
+	let initial = global::initial_graph_weight();
+	assert!(verify_secondary_scaling(0, initial, expected));
+	assert!(!verify_secondary_scaling(0, initial + 1, initial + 1));
+}
+
 #[test]
 fn test_secondary_pow_scale() {
 	global::set_local_chain_type(global::ChainTypes::Mainnet);
@@ -21,11 +21,12 @@ use self::core::core::block::Error::KernelLockHeight;
 use self::core::core::hash::{Hashed, ZERO_HASH};
 use self::core::core::verifier_cache::{LruVerifierCache, VerifierCache};
 use self::core::core::{
-	aggregate, deaggregate, KernelFeatures, KernelProof, Output, OutputFeatures, OutputIdentifier,
-	Transaction, TxKernel, Weighting,
+	aggregate, deaggregate, CommitWrapper, Inputs, KernelFeatures, KernelProof, Output,
+	OutputFeatures, OutputIdentifier, Transaction, TxKernel, Weighting,
 };
 use self::core::libtx::build::{self, initial_tx, input, output, with_excess};
-use self::core::libtx::{aggsig, ProofBuilder};
+use self::core::libtx::{self, aggsig, ProofBuilder};
+use self::core::ser::Readable;
 use self::core::{global, ser};
 use crate::common::{new_block, tx1i1o, tx1i2o, tx2i1o};
 use grin_core as core;
@@ -126,6 +127,83 @@ fn tx_double_ser_deser() {
 	assert_eq!(dtx.hash(), dtx2.hash());
 }
 
+#[test]
+fn protocol_version_downgrade_to() {
+	let local = ser::ProtocolVersion(4);
+	for v in 1..=4 {
+		let target = ser::ProtocolVersion(v);
+		assert_eq!(local.downgrade_to(target).unwrap(), target);
+	}
+	// never upgrade past our own version
+	assert_eq!(local.downgrade_to(ser::ProtocolVersion(5)).unwrap(), local);
+	match local.downgrade_to(ser::ProtocolVersion(0)) {
+		Err(ser::Error::UnsupportedProtocolVersion(_)) => {}
+		res => panic!(
+			"expected an unsupported protocol version error, got {:?}",
+			res
+		),
+	}
+}
+
+#[test]
+fn tx_deser_size_limit() {
+	test_setup();
+	let tx = tx2i1o();
+	let mut vec = Vec::new();
+	ser::serialize_default(&mut vec, &tx).expect("serialization failed");
+
+	// exactly the size of the tx is fine
+	let mut source = &vec[..];
+	let mut reader = ser::BinReader::new(&mut source, ser::ProtocolVersion::local());
+	let mut limited = ser::LimitedReader::new(&mut reader, ser::SizeLimit(vec.len()));
+	let dtx = Transaction::read(&mut limited).unwrap();
+	assert_eq!(limited.bytes_read(), vec.len());
+	assert_eq!(tx.hash(), dtx.hash());
+
+	// one byte less is not
+	let mut source = &vec[..];
+	let mut reader = ser::BinReader::new(&mut source, ser::ProtocolVersion::local());
+	let mut limited = ser::LimitedReader::new(&mut reader, ser::SizeLimit(vec.len() - 1));
+	match Transaction::read(&mut limited) {
+		Err(ser::Error::TooLargeReadErr(_)) => {}
+		res => panic!("expected a too large read error, got {:?}", res),
+	}
+}
+
+#[test]
+fn tx_is_standard() {
+	test_setup();
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let key_id1 = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let key_id2 = ExtKeychain::derive_key_id(1, 2, 0, 0, 0);
+
+	// tiny fee, only standard with a low enough base fee
+	let tx = tx1i1o();
+	assert!(!tx.is_standard());
+	assert!(tx.is_standard_with_fee_base(0));
+
+	let fee = tx.tx_weight() * libtx::DEFAULT_BASE_FEE;
+	let tx = build::transaction(
+		KernelFeatures::Plain { fee },
+		&[input(fee + 5, key_id1.clone()), output(5, key_id2.clone())],
+		&keychain,
+		&builder,
+	)
+	.unwrap();
+	assert!(tx.is_standard());
+
+	// zero fee kernels are never standard
+	let tx = build::transaction(
+		KernelFeatures::Plain { fee: 0 },
+		&[input(5, key_id1), output(5, key_id2)],
+		&keychain,
+		&builder,
+	)
+	.unwrap();
+	assert!(!tx.is_standard_with_fee_base(0));
+}
+
 #[test]
 fn test_zero_commit_fails() {
 	test_setup();
@@ -278,6 +356,53 @@ fn transaction_cut_through() {
 	assert!(tx3.validate(Weighting::AsTransaction, vc.clone()).is_ok());
 }
 
+// Merge two transactions, the second spending the output of the first, without
+// cut-through and check the spent output and its input are removed afterwards.
+#[test]
+fn transaction_cut_through_spent_output() {
+	test_setup();
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let builder = ProofBuilder::new(&keychain);
+	let key_id1 = ExtKeychain::derive_key_id(1, 1, 0, 0, 0);
+	let key_id2 = ExtKeychain::derive_key_id(1, 2, 0, 0, 0);
+	let key_id3 = ExtKeychain::derive_key_id(1, 3, 0, 0, 0);
+
+	let tx1 = build::transaction(
+		KernelFeatures::Plain { fee: 2 },
+		&[input(10, key_id1), output(8, key_id2.clone())],
+		&keychain,
+		&builder,
+	)
+	.unwrap();
+	let tx2 = build::transaction(
+		KernelFeatures::Plain { fee: 2 },
+		&[input(8, key_id2), output(6, key_id3)],
+		&keychain,
+		&builder,
+	)
+	.unwrap();
+
+	let vc = verifier_cache();
+	let aggregated = aggregate(&[tx1.clone(), tx2.clone()]).unwrap();
+
+	let inputs1: Vec<CommitWrapper> = tx1.inputs().into();
+	let inputs2: Vec<CommitWrapper> = tx2.inputs().into();
+	let inputs = [inputs1, inputs2].concat();
+	let outputs = [tx1.outputs(), tx2.outputs()].concat();
+	let kernels = [tx1.kernels(), tx2.kernels()].concat();
+	let tx3 = Transaction::new(Inputs::from(inputs.as_slice()), &outputs, &kernels)
+		.with_offset(aggregated.offset.clone());
+	assert_eq!(tx3.inputs().len(), 2);
+	assert_eq!(tx3.outputs().len(), 2);
+
+	let tx4 = tx3.cut_through().unwrap();
+	assert_eq!(tx4.inputs(), tx1.inputs());
+	assert_eq!(tx4.outputs(), tx2.outputs());
+	assert_eq!(tx4.kernels(), tx3.kernels());
+	assert_eq!(tx4, aggregated);
+	assert!(tx4.validate(Weighting::AsTransaction, vc.clone()).is_ok());
+}
+
 // Attempt to deaggregate a multi-kernel transaction in a different way
 #[test]
 fn multi_kernel_transaction_deaggregation() {
@@ -704,3 +829,43 @@ pub fn test_verify_2i1o_sig() {
 	tx.validate(Weighting::AsTransaction, verifier_cache())
 		.unwrap();
 }
+
+#[test]
+fn init_global_chain_type_once() {
+	// may or may not be the first initialization, but never twice
+	global::init_global_chain_type_once(global::ChainTypes::AutomatedTesting);
+	assert!(!global::init_global_chain_type_once(
+		global::ChainTypes::AutomatedTesting
+	));
+	assert!(global::GLOBAL_CHAIN_TYPE.is_init());
+}
+
+#[test]
+fn chain_type_names() {
+	let names = [
+		(global::ChainTypes::AutomatedTesting, "auto"),
+		(global::ChainTypes::UserTesting, "user"),
+		(global::ChainTypes::Floonet, "floo"),
+		(global::ChainTypes::Mainnet, "main"),
+	];
+	for (chain_type, name) in names.iter() {
+		assert_eq!(chain_type.to_string(), *name);
+		assert_eq!(chain_type.shortname(), *name);
+		assert_eq!(format!("{}", chain_type), *name);
+
+		// round trip, whatever the case
+		assert_eq!(name.parse::<global::ChainTypes>(), Ok(*chain_type));
+		assert_eq!(
+			name.to_uppercase().parse::<global::ChainTypes>(),
+			Ok(*chain_type)
+		);
+		let variant = format!("{:?}", chain_type);
+		assert_eq!(variant.parse::<global::ChainTypes>(), Ok(*chain_type));
+		assert_eq!(
+			variant.to_lowercase().parse::<global::ChainTypes>(),
+			Ok(*chain_type)
+		);
+	}
+	assert!("testnet".parse::<global::ChainTypes>().is_err());
+	assert!("".parse::<global::ChainTypes>().is_err());
+}
@@ -15,10 +15,12 @@
 //! Transaction integration tests
 
 pub mod common;
-use crate::common::tx1i10_v2_compatible;
+use crate::common::{tx1i10_v2_compatible, tx2i1o};
 use crate::core::core::transaction::{self, Error};
 use crate::core::core::verifier_cache::LruVerifierCache;
-use crate::core::core::{KernelFeatures, Output, OutputFeatures, Transaction, Weighting};
+use crate::core::core::{
+	CompactTransaction, KernelFeatures, Output, OutputFeatures, Transaction, Weighting,
+};
 use crate::core::global;
 use crate::core::libtx::build;
 use crate::core::libtx::proof::{self, ProofBuilder};
@@ -208,3 +210,26 @@ fn test_verify_cut_through_coinbase() -> Result<(), Error> {
 
 	Ok(())
 }
+
+#[test]
+fn test_compact_transaction_ser_deser() {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	let tx = tx2i1o();
+	let ctx = tx.to_compact(42);
+
+	assert_eq!(ctx.nonce, 42);
+	assert_eq!(ctx.input_ids().len(), 2);
+	assert_eq!(ctx.outputs(), tx.outputs());
+	assert_eq!(ctx.kernels(), tx.kernels());
+
+	let mut vec = Vec::new();
+	ser::serialize_default(&mut vec, &ctx).expect("serialization failed");
+	let ctx2: CompactTransaction = ser::deserialize_default(&mut &vec[..]).unwrap();
+	assert_eq!(ctx.offset, ctx2.offset);
+	assert_eq!(ctx.input_ids(), ctx2.input_ids());
+	assert_eq!(ctx.outputs(), ctx2.outputs());
+	assert_eq!(ctx.kernels(), ctx2.kernels());
+
+	// different nonce, different short_ids
+	assert_ne!(tx.to_compact(43).input_ids(), ctx.input_ids());
+}
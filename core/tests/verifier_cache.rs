@@ -14,7 +14,7 @@
 
 pub mod common;
 
-use self::core::core::verifier_cache::{LruVerifierCache, VerifierCache};
+use self::core::core::verifier_cache::{LruVerifierCache, Stats, VerifierCache};
 use self::core::core::{Output, OutputFeatures};
 use self::core::libtx::proof;
 use grin_core as core;
@@ -59,3 +59,41 @@ fn test_verifier_cache_rangeproofs() {
 		assert_eq!(unverified, vec![]);
 	}
 }
+
+#[test]
+fn test_verifier_cache_stats() {
+	let mut cache = LruVerifierCache::with_capacity(1, 10);
+	assert_eq!(cache.stats(), Stats::default());
+
+	let keychain = ExtKeychain::from_random_seed(false).unwrap();
+	let switch = SwitchCommitmentType::Regular;
+	let builder = proof::ProofBuilder::new(&keychain);
+	let outputs: Vec<_> = (1..=2)
+		.map(|i| {
+			let key_id = ExtKeychain::derive_key_id(1, i, 0, 0, 0);
+			let commit = keychain.commit(5, &key_id, switch).unwrap();
+			let proof =
+				proof::create(&keychain, &builder, 5, &key_id, switch, commit, None).unwrap();
+			Output::new_interactive(OutputFeatures::Plain, commit, proof)
+		})
+		.collect();
+
+	// Both outputs miss, only one of them fits in the cache.
+	let unverified = cache.filter_rangeproof_unverified(&outputs);
+	cache.add_rangeproof_verified(unverified);
+	let stats = cache.stats();
+	assert_eq!(stats.rangeproof_size, 1);
+	assert_eq!(stats.rangeproof_misses, 2);
+	assert_eq!(stats.rangeproof_hits, 0);
+
+	// The most recently added output is a hit, the evicted one a miss again.
+	assert_eq!(
+		cache.filter_rangeproof_unverified(&outputs),
+		vec![outputs[0]]
+	);
+	let stats = cache.stats();
+	assert_eq!(stats.rangeproof_hits, 1);
+	assert_eq!(stats.rangeproof_misses, 3);
+	assert_eq!(stats.kernel_size, 0);
+	assert_eq!(stats.kernel_hits + stats.kernel_misses, 0);
+}
@@ -41,13 +41,6 @@ const NONCES_CAP: usize = 100;
 /// 10 should be enough since most of servers don't have more than 10 IP addresses.
 const ADDRS_CAP: usize = 10;
 
-/// The initial Hand message should come in immediately after the connection is initiated.
-/// But for consistency use the same timeout for reading both Hand and Shake messages.
-const HAND_READ_TIMEOUT: Duration = Duration::from_millis(10_000);
-
-/// We need to allow time for the peer to receive our Hand message and send back a Shake reply.
-const SHAKE_READ_TIMEOUT: Duration = Duration::from_millis(10_000);
-
 /// Fail fast when trying to write a Hand message to the tcp stream.
 /// If we cannot write it within a couple of seconds then something has likely gone wrong.
 const HAND_WRITE_TIMEOUT: Duration = Duration::from_millis(2_000);
@@ -88,15 +81,32 @@ impl Handshake {
 		}
 	}
 
+	/// Timeout for reading the Hand and Shake messages. The initial Hand message should
+	/// come in immediately after the connection is initiated, the Shake reply needs time
+	/// for the peer to receive our Hand. Both take a lot longer over Tor.
+	fn read_timeout(&self) -> Duration {
+		let secs = match self.onion_address {
+			Some(_) => self.config.tor_peer_connect_timeout_secs(),
+			None => self.config.peer_connect_timeout_secs(),
+		};
+		Duration::from_secs(secs)
+	}
+
 	/// Select a protocol version here that we know is supported by both us and the remote peer.
 	///
-	/// Current strategy is to simply use `min(local, remote)`.
-	///
-	/// We can enforce "minimum" protocol version here in the future
-	/// by raising an error and forcing the connection to close.
+	/// Current strategy is to downgrade to `min(local, remote)`, the highest version
+	/// both sides speak, so a peer on an older version is still talked to in its own
+	/// version. Only a remote version below `ProtocolVersion::MIN` closes the
+	/// connection, we can't serialize for it.
 	///
 	fn negotiate_protocol_version(&self, other: ProtocolVersion) -> Result<ProtocolVersion, Error> {
-		let version = std::cmp::min(self.protocol_version, other);
+		let version = self.protocol_version.downgrade_to(other)?;
+		if version != self.protocol_version {
+			debug!(
+				"Peer speaks protocol version {}, falling back to it from {}",
+				other, self.protocol_version
+			);
+		}
 		Ok(version)
 	}
 
@@ -112,7 +122,7 @@ impl Handshake {
 		// Once the peer is up and running we will set new values for these.
 		// We initiate this connection, writing a Hand message and read a Shake reply.
 		let _ = conn.set_write_timeout(Some(HAND_WRITE_TIMEOUT));
-		let _ = conn.set_read_timeout(Some(SHAKE_READ_TIMEOUT));
+		let _ = conn.set_read_timeout(Some(self.read_timeout()));
 
 		// prepare the first part of the handshake
 		let nonce = self.next_nonce();
@@ -146,6 +156,9 @@ impl Handshake {
 			});
 		}
 
+		// Everything we send from now on is in the version agreed with the peer.
+		let negotiated_version = self.negotiate_protocol_version(shake.version)?;
+
 		if shake.capabilities.contains(Capabilities::TOR_ADDRESS) && self.onion_address.is_some() {
 			let onion_address = self.onion_address.as_ref().unwrap().to_string();
 			debug!(
@@ -155,14 +168,12 @@ impl Handshake {
 
 			// send tor address
 			let tor_address = TorAddress::new(onion_address);
-			let msg = Msg::new(Type::TorAddress, tor_address, self.protocol_version)?;
+			let msg = Msg::new(Type::TorAddress, tor_address, negotiated_version)?;
 			write_message(conn, &msg, self.tracker.clone())?;
 		} else {
 			debug!("non-Tor peer {:?}", self_addr);
 		}
 
-		let negotiated_version = self.negotiate_protocol_version(shake.version)?;
-
 		let peer_info = PeerInfo {
 			capabilities: shake.capabilities,
 			user_agent: shake.user_agent,
@@ -193,7 +204,6 @@ impl Handshake {
 			peer_info.user_agent,
 			peer_info.capabilities,
 		);
-		// when more than one protocol version is supported, choosing should go here
 		Ok(peer_info)
 	}
 
@@ -206,7 +216,7 @@ impl Handshake {
 		// Set explicit timeouts on the tcp stream for hand/shake messages.
 		// Once the peer is up and running we will set new values for these.
 		// We accept an inbound connection, reading a Hand then writing a Shake reply.
-		let _ = conn.set_read_timeout(Some(HAND_READ_TIMEOUT));
+		let _ = conn.set_read_timeout(Some(self.read_timeout()));
 		let _ = conn.set_write_timeout(Some(SHAKE_WRITE_TIMEOUT));
 
 		let hand: Hand = read_message(conn, self.protocol_version, Type::Hand)?;
@@ -19,7 +19,8 @@ use crate::core::core::hash::Hash;
 use crate::core::core::BlockHeader;
 use crate::core::pow::Difficulty;
 use crate::core::ser::{
-	self, ProtocolVersion, Readable, Reader, StreamingReader, Writeable, Writer,
+	self, BinReader, LimitedReader, ProtocolVersion, Readable, Reader, SizeLimit, StreamingReader,
+	Writeable, Writer,
 };
 use crate::core::{consensus, global};
 use crate::types::{
@@ -108,6 +109,12 @@ fn max_msg_size(msg_type: Type) -> u64 {
 	}
 }
 
+// Max number of bytes we deserialize for a msg of the given type, same 4x margin
+// as the msg_len check when reading the msg header.
+fn size_limit(msg_type: Type) -> SizeLimit {
+	SizeLimit((max_msg_size(msg_type) * 4) as usize)
+}
+
 fn magic() -> [u8; 2] {
 	match global::get_chain_type() {
 		global::ChainTypes::Floonet => FLOONET_MAGIC,
@@ -162,12 +169,14 @@ pub fn read_header<R: Read>(
 /// Read a single item from the provided stream, always blocking until we
 /// have a result (or timeout).
 /// Returns the item and the total bytes read.
+/// The item is limited to the max size of the msg type it's part of.
 pub fn read_item<T: Readable, R: Read>(
+	msg_type: Type,
 	stream: &mut R,
 	version: ProtocolVersion,
 ) -> Result<(T, u64), Error> {
 	let mut reader = StreamingReader::new(stream, version);
-	let res = T::read(&mut reader)?;
+	let res = T::read(&mut LimitedReader::new(&mut reader, size_limit(msg_type)))?;
 	Ok((res, reader.total_bytes_read()))
 }
 
@@ -180,7 +189,9 @@ pub fn read_body<T: Readable, R: Read>(
 ) -> Result<T, Error> {
 	let mut body = vec![0u8; h.msg_len as usize];
 	stream.read_exact(&mut body)?;
-	ser::deserialize(&mut &body[..], version).map_err(From::from)
+	let mut source = &body[..];
+	let mut reader = BinReader::new(&mut source, version);
+	T::read(&mut LimitedReader::new(&mut reader, size_limit(h.msg_type))).map_err(From::from)
 }
 
 /// Read (an unknown) message from the provided stream and discard it.
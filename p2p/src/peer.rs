@@ -25,10 +25,10 @@ use lru_cache::LruCache;
 
 use crate::chain;
 use crate::conn;
+use crate::core::core;
 use crate::core::core::hash::{Hash, Hashed};
 use crate::core::pow::Difficulty;
 use crate::core::ser::Writeable;
-use crate::core::{core, global};
 use crate::handshake::Handshake;
 use crate::msg::{self, BanReason, GetPeerAddrs, Locator, Msg, Ping, TxHashSetRequest, Type};
 use crate::protocol::Protocol;
@@ -222,12 +222,12 @@ impl Peer {
 		State::Banned == *self.state.read()
 	}
 
-	/// Whether this peer is stuck on sync.
-	pub fn is_stuck(&self) -> (bool, Difficulty) {
+	/// Whether this peer is stuck on sync, i.e. its difficulty hasn't been
+	/// updated for more than `kick_time` milliseconds.
+	pub fn is_stuck(&self, kick_time: i64) -> (bool, Difficulty) {
 		let peer_live_info = self.info.live_info.read();
 		let now = Utc::now().timestamp_millis();
-		// if last updated difficulty is 2 hours ago, we're sure this peer is a stuck node.
-		if now > peer_live_info.stuck_detector.timestamp_millis() + global::STUCK_PEER_KICK_TIME {
+		if now > peer_live_info.stuck_detector.timestamp_millis() + kick_time {
 			(true, peer_live_info.total_difficulty)
 		} else {
 			(false, peer_live_info.total_difficulty)
@@ -277,6 +277,7 @@ impl Peer {
 			total_difficulty,
 			height,
 		};
+		self.info.ping_sent();
 		self.send(ping_msg, msg::Type::Ping)
 	}
 
@@ -498,6 +499,7 @@ impl ChainAdapter for TrackingAdapter {
 	fn transaction_received(
 		&self,
 		tx: core::Transaction,
+		peer_info: &PeerInfo,
 		stem: bool,
 	) -> Result<bool, chain::Error> {
 		// Do not track the tx hash for stem txs.
@@ -507,7 +509,7 @@ impl ChainAdapter for TrackingAdapter {
 			let kernel = &tx.kernels()[0];
 			self.push_recv(kernel.hash());
 		}
-		self.adapter.transaction_received(tx, stem)
+		self.adapter.transaction_received(tx, peer_info, stem)
 	}
 
 	fn block_received(
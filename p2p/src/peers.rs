@@ -16,7 +16,9 @@ use crate::util::RwLock;
 use std::collections::HashMap;
 use std::fs::File;
 use std::path::PathBuf;
+use std::sync::mpsc;
 use std::sync::Arc;
+use std::time::{self, Instant};
 
 use rand::seq::SliceRandom;
 use rand::thread_rng;
@@ -24,7 +26,6 @@ use rand::thread_rng;
 use crate::chain;
 use crate::core::core;
 use crate::core::core::hash::{Hash, Hashed};
-use crate::core::global;
 use crate::core::pow::Difficulty;
 use crate::peer::Peer;
 use crate::store::{PeerData, PeerStore, State};
@@ -178,6 +179,11 @@ impl Peers {
 		self.connected_peers().len() as u32
 	}
 
+	/// Size (in bytes) of the peer db on disk.
+	pub fn store_disk_size(&self) -> Result<u64, Error> {
+		Ok(self.store.disk_size()?)
+	}
+
 	/// Number of outbound peers currently connected to.
 	pub fn peer_outbound_count(&self) -> u32 {
 		self.outgoing_connected_peers().len() as u32
@@ -262,6 +268,15 @@ impl Peers {
 		self.most_work_peers().pop()
 	}
 
+	/// The single connected peer to sync from, the one with the highest total
+	/// difficulty. Unlike most_work_peer, ties go to the peer we've known the
+	/// longest so we keep syncing from the same peer. None if no peer is connected.
+	pub fn get_top_difficulty_peer(&self) -> Option<Arc<Peer>> {
+		top_difficulty(self.connected_peers(), |peer| {
+			(peer.info.total_difficulty(), peer.info.first_seen())
+		})
+	}
+
 	pub fn is_banned(&self, peer_addr: PeerAddr) -> bool {
 		if let Ok(peer) = self.store.get_peer(peer_addr) {
 			return peer.flags == State::Banned;
@@ -335,7 +350,7 @@ impl Peers {
 
 	/// Broadcast a compact block to all our connected peers.
 	/// This is only used when initially broadcasting a newly mined block.
-	pub fn broadcast_compact_block(&self, b: &core::CompactBlock) {
+	pub fn broadcast_compact_block(&self, b: &core::CompactBlock) -> u32 {
 		let count = self.broadcast("compact block", |p| p.send_compact_block(b));
 		debug!(
 			"broadcast_compact_block: {}, {} at {}, to {} peers, done.",
@@ -344,12 +359,13 @@ impl Peers {
 			b.header.height,
 			count,
 		);
+		count
 	}
 
 	/// Broadcast a block header to all our connected peers.
 	/// A peer implementation may drop the broadcast request
 	/// if it knows the remote peer already has the header.
-	pub fn broadcast_header(&self, bh: &core::BlockHeader) {
+	pub fn broadcast_header(&self, bh: &core::BlockHeader) -> u32 {
 		let count = self.broadcast("header", |p| p.send_header(bh));
 		debug!(
 			"broadcast_header: {}, {} at {}, to {} peers, done.",
@@ -358,6 +374,7 @@ impl Peers {
 			bh.height,
 			count,
 		);
+		count
 	}
 
 	/// Broadcasts the provided transaction to all our connected peers.
@@ -391,6 +408,46 @@ impl Peers {
 		}
 	}
 
+	/// Ping all our connected peers and wait (up to the provided timeout) for
+	/// their pongs. Returns the round trip time of every peer that answered.
+	pub fn ping_all_peers(
+		&self,
+		timeout: time::Duration,
+	) -> Result<HashMap<PeerAddr, time::Duration>, Error> {
+		let total_difficulty = self.total_difficulty()?;
+		let height = self.total_height()?;
+
+		let (pong_tx, pong_rx) = mpsc::channel();
+		let mut pending = vec![];
+		for p in self.connected_peers() {
+			p.info.listen_pong(pong_tx.clone());
+			match p.send_ping(total_difficulty, height) {
+				Ok(_) => pending.push(p),
+				Err(e) => debug!("Error pinging peer {:?}: {:?}", &p.info.addr, e),
+			}
+		}
+
+		let mut latencies = HashMap::new();
+		let deadline = Instant::now() + timeout;
+		loop {
+			pending.retain(|p| match p.info.ping_rtt() {
+				Some(rtt) => {
+					latencies.insert(p.info.addr.clone(), rtt);
+					false
+				}
+				None => true,
+			});
+			let now = Instant::now();
+			if pending.is_empty() || now >= deadline {
+				break;
+			}
+			// woken up by every pong, we still hold a sender so this never
+			// returns early on a disconnected channel
+			let _ = pong_rx.recv_timeout(deadline - now);
+		}
+		Ok(latencies)
+	}
+
 	/// All peer information we have in storage
 	pub fn all_peers(&self) -> Vec<PeerData> {
 		match self.store.all_peers() {
@@ -472,7 +529,7 @@ impl Peers {
 					let _ = self.update_state(peer.info.addr.clone(), State::Banned);
 					rm.push(peer.info.addr.clone());
 				} else {
-					let (stuck, diff) = peer.is_stuck();
+					let (stuck, diff) = peer.is_stuck(self.config.stuck_peer_kick_time());
 					match self.adapter.total_difficulty() {
 						Ok(total_difficulty) => {
 							if stuck && diff < total_difficulty {
@@ -549,13 +606,13 @@ impl Peers {
 	/// Removes those peers that seem to have expired
 	pub fn remove_expired(&self) {
 		let now = Utc::now();
+		let expiration = Duration::seconds(self.config.peer_expiration_remove_time());
 
 		// Delete defunct peers from storage
 		let _ = self.store.delete_peers(|peer| {
 			let diff = now - Utc.timestamp(peer.last_connected, 0);
 
-			let should_remove = peer.flags == State::Defunct
-				&& diff > Duration::seconds(global::PEER_EXPIRATION_REMOVE_TIME);
+			let should_remove = peer.flags == State::Defunct && diff > expiration;
 
 			if should_remove {
 				debug!(
@@ -596,9 +653,10 @@ impl ChainAdapter for Peers {
 	fn transaction_received(
 		&self,
 		tx: core::Transaction,
+		peer_info: &PeerInfo,
 		stem: bool,
 	) -> Result<bool, chain::Error> {
-		self.adapter.transaction_received(tx, stem)
+		self.adapter.transaction_received(tx, peer_info, stem)
 	}
 
 	fn block_received(
@@ -796,3 +854,45 @@ impl NetAdapter for Peers {
 		}
 	}
 }
+
+/// The item with the highest total difficulty, ties going to the one first seen
+/// the earliest. None if there are no items.
+fn top_difficulty<T, F>(items: Vec<T>, key: F) -> Option<T>
+where
+	F: Fn(&T) -> (Difficulty, DateTime<Utc>),
+{
+	items.into_iter().max_by(|a, b| {
+		let (a_diff, a_first_seen) = key(a);
+		let (b_diff, b_first_seen) = key(b);
+		a_diff
+			.cmp(&b_diff)
+			.then_with(|| b_first_seen.cmp(&a_first_seen))
+	})
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn top_difficulty_peer() {
+		let now = Utc::now();
+		let key = |p: &(u64, i64, &str)| (Difficulty::from_num(p.0), now + Duration::seconds(p.1));
+
+		// highest difficulty first
+		let peers = vec![(10, 0, "a"), (30, 20, "b"), (20, 10, "c")];
+		assert_eq!(top_difficulty(peers, key).unwrap().2, "b");
+
+		// then the oldest, whatever the order
+		let peers = vec![(30, 20, "a"), (30, 0, "b"), (10, -10, "c"), (30, 10, "d")];
+		assert_eq!(top_difficulty(peers, key).unwrap().2, "b");
+		let peers = vec![(30, 0, "b"), (30, 20, "a")];
+		assert_eq!(top_difficulty(peers, key).unwrap().2, "b");
+	}
+
+	#[test]
+	fn top_difficulty_peer_empty() {
+		let peers: Vec<(Difficulty, DateTime<Utc>)> = vec![];
+		assert!(top_difficulty(peers, |p| *p).is_none());
+	}
+}
@@ -103,6 +103,7 @@ impl MessageHandler for Protocol {
 
 			Type::Pong => {
 				let pong: Pong = msg.body()?;
+				self.peer_info.pong_received();
 				adapter.peer_difficulty(
 					self.peer_info.addr.clone(),
 					pong.total_difficulty,
@@ -151,7 +152,7 @@ impl MessageHandler for Protocol {
 					msg.header.msg_len
 				);
 				let tx: core::Transaction = msg.body()?;
-				adapter.transaction_received(tx, false)?;
+				adapter.transaction_received(tx, &self.peer_info, false)?;
 				Ok(None)
 			}
 
@@ -161,7 +162,7 @@ impl MessageHandler for Protocol {
 					msg.header.msg_len
 				);
 				let tx: core::Transaction = msg.body()?;
-				adapter.transaction_received(tx, true)?;
+				adapter.transaction_received(tx, &self.peer_info, true)?;
 				Ok(None)
 			}
 
@@ -229,7 +229,8 @@ impl Server {
 					}
 				} else {
 					peer_addr = Some(PeerAddr::Ip(address));
-					TcpStream::connect_timeout(&address, Duration::from_secs(10))?
+					let timeout = Duration::from_secs(self.config.peer_connect_timeout_secs());
+					TcpStream::connect_timeout(&address, timeout)?
 				}
 			}
 			PeerAddr::Onion(onion_address) => {
@@ -392,6 +393,7 @@ impl ChainAdapter for DummyAdapter {
 	fn transaction_received(
 		&self,
 		_: core::Transaction,
+		_peer_info: &PeerInfo,
 		_stem: bool,
 	) -> Result<bool, chain::Error> {
 		Ok(true)
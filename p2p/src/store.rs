@@ -131,6 +131,11 @@ impl PeerStore {
 		Ok(PeerStore { db: db })
 	}
 
+	/// Size (in bytes) of the peer db on disk.
+	pub fn disk_size(&self) -> Result<u64, Error> {
+		self.db.disk_size()
+	}
+
 	pub fn save_peer(&self, p: &PeerData) -> Result<(), Error> {
 		debug!("save_peer: {:?} marked {:?}", p.addr.clone(), p.flags);
 
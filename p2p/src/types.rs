@@ -22,6 +22,7 @@ use std::io;
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6, ToSocketAddrs};
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::mpsc;
 use std::sync::Arc;
 use std::sync::Mutex;
 
@@ -40,7 +41,7 @@ use crate::core::pow::Difficulty;
 use crate::core::ser::{self, ProtocolVersion, Readable, Reader, Writeable, Writer};
 use crate::msg::PeerAddrs;
 use crate::util::RwLock;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 /// Maximum number of block headers a peer should ever send
 pub const MAX_BLOCK_HEADERS: u32 = 512;
@@ -71,6 +72,24 @@ const PEER_MIN_PREFERRED_OUTBOUND_COUNT: u32 = 8;
 /// than allowed by PEER_MAX_INBOUND_COUNT to encourage network bootstrapping.
 const PEER_LISTENER_BUFFER_COUNT: u32 = 8;
 
+/// Seconds to connect to a peer and complete the handshake with it
+const PEER_CONNECT_TIMEOUT_SECS: u64 = 10;
+
+/// Seconds to complete the handshake with a peer over Tor, where round trips
+/// through the circuit make handshakes of 30 seconds and more common
+const TOR_PEER_CONNECT_TIMEOUT_SECS: u64 = 60;
+
+/// If a peer's last updated difficulty is 2 hours ago and its difficulty's lower than ours,
+/// we're sure this peer is a stuck node, and we will kick out such kind of stuck peers.
+/// In milliseconds.
+const STUCK_PEER_KICK_TIME: i64 = 2 * 3600 * 1000;
+
+/// If a peer's last seen time is 2 weeks ago we will forget such kind of defunct peers.
+const PEER_EXPIRATION_DAYS: i64 = 7 * 2;
+
+/// Defunct peer timeout in seconds to be used in checks.
+const PEER_EXPIRATION_REMOVE_TIME: i64 = PEER_EXPIRATION_DAYS * 24 * 3600;
+
 #[derive(Debug, Fail)]
 pub enum Error {
 	#[fail(display = "p2p Serialization error, {}", _0)]
@@ -381,6 +400,14 @@ pub struct P2PConfig {
 
 	pub peer_listener_buffer_count: Option<u32>,
 
+	pub peer_connect_timeout_secs: Option<u64>,
+
+	pub tor_peer_connect_timeout_secs: Option<u64>,
+
+	pub stuck_peer_kick_time: Option<i64>,
+
+	pub peer_expiration_remove_time: Option<i64>,
+
 	pub dandelion_peer: Option<PeerAddr>,
 }
 
@@ -402,6 +429,10 @@ impl Default for P2PConfig {
 			peer_max_outbound_count: None,
 			peer_min_preferred_outbound_count: None,
 			peer_listener_buffer_count: None,
+			peer_connect_timeout_secs: None,
+			tor_peer_connect_timeout_secs: None,
+			stuck_peer_kick_time: None,
+			peer_expiration_remove_time: None,
 			dandelion_peer: None,
 		}
 	}
@@ -449,6 +480,40 @@ impl P2PConfig {
 			None => PEER_LISTENER_BUFFER_COUNT,
 		}
 	}
+
+	/// return timeout (seconds) to connect and handshake with a peer
+	pub fn peer_connect_timeout_secs(&self) -> u64 {
+		match self.peer_connect_timeout_secs {
+			Some(n) => n,
+			None => PEER_CONNECT_TIMEOUT_SECS,
+		}
+	}
+
+	/// return timeout (seconds) to handshake with a peer over Tor
+	pub fn tor_peer_connect_timeout_secs(&self) -> u64 {
+		match self.tor_peer_connect_timeout_secs {
+			Some(n) => n,
+			None => TOR_PEER_CONNECT_TIMEOUT_SECS,
+		}
+	}
+
+	/// return time (milliseconds) without a difficulty update after which a
+	/// peer behind us is considered stuck
+	pub fn stuck_peer_kick_time(&self) -> i64 {
+		match self.stuck_peer_kick_time {
+			Some(n) => n,
+			None => STUCK_PEER_KICK_TIME,
+		}
+	}
+
+	/// return time (seconds) after which a defunct peer is removed from the
+	/// peer store
+	pub fn peer_expiration_remove_time(&self) -> i64 {
+		match self.peer_expiration_remove_time {
+			Some(n) => n,
+			None => PEER_EXPIRATION_REMOVE_TIME,
+		}
+	}
 }
 
 /// Type of seeding the server will use to find other peers on the network.
@@ -534,6 +599,12 @@ pub struct PeerLiveInfo {
 	pub last_seen: DateTime<Utc>,
 	pub stuck_detector: DateTime<Utc>,
 	pub first_seen: DateTime<Utc>,
+	/// When our last ping to this peer was sent (if no pong came back yet).
+	pub ping_sent: Option<Instant>,
+	/// Round trip time of our last answered ping.
+	pub ping_rtt: Option<Duration>,
+	/// Notified when the pong to our pending ping comes back.
+	pub pong_listener: Option<mpsc::Sender<()>>,
 }
 
 /// General information about a connected peer that's useful to other modules.
@@ -558,6 +629,9 @@ impl PeerLiveInfo {
 			first_seen: Utc::now(),
 			last_seen: Utc::now(),
 			stuck_detector: Utc::now(),
+			ping_sent: None,
+			ping_rtt: None,
+			pong_listener: None,
 		}
 	}
 }
@@ -602,6 +676,36 @@ impl PeerInfo {
 		live_info.total_difficulty = total_difficulty;
 		live_info.last_seen = Utc::now()
 	}
+
+	/// Round trip time of our last answered ping, if any.
+	pub fn ping_rtt(&self) -> Option<Duration> {
+		self.live_info.read().ping_rtt
+	}
+
+	/// Record a ping being sent to the peer, resetting the last round trip time.
+	pub fn ping_sent(&self) {
+		let mut live_info = self.live_info.write();
+		live_info.ping_sent = Some(Instant::now());
+		live_info.ping_rtt = None;
+	}
+
+	/// Notify the provided listener when the pong to our next ping comes back.
+	pub fn listen_pong(&self, listener: mpsc::Sender<()>) {
+		self.live_info.write().pong_listener = Some(listener);
+	}
+
+	/// Record a pong received from the peer, completing the round trip of the
+	/// pending ping (if any).
+	pub fn pong_received(&self) {
+		let mut live_info = self.live_info.write();
+		if let Some(sent) = live_info.ping_sent.take() {
+			live_info.ping_rtt = Some(sent.elapsed());
+		}
+		if let Some(listener) = live_info.pong_listener.take() {
+			// the listener may have stopped waiting already
+			let _ = listener.send(());
+		}
+	}
 }
 
 /// This is needed for legacy purposes
@@ -665,8 +769,12 @@ pub trait ChainAdapter: Sync + Send {
 	fn total_height(&self) -> Result<u64, chain::Error>;
 
 	/// A valid transaction has been received from one of our peers
-	fn transaction_received(&self, tx: core::Transaction, stem: bool)
-		-> Result<bool, chain::Error>;
+	fn transaction_received(
+		&self,
+		tx: core::Transaction,
+		peer_info: &PeerInfo,
+		stem: bool,
+	) -> Result<bool, chain::Error>;
 
 	fn get_transaction(&self, kernel_hash: Hash) -> Option<core::Transaction>;
 
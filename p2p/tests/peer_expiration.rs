@@ -0,0 +1,81 @@
+// Copyright 2020 The Grin Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grin_core as core;
+use grin_p2p as p2p;
+
+use grin_util as util;
+use grin_util::StopState;
+
+use chrono::Utc;
+use std::fs;
+use std::sync::Arc;
+
+use crate::core::core::hash::Hash;
+use crate::core::global;
+use crate::p2p::types::PeerAddr;
+use crate::p2p::{Capabilities, PeerData, ReasonForBan, State};
+
+fn peer_data(addr: &str, flags: State, last_connected: i64) -> PeerData {
+	PeerData {
+		addr: PeerAddr::Ip(addr.parse().unwrap()),
+		capabilities: Capabilities::FULL_NODE,
+		user_agent: "test".to_owned(),
+		flags,
+		last_banned: 0,
+		ban_reason: ReasonForBan::None,
+		last_connected,
+	}
+}
+
+// With a very short expiration, defunct peers we haven't connected to for a
+// minute are forgotten right away, healthy ones are kept.
+#[test]
+fn remove_expired_peers() {
+	global::init_global_chain_type_once(global::ChainTypes::AutomatedTesting);
+	util::init_test_logger();
+
+	let db_root = ".grin_peer_expiration";
+	let _ = fs::remove_dir_all(db_root);
+
+	let p2p_config = p2p::P2PConfig {
+		host: "127.0.0.1".parse().unwrap(),
+		peer_expiration_remove_time: Some(1),
+		..p2p::P2PConfig::default()
+	};
+	let server = p2p::Server::new(
+		db_root,
+		Capabilities::UNKNOWN,
+		p2p_config,
+		Arc::new(p2p::DummyAdapter {}),
+		Hash::from_vec(&vec![]),
+		Arc::new(StopState::new()),
+		0,
+		None,
+	)
+	.unwrap();
+
+	let last_connected = Utc::now().timestamp() - 60;
+	let defunct = peer_data("10.0.0.1:3414", State::Defunct, last_connected);
+	let healthy = peer_data("10.0.0.2:3414", State::Healthy, last_connected);
+	server.peers.save_peer(&defunct).unwrap();
+	server.peers.save_peer(&healthy).unwrap();
+
+	server.peers.remove_expired();
+
+	assert!(!server.peers.exists_peer(defunct.addr.clone()).unwrap());
+	assert!(server.peers.exists_peer(healthy.addr.clone()).unwrap());
+
+	let _ = fs::remove_dir_all(db_root);
+}
@@ -18,6 +18,7 @@ use grin_p2p as p2p;
 use grin_util as util;
 use grin_util::StopState;
 
+use std::io::Write;
 use std::net::{SocketAddr, TcpListener, TcpStream};
 use std::sync::Arc;
 use std::{thread, time};
@@ -25,6 +26,9 @@ use std::{thread, time};
 use crate::core::core::hash::Hash;
 use crate::core::global;
 use crate::core::pow::Difficulty;
+use crate::core::ser::{self, ProtocolVersion, Writeable};
+use crate::p2p::handshake::Handshake;
+use crate::p2p::msg::{read_message, Hand, MsgHeader, Shake, Type};
 use crate::p2p::types::PeerAddr;
 use crate::p2p::Peer;
 
@@ -39,10 +43,19 @@ fn open_port() -> u16 {
 // Setup test with AutomatedTesting chain_type;
 fn test_setup() {
 	// Set "global" chain type here as we spawn peer threads for read/write.
-	global::init_global_chain_type(global::ChainTypes::AutomatedTesting);
+	global::init_global_chain_type_once(global::ChainTypes::AutomatedTesting);
 	util::init_test_logger();
 }
 
+// Writes a message the way a peer only speaking protocol version 1 does.
+fn write_v1_message<T: Writeable>(stream: &mut TcpStream, msg_type: Type, msg: T) {
+	let version = ProtocolVersion(1);
+	let body = ser::ser_vec(&msg, version).unwrap();
+	let header = ser::ser_vec(&MsgHeader::new(msg_type, body.len() as u64), version).unwrap();
+	stream.write_all(&header).unwrap();
+	stream.write_all(&body).unwrap();
+}
+
 // Starts a server and connects a client peer to it to check handshake,
 // followed by a ping/pong exchange to make sure the connection is live.
 #[test]
@@ -103,3 +116,126 @@ fn peer_handshake() {
 	assert_eq!(server_peer.info.total_difficulty(), Difficulty::min());
 	assert!(server.peers.peer_count() > 0);
 }
+
+// Connects to a "peer" that accepts the connection but never replies with a Shake,
+// the handshake should give up after the configured timeout rather than hang.
+#[test]
+fn peer_handshake_timeout() {
+	test_setup();
+
+	let p2p_config = p2p::P2PConfig {
+		host: "127.0.0.1".parse().unwrap(),
+		port: open_port(),
+		peer_connect_timeout_secs: Some(1),
+		..p2p::P2PConfig::default()
+	};
+	let net_adapter = Arc::new(p2p::DummyAdapter {});
+	let server = p2p::Server::new(
+		".grin_timeout",
+		p2p::Capabilities::UNKNOWN,
+		p2p_config.clone(),
+		net_adapter.clone(),
+		Hash::from_vec(&vec![]),
+		Arc::new(StopState::new()),
+		0,
+		None,
+	)
+	.unwrap();
+
+	// accepts and keeps the connection open, never sending anything back
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let addr = listener.local_addr().unwrap();
+	let _ = thread::spawn(move || {
+		let (_stream, _) = listener.accept().unwrap();
+		thread::sleep(time::Duration::from_secs(30));
+	});
+
+	let socket = TcpStream::connect_timeout(&addr, time::Duration::from_secs(10)).unwrap();
+	let start = time::Instant::now();
+	let res = Peer::connect(
+		socket,
+		p2p::Capabilities::UNKNOWN,
+		Difficulty::min(),
+		PeerAddr::Ip("127.0.0.1:5000".parse().unwrap()),
+		&p2p::handshake::Handshake::new(Hash::from_vec(&vec![]), p2p_config, None),
+		net_adapter,
+		100_000,
+		None,
+		server,
+	);
+	let elapsed = start.elapsed();
+	assert!(res.is_err());
+	assert!(elapsed >= time::Duration::from_secs(1), "{:?}", elapsed);
+	assert!(elapsed < time::Duration::from_secs(5), "{:?}", elapsed);
+}
+
+// A peer still on protocol version 1 connects to us, both sides fall back to version 1.
+#[test]
+fn peer_handshake_older_version_inbound() {
+	test_setup();
+	assert!(ProtocolVersion::local() > ProtocolVersion(1));
+
+	let genesis = Hash::from_vec(&vec![]);
+	let hs = Handshake::new(genesis, p2p::P2PConfig::default(), None);
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let addr = listener.local_addr().unwrap();
+	let node = thread::spawn(move || {
+		let (mut conn, _) = listener.accept().unwrap();
+		hs.accept(p2p::Capabilities::UNKNOWN, Difficulty::min(), &mut conn)
+	});
+
+	let mut socket = TcpStream::connect_timeout(&addr, time::Duration::from_secs(10)).unwrap();
+	let hand = Hand {
+		version: ProtocolVersion(1),
+		capabilities: p2p::Capabilities::UNKNOWN,
+		nonce: 1,
+		genesis,
+		total_difficulty: Difficulty::min(),
+		sender_addr: PeerAddr::Ip("127.0.0.1:5000".parse().unwrap()),
+		receiver_addr: PeerAddr::Ip(addr),
+		user_agent: "MW/MWC 1.0.0".to_owned(),
+	};
+	write_v1_message(&mut socket, Type::Hand, hand);
+	let shake: Shake = read_message(&mut socket, ProtocolVersion(1), Type::Shake).unwrap();
+	assert_eq!(shake.version, ProtocolVersion::local());
+
+	let info = node.join().unwrap().unwrap();
+	assert_eq!(info.version, ProtocolVersion(1));
+}
+
+// We connect to a peer still on protocol version 1 and fall back to its version.
+#[test]
+fn peer_handshake_older_version_outbound() {
+	test_setup();
+
+	let genesis = Hash::from_vec(&vec![]);
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let addr = listener.local_addr().unwrap();
+	let old_peer = thread::spawn(move || {
+		let (mut conn, _) = listener.accept().unwrap();
+		let hand: Hand = read_message(&mut conn, ProtocolVersion(1), Type::Hand).unwrap();
+		let shake = Shake {
+			version: ProtocolVersion(1),
+			capabilities: p2p::Capabilities::UNKNOWN,
+			genesis,
+			total_difficulty: Difficulty::min(),
+			user_agent: "MW/MWC 1.0.0".to_owned(),
+		};
+		write_v1_message(&mut conn, Type::Shake, shake);
+		hand.version
+	});
+
+	let mut socket = TcpStream::connect_timeout(&addr, time::Duration::from_secs(10)).unwrap();
+	let hs = Handshake::new(genesis, p2p::P2PConfig::default(), None);
+	let info = hs
+		.initiate(
+			p2p::Capabilities::UNKNOWN,
+			Difficulty::min(),
+			PeerAddr::Ip("127.0.0.1:5000".parse().unwrap()),
+			&mut socket,
+			None,
+		)
+		.unwrap();
+	assert_eq!(old_peer.join().unwrap(), ProtocolVersion::local());
+	assert_eq!(info.version, ProtocolVersion(1));
+}
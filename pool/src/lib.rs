@@ -31,11 +31,14 @@ extern crate serde_derive;
 extern crate log;
 
 mod pool;
+mod rate_limiter;
 pub mod transaction_pool;
 pub mod types;
 
 pub use crate::pool::Pool;
+pub use crate::rate_limiter::TxRateLimiter;
 pub use crate::transaction_pool::TransactionPool;
 pub use crate::types::{
-	BlockChain, DandelionConfig, PoolAdapter, PoolConfig, PoolEntry, PoolError, TxSource,
+	BlockChain, DandelionConfig, FeeHistogram, PoolAdapter, PoolConfig, PoolEntry, PoolError,
+	TxSource,
 };
@@ -22,14 +22,22 @@ use self::core::core::verifier_cache::VerifierCache;
 use self::core::core::{
 	Block, BlockHeader, BlockSums, Committed, OutputIdentifier, Transaction, TxKernel, Weighting,
 };
+use self::util::secp::pedersen::Commitment;
 use self::util::RwLock;
-use crate::types::{BlockChain, PoolEntry, PoolError};
+use crate::types::{BlockChain, FeeHistogram, PoolEntry, PoolError};
+use chrono::prelude::{DateTime, Utc};
 use grin_core as core;
 use grin_util as util;
-use std::cmp::Reverse;
+use std::cmp::{max, min, Reverse};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
+/// Number of buckets in the fee histogram.
+const FEE_HISTOGRAM_BUCKETS: usize = 10;
+
+/// Percentile of pending txs (by fee rate) used for the recommended fee rate.
+const RECOMMENDED_FEE_RATE_PERCENTILE: usize = 80;
+
 pub struct Pool<B, V>
 where
 	B: BlockChain,
@@ -75,6 +83,15 @@ where
 		None
 	}
 
+	/// Query the pool for the kernel with the given excess.
+	pub fn retrieve_kernel(&self, excess: &Commitment) -> Option<TxKernel> {
+		self.entries
+			.iter()
+			.flat_map(|x| x.tx.kernels())
+			.find(|k| k.excess() == *excess)
+			.cloned()
+	}
+
 	/// Query the tx pool for all known txs based on kernel short_ids
 	/// from the provided compact_block.
 	/// Note: does not validate that we return the full set of required txs.
@@ -480,6 +497,16 @@ where
 		});
 	}
 
+	/// Remove all entries added to the pool before the provided cutoff.
+	/// Returns the number of entries removed.
+	/// Note: txs depending on a removed entry are left in place, the caller is
+	/// expected to reconcile the pool afterwards.
+	pub fn remove_older_than(&mut self, cutoff: DateTime<Utc>) -> usize {
+		let size = self.entries.len();
+		self.entries.retain(|x| x.tx_at >= cutoff);
+		size - self.entries.len()
+	}
+
 	/// Size of the pool.
 	pub fn size(&self) -> usize {
 		self.entries.len()
@@ -495,6 +522,46 @@ where
 	pub fn is_empty(&self) -> bool {
 		self.entries.is_empty()
 	}
+
+	/// Histogram of the fee rates (fee_to_weight) of the txs in the pool.
+	/// Buckets are logarithmically spaced between the lowest and highest observed
+	/// fee rate. Empty if there are no txs in the pool.
+	pub fn fee_histogram(&self) -> FeeHistogram {
+		let mut rates: Vec<u64> = self.entries.iter().map(|x| x.tx.fee_to_weight()).collect();
+		if rates.is_empty() {
+			return FeeHistogram {
+				buckets: vec![],
+				recommended_fee_rate: 0,
+			};
+		}
+		rates.sort_unstable();
+
+		let min_rate = max(1, rates[0]) as f64;
+		let max_rate = max(1, rates[rates.len() - 1]) as f64;
+		let log_range = (max_rate / min_rate).ln();
+
+		let mut buckets: Vec<(u64, u64)> = (0..FEE_HISTOGRAM_BUCKETS)
+			.map(|i| {
+				let exp = log_range * i as f64 / FEE_HISTOGRAM_BUCKETS as f64;
+				((min_rate * exp.exp()).round() as u64, 0)
+			})
+			.collect();
+		for rate in &rates {
+			let idx = if log_range > 0.0 {
+				let pos = (max(1, *rate) as f64 / min_rate).ln() / log_range;
+				(pos * FEE_HISTOGRAM_BUCKETS as f64) as usize
+			} else {
+				0
+			};
+			buckets[min(idx, FEE_HISTOGRAM_BUCKETS - 1)].1 += 1;
+		}
+
+		let rank = (rates.len() * RECOMMENDED_FEE_RATE_PERCENTILE + 99) / 100;
+		FeeHistogram {
+			buckets,
+			recommended_fee_rate: rates[max(1, rank) - 1],
+		}
+	}
 }
 
 struct Bucket {
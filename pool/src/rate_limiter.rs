@@ -0,0 +1,79 @@
+// Copyright 2020 The Grin Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rate limiting of the transactions submitted to the pool, per source IP.
+
+use crate::types::PoolError;
+use chrono::prelude::*;
+use chrono::Duration;
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+
+/// Length of the sliding window the submissions are counted over, in seconds
+const RATE_LIMIT_WINDOW_SECS: i64 = 60;
+
+/// Tracks the transactions submitted by every source IP over the last minute
+/// and rejects the ones going over the configured limit.
+pub struct TxRateLimiter {
+	/// Max number of txs per minute and per IP, 0 disables the limit
+	max_tx_per_minute: usize,
+	/// Submission times within the window, oldest first
+	submitted: HashMap<IpAddr, VecDeque<DateTime<Utc>>>,
+	/// Last time the IPs that went quiet were forgotten, at most once per window
+	last_pruned: DateTime<Utc>,
+}
+
+impl TxRateLimiter {
+	/// New rate limiter allowing `max_tx_per_minute` txs per source IP
+	pub fn new(max_tx_per_minute: usize) -> TxRateLimiter {
+		TxRateLimiter {
+			max_tx_per_minute,
+			submitted: HashMap::new(),
+			last_pruned: Utc::now(),
+		}
+	}
+
+	/// Record a tx submitted by `addr` now, or reject it with
+	/// `PoolError::RateLimited` if that IP is over the limit.
+	pub fn check(&mut self, addr: IpAddr) -> Result<(), PoolError> {
+		self.check_at(addr, Utc::now())
+	}
+
+	/// Same as `check`, for a tx submitted at `now`. Loopback addresses (our
+	/// own API) are never limited.
+	pub fn check_at(&mut self, addr: IpAddr, now: DateTime<Utc>) -> Result<(), PoolError> {
+		if self.max_tx_per_minute == 0 || addr.is_loopback() {
+			return Ok(());
+		}
+
+		let cutoff = now - Duration::seconds(RATE_LIMIT_WINDOW_SECS);
+		// forget the IPs that went quiet for the whole window, only once per
+		// window as it goes through every IP (under the pool lock)
+		if self.last_pruned <= cutoff {
+			self.submitted
+				.retain(|_, times| times.back().map_or(false, |t| *t > cutoff));
+			self.last_pruned = now;
+		}
+
+		let times = self.submitted.entry(addr).or_insert_with(VecDeque::new);
+		while times.front().map_or(false, |t| *t <= cutoff) {
+			times.pop_front();
+		}
+		if times.len() >= self.max_tx_per_minute {
+			return Err(PoolError::RateLimited);
+		}
+		times.push_back(now);
+		Ok(())
+	}
+}
@@ -21,13 +21,18 @@ use self::core::core::hash::{Hash, Hashed};
 use self::core::core::id::ShortId;
 use self::core::core::verifier_cache::VerifierCache;
 use self::core::core::{
-	transaction, Block, BlockHeader, HeaderVersion, OutputIdentifier, Transaction, Weighting,
+	transaction, Block, BlockHeader, OutputIdentifier, Transaction, TxKernel, Weighting,
 };
 use self::core::global;
+use self::util::secp::pedersen::Commitment;
 use self::util::RwLock;
 use crate::pool::Pool;
-use crate::types::{BlockChain, PoolAdapter, PoolConfig, PoolEntry, PoolError, TxSource};
+use crate::rate_limiter::TxRateLimiter;
+use crate::types::{
+	BlockChain, FeeHistogram, PoolAdapter, PoolConfig, PoolEntry, PoolError, TxSource,
+};
 use chrono::prelude::*;
+use chrono::Duration;
 use grin_core as core;
 use grin_core::ser;
 use grin_keychain::base58;
@@ -58,6 +63,10 @@ where
 	pub adapter: Arc<P>,
 	///the replay attack cache
 	pub replay_verifier_cache: Arc<RwLock<LruCache<[u8; 32], ()>>>,
+	/// Per peer IP limit of the txs relayed to us
+	pub rate_limiter: TxRateLimiter,
+	/// Bumped every time the txpool content changes
+	txpool_version: u64,
 }
 
 impl<B, P, V> TransactionPool<B, P, V>
@@ -73,6 +82,7 @@ where
 		verifier_cache: Arc<RwLock<V>>,
 		adapter: Arc<P>,
 	) -> Self {
+		let rate_limiter = TxRateLimiter::new(config.max_tx_per_minute);
 		TransactionPool {
 			config,
 			txpool: Pool::new(chain.clone(), verifier_cache.clone(), "txpool".to_string()),
@@ -86,9 +96,21 @@ where
 			verifier_cache,
 			adapter,
 			replay_verifier_cache: Arc::new(RwLock::new(LruCache::new(100))),
+			rate_limiter,
+			txpool_version: 0,
 		}
 	}
 
+	/// Version of the txpool content, changes every time txs are added to or
+	/// removed from the txpool. Lets callers know when mineable txs may differ.
+	pub fn txpool_version(&self) -> u64 {
+		self.txpool_version
+	}
+
+	fn bump_txpool_version(&mut self) {
+		self.txpool_version = self.txpool_version.wrapping_add(1);
+	}
+
 	pub fn chain_head(&self) -> Result<BlockHeader, PoolError> {
 		self.blockchain.chain_head()
 	}
@@ -129,6 +151,7 @@ where
 
 	fn add_to_txpool(&mut self, entry: &PoolEntry, header: &BlockHeader) -> Result<(), PoolError> {
 		self.txpool.add_to_pool(entry.clone(), None, header)?;
+		self.bump_txpool_version();
 
 		// We now need to reconcile the stempool based on the new state of the txpool.
 		// Some stempool txs may no longer be valid and we need to evict them.
@@ -149,7 +172,7 @@ where
 			if !global::is_nrd_enabled() {
 				return Err(PoolError::NRDKernelNotEnabled);
 			}
-			if header.version < HeaderVersion(4) {
+			if header.is_before_hf(3) {
 				return Err(PoolError::NRDKernelPreHF3);
 			}
 		}
@@ -307,7 +330,51 @@ where
 	// Uses bucket logic to identify the "last" transaction.
 	// No other tx depends on it and it has low fee_to_weight.
 	pub fn evict_from_txpool(&mut self) {
-		self.txpool.evict_transaction()
+		self.txpool.evict_transaction();
+		self.bump_txpool_version();
+	}
+
+	/// Remove all txs that have been in the pool (txpool and stempool) longer than
+	/// max_age. Txs depending on a pruned tx are removed as well when the pools are
+	/// reconciled. Returns the total number of txs removed.
+	pub fn prune_by_age(&mut self, max_age: Duration) -> usize {
+		let cutoff = match Utc::now().checked_sub_signed(max_age) {
+			Some(cutoff) => cutoff,
+			// nothing in the pool can be that old
+			None => return 0,
+		};
+		let size_before = self.txpool.size() + self.stempool.size();
+
+		let pruned =
+			self.txpool.remove_older_than(cutoff) + self.stempool.remove_older_than(cutoff);
+		if pruned == 0 {
+			return 0;
+		}
+		self.bump_txpool_version();
+
+		match self.chain_head() {
+			Ok(header) => {
+				if let Err(e) = self.txpool.reconcile(None, &header) {
+					warn!("prune_by_age: failed to reconcile txpool, {}", e);
+				}
+				match self.txpool.all_transactions_aggregate(None) {
+					Ok(txpool_tx) => {
+						if let Err(e) = self.stempool.reconcile(txpool_tx, &header) {
+							warn!("prune_by_age: failed to reconcile stempool, {}", e);
+						}
+					}
+					Err(e) => warn!("prune_by_age: failed to aggregate txpool, {}", e),
+				}
+			}
+			Err(e) => warn!("prune_by_age: failed to get chain head, {}", e),
+		}
+
+		let removed = size_before - (self.txpool.size() + self.stempool.size());
+		debug!(
+			"prune_by_age: removed {} txs older than {}",
+			removed, cutoff
+		);
+		removed
 	}
 
 	// Old txs will "age out" after 30 mins.
@@ -364,6 +431,7 @@ where
 
 		// First reconcile the txpool.
 		self.txpool.reconcile_block(block);
+		self.bump_txpool_version();
 		self.txpool.reconcile(None, &block.header)?;
 
 		// Now reconcile our stempool, accounting for the updated txpool txs.
@@ -396,6 +464,14 @@ where
 		self.txpool.retrieve_tx_by_kernel_hash(hash)
 	}
 
+	/// Retrieve the kernel with the given excess from the txpool or the stempool,
+	/// a wallet can check the lock height of its pending transactions with it.
+	pub fn retrieve_kernel(&self, excess: &Commitment) -> Option<TxKernel> {
+		self.txpool
+			.retrieve_kernel(excess)
+			.or_else(|| self.stempool.retrieve_kernel(excess))
+	}
+
 	/// Retrieve all transactions matching the provided "compact block"
 	/// based on the kernel set.
 	/// Note: we only look in the txpool for this (stempool is under embargo).
@@ -431,6 +507,12 @@ where
 				return Err(PoolError::LowFeeTransaction(threshold));
 			}
 		}
+
+		if !self.config.accept_non_standard
+			&& !tx.is_standard_with_fee_base(self.config.accept_fee_base)
+		{
+			return Err(PoolError::NonStandardTransaction);
+		}
 		Ok(())
 	}
 
@@ -440,6 +522,12 @@ where
 		self.txpool.size()
 	}
 
+	/// Fee rate histogram of the txpool, used for fee estimation.
+	/// Note: we only consider the txpool here as stempool is under embargo.
+	pub fn fee_histogram(&self) -> FeeHistogram {
+		self.txpool.fee_histogram()
+	}
+
 	/// Returns a vector of transactions from the txpool so we can build a
 	/// block from them.
 	pub fn prepare_mineable_transactions(&self) -> Result<Vec<Transaction>, PoolError> {
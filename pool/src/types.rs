@@ -22,9 +22,11 @@ use self::core::core::hash::Hash;
 use self::core::core::transaction::{self, Transaction};
 use self::core::core::{BlockHeader, BlockSums, Inputs, OutputIdentifier};
 use chrono::prelude::*;
+use chrono::Duration;
 use failure::Fail;
 use grin_core as core;
 use grin_keychain as keychain;
+use std::cmp;
 
 /// Dandelion "epoch" length.
 const DANDELION_EPOCH_SECS: u16 = 600;
@@ -124,6 +126,30 @@ pub struct PoolConfig {
 	/// blocks.
 	#[serde(default = "default_mineable_max_weight")]
 	pub mineable_max_weight: u64,
+
+	/// Maximum age in hours of a transaction in the pool, older transactions
+	/// are pruned during the periodic compaction check
+	#[serde(default = "default_max_tx_age_hours")]
+	pub max_tx_age_hours: u64,
+
+	/// Accept transactions that don't follow the "standard" policy (see
+	/// Transaction::is_standard), rejected by default
+	#[serde(default = "default_accept_non_standard")]
+	pub accept_non_standard: bool,
+
+	/// Maximum number of transactions a single peer IP can relay to the pool
+	/// per minute, 0 disables the limit
+	#[serde(default = "default_max_tx_per_minute")]
+	pub max_tx_per_minute: usize,
+}
+
+impl PoolConfig {
+	/// max_tx_age_hours as a Duration, capped to the largest Duration chrono
+	/// can represent so a huge configured value can't overflow.
+	pub fn max_tx_age(&self) -> Duration {
+		let max_hours = Duration::max_value().num_hours() as u64;
+		Duration::hours(cmp::min(self.max_tx_age_hours, max_hours) as i64)
+	}
 }
 
 impl Default for PoolConfig {
@@ -134,6 +160,9 @@ impl Default for PoolConfig {
 			reorg_cache_timeout: default_reorg_cache_timeout(),
 			max_stempool_size: default_max_stempool_size(),
 			mineable_max_weight: default_mineable_max_weight(),
+			max_tx_age_hours: default_max_tx_age_hours(),
+			accept_non_standard: default_accept_non_standard(),
+			max_tx_per_minute: default_max_tx_per_minute(),
 		}
 	}
 }
@@ -153,6 +182,15 @@ fn default_max_stempool_size() -> usize {
 fn default_mineable_max_weight() -> u64 {
 	consensus::MAX_BLOCK_WEIGHT
 }
+fn default_max_tx_age_hours() -> u64 {
+	24
+}
+fn default_accept_non_standard() -> bool {
+	false
+}
+fn default_max_tx_per_minute() -> usize {
+	120
+}
 
 /// Represents a single entry in the pool.
 /// A single (possibly aggregated) transaction.
@@ -176,6 +214,17 @@ impl PoolEntry {
 	}
 }
 
+/// Fee rates of the transactions currently in the pool, bucketed for
+/// wallet fee estimation.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct FeeHistogram {
+	/// Logarithmically spaced buckets from the lowest to the highest observed
+	/// fee rate, as (lower bound of the bucket fee rate, number of txs).
+	pub buckets: Vec<(u64, u64)>,
+	/// Fee rate at the 80th percentile of pending transactions.
+	pub recommended_fee_rate: u64,
+}
+
 /// Used to make decisions based on transaction acceptance priority from
 /// various sources. For example, a node may want to bypass pool size
 /// restrictions when accepting a transaction from a local wallet.
@@ -232,6 +281,9 @@ pub enum PoolError {
 	/// Transaction fee is too low given its weight
 	#[fail(display = "Tx Pool Low fee transaction {}", _0)]
 	LowFeeTransaction(u64),
+	/// Transaction doesn't follow the standard policy
+	#[fail(display = "Tx Pool Non standard transaction")]
+	NonStandardTransaction,
 	/// Attempt to add a duplicate output to the pool.
 	#[fail(display = "Tx Pool Duplicate output ID")]
 	DuplicateOutputId,
@@ -241,6 +293,9 @@ pub enum PoolError {
 	/// Attempt to add a duplicate tx to the pool.
 	#[fail(display = "Tx Pool Duplicate tx")]
 	DuplicateTx,
+	/// The source of the tx submitted more than allowed over the last minute
+	#[fail(display = "Tx Pool Rate limited")]
+	RateLimited,
 	/// NRD kernels will not be accepted by the txpool/stempool pre-HF3.
 	#[fail(display = "NRD kernel pre-HF3")]
 	NRDKernelPreHF3,
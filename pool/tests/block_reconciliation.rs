@@ -111,6 +111,7 @@ fn test_transaction_pool_block_reconciliation() {
 	// First we add the above transactions to the pool.
 	// All should be accepted.
 	assert_eq!(pool.total_size(), 0);
+	assert_eq!(pool.txpool_version(), 0);
 
 	for tx in &txs_to_add {
 		pool.add_to_pool(test_source(), tx.clone(), false, &header)
@@ -118,6 +119,8 @@ fn test_transaction_pool_block_reconciliation() {
 	}
 
 	assert_eq!(pool.total_size(), txs_to_add.len());
+	let txpool_version = pool.txpool_version();
+	assert_ne!(txpool_version, 0);
 
 	// Now we prepare the block that will cause the above conditions to be met.
 	// First, the transactions we want in the block:
@@ -136,11 +139,13 @@ fn test_transaction_pool_block_reconciliation() {
 
 	// Check the pool still contains everything we expect at this point.
 	assert_eq!(pool.total_size(), txs_to_add.len());
+	assert_eq!(pool.txpool_version(), txpool_version);
 
 	// And reconcile the pool with this latest block.
 	pool.reconcile_block(&block).unwrap();
 
 	assert_eq!(pool.total_size(), 4);
+	assert_ne!(pool.txpool_version(), txpool_version);
 	// Compare the various txs by their kernels as entries in the pool are "v2" compatibility.
 	assert_eq!(
 		pool.txpool.entries[0].tx.kernels(),
@@ -184,6 +184,9 @@ where
 			max_pool_size: 50,
 			max_stempool_size: 50,
 			mineable_max_weight: 10_000,
+			max_tx_age_hours: 24,
+			accept_non_standard: false,
+			max_tx_per_minute: 0,
 		},
 		chain.clone(),
 		verifier_cache.clone(),
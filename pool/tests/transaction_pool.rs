@@ -93,6 +93,14 @@ fn test_the_transaction_pool() {
 		assert_eq!(pool.total_size(), 3);
 	}
 
+	// Check the fee histogram accounts for every tx in the txpool.
+	{
+		let histogram = pool.fee_histogram();
+		assert_eq!(histogram.buckets.len(), 10);
+		assert_eq!(histogram.buckets.iter().map(|(_, n)| n).sum::<u64>(), 3);
+		assert!(histogram.recommended_fee_rate >= histogram.buckets[0].0);
+	}
+
 	// Test adding the exact same tx multiple times (same kernel signature).
 	// This will fail for stem=false during tx aggregation due to duplicate
 	// outputs and duplicate kernels.
@@ -0,0 +1,80 @@
+// Copyright 2020 The Grin Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod common;
+
+use self::core::core::verifier_cache::LruVerifierCache;
+use self::core::global;
+use self::keychain::{ExtKeychain, Keychain};
+use self::pool::types::PoolError;
+use self::util::RwLock;
+use crate::common::*;
+use grin_core as core;
+use grin_keychain as keychain;
+use grin_pool as pool;
+use grin_util as util;
+use std::sync::Arc;
+
+/// Test a non standard (zero fee) tx is rejected by default and only
+/// accepted once accept_non_standard is set.
+#[test]
+fn test_non_standard_tx() {
+	util::init_test_logger();
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	let keychain: ExtKeychain = Keychain::from_random_seed(false).unwrap();
+
+	let db_root = "target/.tx_non_standard";
+	clean_output_dir(db_root.into());
+
+	let genesis = genesis_block(&keychain);
+	let chain = Arc::new(init_chain(db_root, genesis));
+	let verifier_cache = Arc::new(RwLock::new(LruVerifierCache::new()));
+
+	let mut pool = init_transaction_pool(
+		Arc::new(ChainAdapter {
+			chain: chain.clone(),
+		}),
+		verifier_cache,
+	);
+
+	add_some_blocks(&chain, 3, &keychain);
+	let header_1 = chain.get_header_by_height(1).unwrap();
+	let initial_tx = test_transaction_spending_coinbase(&keychain, &header_1, vec![100, 200]);
+	add_block(&chain, &[initial_tx], &keychain);
+	let header = chain.head_header().unwrap();
+
+	// A plain tx paying a fee is standard.
+	let tx = test_transaction(&keychain, vec![100], vec![90]);
+	assert!(tx.is_standard_with_fee_base(pool.config.accept_fee_base));
+	pool.add_to_pool(test_source(), tx, false, &header).unwrap();
+	assert_eq!(pool.total_size(), 1);
+
+	// A zero fee tx is not.
+	let zero_fee_tx = test_transaction(&keychain, vec![200], vec![200]);
+	assert!(!pool.config.accept_non_standard);
+	assert_eq!(
+		pool.add_to_pool(test_source(), zero_fee_tx.clone(), false, &header),
+		Err(PoolError::NonStandardTransaction)
+	);
+	assert_eq!(pool.total_size(), 1);
+
+	// Unless the pool is configured to accept non standard txs.
+	pool.config.accept_non_standard = true;
+	pool.add_to_pool(test_source(), zero_fee_tx, false, &header)
+		.unwrap();
+	assert_eq!(pool.total_size(), 2);
+
+	// Cleanup db directory
+	clean_output_dir(db_root.into());
+}
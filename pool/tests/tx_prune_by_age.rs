@@ -0,0 +1,97 @@
+// Copyright 2020 The Grin Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod common;
+
+use self::core::core::verifier_cache::LruVerifierCache;
+use self::core::global;
+use self::keychain::{ExtKeychain, Keychain};
+use self::pool::PoolConfig;
+use self::util::RwLock;
+use crate::common::*;
+use chrono::Duration;
+use grin_core as core;
+use grin_keychain as keychain;
+use grin_pool as pool;
+use grin_util as util;
+use std::sync::Arc;
+
+/// Test old txs (and the txs depending on them) are pruned from both pools
+/// while fresh ones are kept.
+#[test]
+fn test_prune_by_age() {
+	util::init_test_logger();
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	let keychain: ExtKeychain = Keychain::from_random_seed(false).unwrap();
+
+	let db_root = "target/.tx_prune_by_age";
+	clean_output_dir(db_root.into());
+
+	let genesis = genesis_block(&keychain);
+	let chain = Arc::new(init_chain(db_root, genesis));
+	let verifier_cache = Arc::new(RwLock::new(LruVerifierCache::new()));
+
+	let mut pool = init_transaction_pool(
+		Arc::new(ChainAdapter {
+			chain: chain.clone(),
+		}),
+		verifier_cache,
+	);
+
+	add_some_blocks(&chain, 3, &keychain);
+	let header_1 = chain.get_header_by_height(1).unwrap();
+	let initial_tx = test_transaction_spending_coinbase(&keychain, &header_1, vec![100, 200, 300]);
+	add_block(&chain, &[initial_tx], &keychain);
+	let header = chain.head_header().unwrap();
+
+	let old_tx = test_transaction(&keychain, vec![100], vec![90]);
+	let child_tx = test_transaction(&keychain, vec![90], vec![80]);
+	let fresh_tx = test_transaction(&keychain, vec![200], vec![190]);
+	let old_stem_tx = test_transaction(&keychain, vec![300], vec![290]);
+
+	pool.add_to_pool(test_source(), old_tx, false, &header)
+		.unwrap();
+	pool.add_to_pool(test_source(), child_tx, false, &header)
+		.unwrap();
+	pool.add_to_pool(test_source(), fresh_tx.clone(), false, &header)
+		.unwrap();
+	pool.add_to_pool(test_source(), old_stem_tx, true, &header)
+		.unwrap();
+	assert_eq!(pool.txpool.size(), 3);
+	assert_eq!(pool.stempool.size(), 1);
+
+	// Nothing is old enough yet.
+	assert_eq!(pool.prune_by_age(Duration::hours(24)), 0);
+
+	// A huge configured max age must not overflow.
+	let config = PoolConfig {
+		max_tx_age_hours: u64::MAX,
+		..PoolConfig::default()
+	};
+	assert_eq!(pool.prune_by_age(config.max_tx_age()), 0);
+
+	// Age the first tx in each pool, the child tx is still fresh itself.
+	let aged = Duration::hours(25);
+	pool.txpool.entries[0].tx_at = pool.txpool.entries[0].tx_at - aged;
+	pool.stempool.entries[0].tx_at = pool.stempool.entries[0].tx_at - aged;
+
+	// The child goes with its parent once the pool is reconciled.
+	assert_eq!(pool.prune_by_age(Duration::hours(24)), 3);
+	assert_eq!(pool.txpool.size(), 1);
+	assert_eq!(pool.txpool.entries[0].tx, fresh_tx);
+	assert!(pool.stempool.is_empty());
+
+	// Cleanup db directory
+	clean_output_dir(db_root.into());
+}
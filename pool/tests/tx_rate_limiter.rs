@@ -0,0 +1,83 @@
+// Copyright 2020 The Grin Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use self::pool::types::PoolError;
+use self::pool::TxRateLimiter;
+use chrono::prelude::*;
+use chrono::Duration;
+use grin_pool as pool;
+use std::net::{IpAddr, Ipv4Addr};
+
+#[test]
+fn test_tx_rate_limiter() {
+	let mut limiter = TxRateLimiter::new(3);
+	let peer = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+	let other_peer = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+	let start = Utc::now();
+
+	for secs in 0..3 {
+		assert!(limiter
+			.check_at(peer, start + Duration::seconds(secs))
+			.is_ok());
+	}
+	assert_eq!(
+		limiter.check_at(peer, start + Duration::seconds(30)),
+		Err(PoolError::RateLimited)
+	);
+
+	// other IPs have their own count
+	assert!(limiter
+		.check_at(other_peer, start + Duration::seconds(30))
+		.is_ok());
+
+	// the first tx leaves the window 60 seconds after it was submitted
+	assert_eq!(
+		limiter.check_at(peer, start + Duration::seconds(59)),
+		Err(PoolError::RateLimited)
+	);
+	assert!(limiter
+		.check_at(peer, start + Duration::seconds(60))
+		.is_ok());
+	assert_eq!(
+		limiter.check_at(peer, start + Duration::seconds(60)),
+		Err(PoolError::RateLimited)
+	);
+
+	// a full window later the count is reset
+	let later = start + Duration::seconds(121);
+	for secs in 0..3 {
+		assert!(limiter
+			.check_at(peer, later + Duration::seconds(secs))
+			.is_ok());
+	}
+}
+
+#[test]
+fn test_tx_rate_limiter_bypass() {
+	let localhost = IpAddr::V4(Ipv4Addr::LOCALHOST);
+	let now = Utc::now();
+
+	// our own API pushes txs from loopback, never limited
+	let mut limiter = TxRateLimiter::new(1);
+	for _ in 0..10 {
+		assert!(limiter.check_at(localhost, now).is_ok());
+	}
+
+	// 0 disables the limit
+	let mut limiter = TxRateLimiter::new(0);
+	let peer = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+	for _ in 0..10 {
+		assert!(limiter.check_at(peer, now).is_ok());
+	}
+}
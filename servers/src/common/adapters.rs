@@ -15,6 +15,7 @@
 //! Adapters connecting new block, new transaction, and accepted transaction
 //! events to consumers of those events.
 
+use crate::api;
 use crate::util::RwLock;
 use std::fs::File;
 use std::path::PathBuf;
@@ -152,6 +153,7 @@ where
 	fn transaction_received(
 		&self,
 		tx: core::Transaction,
+		peer_info: &PeerInfo,
 		stem: bool,
 	) -> Result<bool, chain::Error> {
 		if self.sync_state.is_syncing() {
@@ -178,6 +180,13 @@ where
 		}
 
 		let mut tx_pool = self.tx_pool.write();
+		// onion peers can't be told apart by IP, only the IP ones are limited
+		if let p2p::PeerAddr::Ip(addr) = peer_info.addr {
+			if let Err(e) = tx_pool.rate_limiter.check(addr.ip()) {
+				debug!("Transaction {} from {} rejected: {:?}", tx_hash, addr, e);
+				return Ok(false);
+			}
+		}
 		match tx_pool.add_to_pool(source, tx, stem, &header) {
 			Ok(_) => {
 				self.processed_transactions.contains(&tx_hash, true);
@@ -244,7 +253,7 @@ where
 		let cb_hash = cb.hash();
 		if cb.kern_ids().is_empty() {
 			// push the freshly hydrated block through the chain pipeline
-			match core::Block::hydrate_from(cb, &[]) {
+			match cb.expand(&[]) {
 				Ok(block) => {
 					debug!(
 						"successfully hydrated (empty) block: {} at {} ({})",
@@ -292,7 +301,7 @@ where
 				return Ok(true);
 			}
 
-			let block = match core::Block::hydrate_from(cb.clone(), &txs) {
+			let block = match cb.expand(&txs) {
 				Ok(block) => {
 					if !self.sync_state.is_syncing() {
 						for hook in &self.hooks {
@@ -545,6 +554,17 @@ where
 				Ok(true)
 			}
 			Err(e) => {
+				if let chain::ErrorKind::HeaderChainDiscontinuity { at_height } = e.kind() {
+					// A hole in the headers we got, not a reason to drop the sync. Forget
+					// what we cached from there on so the headers get requested again.
+					warn!(
+						"Block headers discontinuity at height {}, requesting them again",
+						at_height
+					);
+					hashmap.retain(|height, _| *height < at_height);
+					*(self.reset_tip.lock().unwrap()) = at_height.saturating_sub(1);
+					return Ok(true);
+				}
 				debug!("Block headers refused by chain: {:?}", e);
 				if e.is_bad_data() {
 					Ok(false)
@@ -866,10 +886,15 @@ where
 			return;
 		}
 
-		// Roll the dice to trigger compaction at 1/COMPACTION_CHECK chance per block,
+		// Roll the dice to trigger compaction at 1/compaction_check_interval chance per block,
 		// uses a different thread to avoid blocking the caller thread (likely a peer)
+		let interval = self
+			.config
+			.compaction_check_interval
+			.unwrap_or(global::COMPACTION_CHECK)
+			.max(1);
 		let mut rng = thread_rng();
-		if 0 == rng.gen_range(0, global::COMPACTION_CHECK) {
+		if 0 == rng.gen_range(0, interval) {
 			let chain = self.chain();
 			let _ = thread::Builder::new()
 				.name("compactor".to_string())
@@ -878,6 +903,14 @@ where
 						error!("Could not compact chain: {:?}", e);
 					}
 				});
+
+			// Prune stale txs from the pool while we are at it.
+			let mut tx_pool = self.tx_pool.write();
+			let max_age = tx_pool.config.max_tx_age();
+			let pruned = tx_pool.prune_by_age(max_age);
+			if pruned > 0 {
+				info!("check_compact: pruned {} stale txs from the pool", pruned);
+			}
 		}
 	}
 
@@ -957,7 +990,9 @@ where
 {
 	tx_pool: Arc<RwLock<pool::TransactionPool<B, P, V>>>,
 	peers: OneTime<Weak<p2p::Peers>>,
+	chain: OneTime<Weak<chain::Chain>>,
 	hooks: Vec<Box<dyn ChainEvents + Send + Sync>>,
+	events: Arc<api::EventStream>,
 }
 
 impl<B, P, V> ChainAdapter for ChainToPoolAndNetAdapter<B, P, V>
@@ -967,6 +1002,12 @@ where
 	V: VerifierCache + 'static,
 {
 	fn block_accepted(&self, b: &core::Block, status: BlockStatus, opts: Options) {
+		let chain = self.chain();
+		let get_header = |h: &Hash| chain.as_ref().and_then(|c| c.get_block_header(h).ok());
+		for event in block_events(b, status, get_header) {
+			self.events.publish(event);
+		}
+
 		// not broadcasting blocks received through sync
 		if !opts.contains(chain::Options::SYNC) {
 			for hook in &self.hooks {
@@ -975,13 +1016,22 @@ where
 			// If we mined the block then we want to broadcast the compact block.
 			// If we received the block from another node then broadcast "header first"
 			// to minimize network traffic.
-			if opts.contains(Options::MINE) {
+			let relayed_to = if opts.contains(Options::MINE) {
 				// propagate compact block out if we mined the block
 				let cb: CompactBlock = b.clone().into();
-				self.peers().broadcast_compact_block(&cb);
+				self.peers().broadcast_compact_block(&cb)
 			} else {
 				// "header first" propagation if we are not the originator of this block
-				self.peers().broadcast_header(&b.header);
+				self.peers().broadcast_header(&b.header)
+			};
+			if let Some(chain) = self.chain() {
+				if let Err(e) = chain.record_block_relay(&b.hash(), relayed_to as u64) {
+					warn!(
+						"block_accepted: failed to record relay of {}: {}",
+						b.hash(),
+						e
+					);
+				}
 			}
 		}
 
@@ -1015,11 +1065,14 @@ where
 	pub fn new(
 		tx_pool: Arc<RwLock<pool::TransactionPool<B, P, V>>>,
 		hooks: Vec<Box<dyn ChainEvents + Send + Sync>>,
+		events: Arc<api::EventStream>,
 	) -> Self {
 		ChainToPoolAndNetAdapter {
 			tx_pool,
 			peers: OneTime::new(),
+			chain: OneTime::new(),
 			hooks: hooks,
+			events,
 		}
 	}
 
@@ -1029,12 +1082,61 @@ where
 		self.peers.init(Arc::downgrade(&peers));
 	}
 
+	/// Set the chain used to record block relays. Should only be called once.
+	pub fn set_chain(&self, chain_ref: Arc<chain::Chain>) {
+		self.chain.init(Arc::downgrade(&chain_ref));
+	}
+
 	fn peers(&self) -> Arc<p2p::Peers> {
 		self.peers
 			.borrow()
 			.upgrade()
 			.expect("Failed to upgrade weak ref to our peers.")
 	}
+
+	fn chain(&self) -> Option<Arc<chain::Chain>> {
+		if self.chain.is_init() {
+			self.chain.borrow().upgrade()
+		} else {
+			None
+		}
+	}
+}
+
+/// Events published to the API event stream for a block accepted by the chain.
+/// On a reorg the blocks of the former main chain are reverted, highest first,
+/// then the blocks of the new main chain are accepted, lowest first.
+/// A block accepted on a fork doesn't change the main chain, no events for it.
+fn block_events<F>(b: &core::Block, status: BlockStatus, get_header: F) -> Vec<api::NodeEvent>
+where
+	F: Fn(&Hash) -> Option<BlockHeader>,
+{
+	let mut events = vec![];
+	match status {
+		BlockStatus::Next { .. } => {}
+		BlockStatus::Fork { .. } => return events,
+		BlockStatus::Reorg {
+			prev,
+			prev_head,
+			fork_point,
+		} => {
+			let above_fork = |h: &BlockHeader| h.height > fork_point.height;
+			let mut hash = prev_head.last_block_h;
+			while let Some(header) = get_header(&hash).filter(above_fork) {
+				events.push(api::NodeEvent::block_reverted(&header));
+				hash = header.prev_hash;
+			}
+			let mut accepted = vec![];
+			let mut hash = prev.last_block_h;
+			while let Some(header) = get_header(&hash).filter(above_fork) {
+				accepted.push(api::NodeEvent::block_accepted(&header));
+				hash = header.prev_hash;
+			}
+			events.extend(accepted.into_iter().rev());
+		}
+	}
+	events.push(api::NodeEvent::block_accepted(&b.header));
+	events
 }
 
 /// Adapter between the transaction pool and the network, to relay
@@ -1042,6 +1144,7 @@ where
 pub struct PoolToNetAdapter {
 	peers: OneTime<Weak<p2p::Peers>>,
 	dandelion_epoch: Arc<RwLock<DandelionEpoch>>,
+	events: Arc<api::EventStream>,
 }
 
 /// Adapter between the Dandelion monitor and the current Dandelion "epoch".
@@ -1073,6 +1176,7 @@ impl DandelionAdapter for PoolToNetAdapter {
 impl pool::PoolAdapter for PoolToNetAdapter {
 	fn tx_accepted(&self, entry: &pool::PoolEntry) {
 		self.peers().broadcast_transaction(&entry.tx);
+		self.events.publish(api::NodeEvent::tx_accepted(&entry.tx));
 	}
 
 	fn stem_tx_accepted(&self, entry: &pool::PoolEntry) -> Result<(), pool::PoolError> {
@@ -1109,10 +1213,11 @@ impl pool::PoolAdapter for PoolToNetAdapter {
 
 impl PoolToNetAdapter {
 	/// Create a new pool to net adapter
-	pub fn new(config: pool::DandelionConfig) -> PoolToNetAdapter {
+	pub fn new(config: pool::DandelionConfig, events: Arc<api::EventStream>) -> PoolToNetAdapter {
 		PoolToNetAdapter {
 			peers: OneTime::new(),
 			dandelion_epoch: Arc::new(RwLock::new(DandelionEpoch::new(config))),
+			events,
 		}
 	}
 
@@ -1178,6 +1283,10 @@ impl pool::BlockChain for PoolToChainAdapter {
 	}
 
 	fn validate_tx(&self, tx: &Transaction) -> Result<(), pool::PoolError> {
+		// Keep the pool empty on a read-only node, its txs could never be mined.
+		if self.chain().is_readonly() {
+			return Err(pool::PoolError::Other("node is read-only".to_owned()));
+		}
 		self.chain()
 			.validate_tx(tx)
 			.map_err(|e| pool::PoolError::Other(format!("failed to validate tx, {}", e)))
@@ -1240,4 +1349,60 @@ mod test {
 		assert_eq!(cache.contains(&hash2_2, true), true);
 		assert_eq!(cache.contains(&hash3, true), false);
 	}
+
+	fn next_header(prev: &BlockHeader, nonce: u64) -> BlockHeader {
+		let mut header = BlockHeader::default();
+		header.height = prev.height + 1;
+		header.prev_hash = prev.hash();
+		header.pow.nonce = nonce;
+		header
+	}
+
+	#[test]
+	fn test_block_events_reorg() {
+		// main chain genesis <- a1 <- a2 <- a3, fork a1 <- b2 <- b3 <- b4
+		let genesis = BlockHeader::default();
+		let a1 = next_header(&genesis, 1);
+		let a2 = next_header(&a1, 1);
+		let a3 = next_header(&a2, 1);
+		let b2 = next_header(&a1, 2);
+		let b3 = next_header(&b2, 2);
+		let b4 = next_header(&b3, 2);
+		let headers: HashMap<Hash, BlockHeader> = vec![&genesis, &a1, &a2, &a3, &b2, &b3, &b4]
+			.into_iter()
+			.map(|h| (h.hash(), h.clone()))
+			.collect();
+		let get_header = |h: &Hash| headers.get(h).cloned();
+
+		let status = BlockStatus::Next {
+			prev: chain::Tip::from_header(&a2),
+		};
+		let events = block_events(&core::Block::with_header(a3.clone()), status, get_header);
+		assert_eq!(events, vec![api::NodeEvent::block_accepted(&a3)]);
+
+		let status = BlockStatus::Fork {
+			prev: chain::Tip::from_header(&b2),
+			head: chain::Tip::from_header(&a3),
+			fork_point: chain::Tip::from_header(&a1),
+		};
+		let events = block_events(&core::Block::with_header(b3.clone()), status, get_header);
+		assert!(events.is_empty());
+
+		let status = BlockStatus::Reorg {
+			prev: chain::Tip::from_header(&b3),
+			prev_head: chain::Tip::from_header(&a3),
+			fork_point: chain::Tip::from_header(&a1),
+		};
+		let events = block_events(&core::Block::with_header(b4.clone()), status, get_header);
+		assert_eq!(
+			events,
+			vec![
+				api::NodeEvent::block_reverted(&a3),
+				api::NodeEvent::block_reverted(&a2),
+				api::NodeEvent::block_accepted(&b2),
+				api::NodeEvent::block_accepted(&b3),
+				api::NodeEvent::block_accepted(&b4),
+			]
+		);
+	}
 }
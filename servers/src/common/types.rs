@@ -207,6 +207,10 @@ pub struct ServerConfig {
 	/// Whether this node is a full archival node or a fast-sync, pruned node
 	pub archive_mode: Option<bool>,
 
+	/// Number of blocks between two compactions of the chain, on average.
+	/// (Default: COMPACTION_CHECK, a day)
+	pub compaction_check_interval: Option<u64>,
+
 	/// Whether to skip the sync timeout on startup
 	/// (To assist testing on solo chains)
 	pub skip_sync_wait: Option<bool>,
@@ -234,6 +238,11 @@ pub struct ServerConfig {
 	/// Whether to run the test miner (internal, cuckoo 16)
 	pub run_test_miner: Option<bool>,
 
+	/// Run with all write paths disabled (--readonly). Command line only,
+	/// never read from or written to the config file.
+	#[serde(skip)]
+	pub readonly: bool,
+
 	/// Test miner wallet URL
 	pub test_miner_wallet_url: Option<String>,
 
@@ -270,6 +279,14 @@ pub struct ServerConfig {
 	/// Tor Configuration
 	#[serde(default)]
 	pub tor_config: TorConfig,
+
+	/// REST API configuration
+	#[serde(default)]
+	pub api_config: api::ApiConfig,
+
+	/// Node administration (API) configuration
+	#[serde(default)]
+	pub admin_config: api::AdminConfig,
 }
 
 impl Default for ServerConfig {
@@ -286,6 +303,7 @@ impl Default for ServerConfig {
 			stratum_mining_config: Some(StratumServerConfig::default()),
 			chain_type: ChainTypes::default(),
 			archive_mode: Some(false),
+			compaction_check_interval: None,
 			chain_validation_mode: ChainValidationMode::default(),
 			pool_config: pool::PoolConfig::default(),
 			skip_sync_wait: Some(false),
@@ -295,12 +313,15 @@ impl Default for ServerConfig {
 			duration_sync_long: Some(50),
 			run_tui: Some(true),
 			run_test_miner: Some(false),
+			readonly: false,
 			test_miner_wallet_url: None,
 			libp2p_enabled: Some(true),
 			libp2p_port: Some(3417),
 			libp2p_topics: None,
 			webhook_config: WebHooksConfig::default(),
 			tor_config: TorConfig::default(),
+			api_config: api::ApiConfig::default(),
+			admin_config: api::AdminConfig::default(),
 		}
 	}
 }
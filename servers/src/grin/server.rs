@@ -52,6 +52,7 @@ use crate::common::stats::{
 use crate::common::types::{Error, ServerConfig, StratumServerConfig};
 use crate::core::core::hash::Hashed;
 use crate::core::core::verifier_cache::LruVerifierCache;
+use crate::core::core::Block;
 use crate::core::ser::ProtocolVersion;
 use crate::core::stratum::connections;
 use crate::core::{consensus, genesis, global, pow};
@@ -135,6 +136,18 @@ impl Server {
 
 		let mining_config = config.stratum_mining_config.clone();
 		let enable_test_miner = config.run_test_miner;
+
+		if config.readonly {
+			let stratum = mining_config
+				.as_ref()
+				.and_then(|c| c.enable_stratum_server)
+				.unwrap_or(false);
+			if stratum || enable_test_miner.unwrap_or(false) {
+				return Err(Error::Configuration(
+					"Mining can't be enabled in read-only mode".to_owned(),
+				));
+			}
+		}
 		let test_miner_wallet_url = config.test_miner_wallet_url.clone();
 
 		let (ban_action_limit, shares_weight, connection_pace_ms) = match mining_config.clone() {
@@ -186,16 +199,24 @@ impl Server {
 	// Exclusive (advisory) lock_file to ensure we do not run multiple
 	// instance of grin server from the same dir.
 	// This uses fs2 and should be safe cross-platform unless somebody abuses the file itself.
+	// A read-only node only takes a shared lock, so several of them can watch the
+	// same dir, but never while a regular node is running on it.
 	fn one_grin_at_a_time(config: &ServerConfig) -> Result<Arc<File>, Error> {
 		let path = Path::new(&config.db_root);
-		fs::create_dir_all(&path)?;
+		if !config.readonly {
+			fs::create_dir_all(&path)?;
+		}
 		let path = path.join("mwc.lock");
 		let lock_file = fs::OpenOptions::new()
 			.read(true)
-			.write(true)
-			.create(true)
+			.write(!config.readonly)
+			.create(!config.readonly)
 			.open(&path)?;
-		lock_file.try_lock_exclusive().map_err(|e| {
+		let locked = match config.readonly {
+			true => lock_file.try_lock_shared(),
+			false => lock_file.try_lock_exclusive(),
+		};
+		locked.map_err(|e| {
 			let mut stderr = std::io::stderr();
 			writeln!(
 				&mut stderr,
@@ -208,6 +229,31 @@ impl Server {
 		Ok(Arc::new(lock_file))
 	}
 
+	fn genesis_block(chain_type: global::ChainTypes) -> Block {
+		match chain_type {
+			global::ChainTypes::AutomatedTesting => pow::mine_genesis_block().unwrap(),
+			global::ChainTypes::UserTesting => pow::mine_genesis_block().unwrap(),
+			global::ChainTypes::Floonet => genesis::genesis_floo(),
+			global::ChainTypes::Mainnet => genesis::genesis_main(),
+		}
+	}
+
+	/// Opens the chain in the configured db_root on its own, without the rest of the
+	/// server (p2p, pool, api...), for offline maintenance commands. The chain is
+	/// locked against running servers as long as the returned lock file is held.
+	pub fn open_chain(config: &ServerConfig) -> Result<(Arc<File>, Arc<chain::Chain>), Error> {
+		let lock_file = Server::one_grin_at_a_time(config)?;
+		let chain = chain::Chain::init(
+			config.db_root.clone(),
+			Arc::new(chain::types::NoopAdapter {}),
+			Server::genesis_block(config.chain_type),
+			pow::verify_size,
+			Arc::new(RwLock::new(LruVerifierCache::new())),
+			config.archive_mode.unwrap_or(false),
+		)?;
+		Ok((lock_file, Arc::new(chain)))
+	}
+
 	// We don't want allow_to_stop in config because it is too dangerous flag. We don't
 	// want to forget about that, make default e.t.c. That is why it is separated
 
@@ -249,8 +295,14 @@ impl Server {
 		// We cache rangeproof verification and kernel signature verification.
 		let verifier_cache = Arc::new(RwLock::new(LruVerifierCache::new()));
 
+		// Node events pushed to the API event stream subscribers.
+		let events = Arc::new(api::EventStream::new(api::EVENT_STREAM_CAPACITY));
+
 		let pool_adapter = Arc::new(PoolToChainAdapter::new());
-		let pool_net_adapter = Arc::new(PoolToNetAdapter::new(config.dandelion_config.clone()));
+		let pool_net_adapter = Arc::new(PoolToNetAdapter::new(
+			config.dandelion_config.clone(),
+			events.clone(),
+		));
 		let tx_pool = Arc::new(RwLock::new(pool::TransactionPool::new(
 			config.pool_config.clone(),
 			pool_adapter.clone(),
@@ -263,27 +315,43 @@ impl Server {
 		let chain_adapter = Arc::new(ChainToPoolAndNetAdapter::new(
 			tx_pool.clone(),
 			init_chain_hooks(&config),
+			events.clone(),
 		));
 
-		let genesis = match config.chain_type {
-			global::ChainTypes::AutomatedTesting => pow::mine_genesis_block().unwrap(),
-			global::ChainTypes::UserTesting => pow::mine_genesis_block().unwrap(),
-			global::ChainTypes::Floonet => genesis::genesis_floo(),
-			global::ChainTypes::Mainnet => genesis::genesis_main(),
-		};
+		let genesis = Server::genesis_block(config.chain_type);
 
 		info!("Starting server, genesis block: {}", genesis.hash());
 
-		let shared_chain = Arc::new(chain::Chain::init(
-			config.db_root.clone(),
-			chain_adapter.clone(),
-			genesis.clone(),
-			pow::verify_size,
-			verifier_cache.clone(),
-			archive_mode,
-		)?);
+		let chain_init = match config.readonly {
+			true => {
+				warn!(
+					"Starting in read-only mode: chain data in {} won't be modified, \
+					 blocks and transactions are refused and peers aren't connected to",
+					config.db_root
+				);
+				chain::Chain::init_readonly
+			}
+			false => chain::Chain::init,
+		};
+		let shared_chain = Arc::new(
+			chain_init(
+				config.db_root.clone(),
+				chain_adapter.clone(),
+				genesis.clone(),
+				pow::verify_size,
+				verifier_cache.clone(),
+				archive_mode,
+			)
+			.map_err(|e| {
+				if let chain::ErrorKind::DatabaseMigrationRequired { .. } = e.kind() {
+					error!("{}. Chain data is in {}", e.kind(), config.db_root);
+				}
+				e
+			})?,
+		);
 
 		pool_adapter.set_chain(shared_chain.clone());
+		chain_adapter.set_chain(shared_chain.clone());
 
 		let net_adapter = Arc::new(NetToChainAdapter::new(
 			sync_state.clone(),
@@ -531,7 +599,7 @@ impl Server {
 
 		let mut connect_thread = None;
 
-		if config.p2p_config.seeding_type != p2p::Seeding::Programmatic {
+		if config.p2p_config.seeding_type != p2p::Seeding::Programmatic && !config.readonly {
 			let seeder = match config.p2p_config.seeding_type {
 				p2p::Seeding::None => {
 					warn!("No seed configured, will stay solo until connected to");
@@ -576,14 +644,16 @@ impl Server {
 			stop_state.clone(),
 		)?;
 
-		let p2p_inner = p2p_server.clone();
-		let _ = thread::Builder::new()
-			.name("p2p-server".to_string())
-			.spawn(move || {
-				if let Err(e) = p2p_inner.listen(header_cache_size) {
-					error!("P2P server failed with erorr: {:?}", e);
-				}
-			})?;
+		if !config.readonly {
+			let p2p_inner = p2p_server.clone();
+			let _ = thread::Builder::new()
+				.name("p2p-server".to_string())
+				.spawn(move || {
+					if let Err(e) = p2p_inner.listen(header_cache_size) {
+						error!("P2P server failed with erorr: {:?}", e);
+					}
+				})?;
+		}
 
 		info!("Starting rest apis at: {}", &config.api_http_addr);
 		let api_secret = get_first_line(config.api_secret_path.clone());
@@ -609,10 +679,13 @@ impl Server {
 			tx_pool.clone(),
 			p2p_server.peers.clone(),
 			sync_state.clone(),
+			events,
 			api_secret,
 			foreign_api_secret,
 			tls_conf,
 			allow_to_stop,
+			config.api_config.clone(),
+			config.admin_config.clone(),
 			stratum_ip_pool,
 			api_chan,
 			stop_state.clone(),
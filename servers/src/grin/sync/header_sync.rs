@@ -172,7 +172,9 @@ impl HeaderSync {
 		if let Ok(header_head) = self.chain.header_head() {
 			let difficulty = header_head.total_difficulty;
 
-			if let Some(peer) = self.peers.most_work_peer() {
+			// always the same peer while it's on top, no point in asking several
+			// peers for the same headers
+			if let Some(peer) = self.peers.get_top_difficulty_peer() {
 				if peer.info.total_difficulty() > difficulty {
 					return self.request_headers(peer);
 				}
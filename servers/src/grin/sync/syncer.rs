@@ -222,10 +222,8 @@ impl SyncRunner {
 	fn needs_syncing(&self) -> Result<(bool, u64), chain::Error> {
 		let local_diff = self.chain.head()?.total_difficulty;
 		let mut is_syncing = self.sync_state.is_syncing();
-		let peer = self.peers.most_work_peer();
-
-		let peer_info = if let Some(p) = peer {
-			p.info.clone()
+		let peer_info = if let Some(peer) = self.peers.get_top_difficulty_peer() {
+			peer.info.clone()
 		} else {
 			warn!("sync: no peers available, disabling sync");
 			return Ok((false, 0));
@@ -246,19 +244,13 @@ impl SyncRunner {
 			}
 		} else {
 			// sum the last 5 difficulties to give us the threshold
-			let threshold = {
-				let diff_iter = match self.chain.difficulty_iter() {
-					Ok(v) => v,
-					Err(e) => {
-						error!("failed to get difficulty iterator: {:?}", e);
-						// we handle 0 height in the caller
-						return Ok((false, 0));
-					}
-				};
-				diff_iter
-					.map(|x| x.difficulty)
-					.take(5)
-					.fold(Difficulty::zero(), |sum, val| sum + val)
+			let threshold = match self.chain.sync_difficulty_threshold() {
+				Ok(v) => v,
+				Err(e) => {
+					error!("failed to get difficulty iterator: {:?}", e);
+					// we handle 0 height in the caller
+					return Ok((false, 0));
+				}
 			};
 
 			let peer_diff = peer_info.total_difficulty();
@@ -14,6 +14,7 @@
 
 //! Mining + Mining server
 
+mod block_template_cache;
 mod mine_block;
 mod stratum_data;
 pub mod stratumserver;
@@ -0,0 +1,92 @@
+// Copyright 2020 The Grin Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cache of the last block built for mining. Rebuilding a block is only
+//! needed when the chain tip or the txpool changed.
+
+use crate::core::core::hash::Hash;
+use crate::core::core::Block;
+use crate::mining::mine_block::BlockFees;
+
+struct CachedTemplate {
+	tip_hash: Hash,
+	txpool_version: u64,
+	block: Block,
+	fees: BlockFees,
+}
+
+/// Last block template built, keyed by the chain tip it was built on and the
+/// txpool version its transactions were taken from.
+pub struct BlockTemplateCache {
+	template: Option<CachedTemplate>,
+}
+
+impl BlockTemplateCache {
+	/// New empty cache.
+	pub fn new() -> BlockTemplateCache {
+		BlockTemplateCache { template: None }
+	}
+
+	/// The cached template if it was built on this tip and txpool version.
+	pub fn get(&self, tip_hash: &Hash, txpool_version: u64) -> Option<(Block, BlockFees)> {
+		self.template
+			.as_ref()
+			.filter(|t| t.tip_hash == *tip_hash && t.txpool_version == txpool_version)
+			.map(|t| (t.block.clone(), t.fees.clone()))
+	}
+
+	/// Cache a freshly built template, replacing the previous one.
+	pub fn put(&mut self, tip_hash: Hash, txpool_version: u64, block: Block, fees: BlockFees) {
+		self.template = Some(CachedTemplate {
+			tip_hash,
+			txpool_version,
+			block,
+			fees,
+		});
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::core::core::hash::{Hashed, ZERO_HASH};
+
+	fn fees() -> BlockFees {
+		BlockFees {
+			fees: 0,
+			height: 1,
+			key_id: None,
+		}
+	}
+
+	#[test]
+	fn block_template_cache() {
+		let mut cache = BlockTemplateCache::new();
+		let tip = Hash::from_vec(&[1; 32]);
+		assert!(cache.get(&tip, 0).is_none());
+
+		let block = Block::default();
+		cache.put(tip, 0, block.clone(), fees());
+
+		// unchanged tip and txpool, same template
+		let (cached, _) = cache.get(&tip, 0).unwrap();
+		assert_eq!(cached.hash(), block.hash());
+		let (cached, _) = cache.get(&tip, 0).unwrap();
+		assert_eq!(cached.hash(), block.hash());
+
+		// a new tip or new txs need a new template
+		assert!(cache.get(&ZERO_HASH, 0).is_none());
+		assert!(cache.get(&tip, 1).is_none());
+	}
+}
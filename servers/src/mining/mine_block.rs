@@ -30,6 +30,7 @@ use crate::core::libtx::secp_ser;
 use crate::core::libtx::ProofBuilder;
 use crate::core::{consensus, core, global};
 use crate::keychain::{ExtKeychain, Identifier, Keychain};
+use crate::mining::block_template_cache::BlockTemplateCache;
 use crate::{ServerTxPool, ServerVerifierCache};
 
 /// Fees in block to use for coinbase amount calculation
@@ -125,6 +126,35 @@ pub fn get_block(
 	return result.unwrap();
 }
 
+// Same as get_block, unless the cache holds a block built on the current chain
+// head with the current txpool txs, in which case that block is returned.
+// The returned flag is true if the block was built by this call.
+pub fn get_block_cached(
+	cache: &mut BlockTemplateCache,
+	chain: &Arc<chain::Chain>,
+	tx_pool: &ServerTxPool,
+	verifier_cache: ServerVerifierCache,
+	key_id: Option<Identifier>,
+	wallet_listener_url: Option<String>,
+) -> (core::Block, BlockFees, bool) {
+	let txpool_version = tx_pool.read().txpool_version();
+	if let Ok(head) = chain.head() {
+		if let Some((block, block_fees)) = cache.get(&head.last_block_h, txpool_version) {
+			return (block, block_fees, false);
+		}
+	}
+
+	let (block, block_fees) =
+		get_block(chain, tx_pool, verifier_cache, key_id, wallet_listener_url);
+	cache.put(
+		block.header.prev_hash,
+		txpool_version,
+		block.clone(),
+		block_fees.clone(),
+	);
+	(block, block_fees, true)
+}
+
 /// Builds a new block with the chain head as previous and eligible
 /// transactions from the pool.
 fn build_block(
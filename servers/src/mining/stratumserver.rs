@@ -32,6 +32,7 @@ use std::sync::Arc;
 use std::time::Duration;
 use std::{cmp, thread};
 
+use super::block_template_cache::BlockTemplateCache;
 use super::stratum_data::WorkersList;
 use crate::chain::{self, SyncState};
 use crate::common::stats::StratumStats;
@@ -199,6 +200,26 @@ impl State {
 			minimum_share_difficulty: minimum_share_difficulty,
 		}
 	}
+
+	/// Adds a new version of the block to mine, a new tip starts a new list of
+	/// versions. A block that wasn't rebuilt (cached template) on the same tip
+	/// keeps the current job and its job id.
+	/// Returns true if there is a new job to send to the workers.
+	fn add_block_version(&mut self, block: Block, built: bool, new_tip: bool) -> bool {
+		if !built && !new_tip {
+			return false;
+		}
+		if new_tip {
+			self.current_block_versions.clear();
+		}
+		self.current_block_versions.push(block);
+		true
+	}
+
+	/// Job id of the current block version
+	fn current_job_id(&self) -> usize {
+		self.current_block_versions.len() - 1
+	}
 }
 
 struct Handler {
@@ -361,7 +382,7 @@ impl Handler {
 
 			(
 				state.current_block_versions.last().unwrap().header.clone(),
-				state.current_block_versions.len() - 1,
+				state.current_job_id(),
 				state.minimum_share_difficulty,
 			)
 		};
@@ -581,6 +602,7 @@ impl Handler {
 		let mut deadline: i64 = 0;
 		let mut head = self.chain.head().unwrap();
 		let mut current_hash = head.prev_block_h;
+		let mut template_cache = BlockTemplateCache::new();
 
 		let worker_checking_period = if self.config.worker_login_timeout_ms <= 0 {
 			1000
@@ -604,7 +626,7 @@ impl Handler {
 			if (current_hash != latest_hash || Utc::now().timestamp() >= deadline)
 				&& self.workers.count() > 0
 			{
-				{
+				let new_job = {
 					debug!("resend updated block");
 					let wallet_listener_url = if !config.burn_reward {
 						Some(config.wallet_listener_url.clone())
@@ -614,47 +636,50 @@ impl Handler {
 					// If this is a new block, clear the current_block version history
 					let clear_blocks = current_hash != latest_hash;
 
-					// Build the new block (version)
-					let (new_block, block_fees) = mine_block::get_block(
+					// Build the new block (version), a cached one keeps the current job
+					let (new_block, block_fees, built) = mine_block::get_block_cached(
+						&mut template_cache,
 						&self.chain,
 						tx_pool,
 						verifier_cache.clone(),
 						self.current_state.read().current_key_id.clone(),
 						wallet_listener_url,
 					);
+					let height = new_block.header.height;
+					let difficulty = new_block.header.total_difficulty() - head.total_difficulty;
+					let new_job = self.current_state.write().add_block_version(
+						new_block,
+						built,
+						clear_blocks,
+					);
+
+					if new_job {
+						{
+							let mut state = self.current_state.write();
 
-					{
-						let mut state = self.current_state.write();
+							state.current_difficulty = difficulty.to_num();
 
-						state.current_difficulty =
-							(new_block.header.total_difficulty() - head.total_difficulty).to_num();
+							state.current_key_id = block_fees.key_id();
 
-						state.current_key_id = block_fees.key_id();
+							current_hash = latest_hash;
+							// set the minimum acceptable share difficulty for this block
+							state.minimum_share_difficulty =
+								cmp::min(config.minimum_share_difficulty, state.current_difficulty);
+						}
 
-						current_hash = latest_hash;
-						// set the minimum acceptable share difficulty for this block
-						state.minimum_share_difficulty =
-							cmp::min(config.minimum_share_difficulty, state.current_difficulty);
+						self.workers.update_block_height(height);
+						let current_difficulty = self.current_state.read().current_difficulty;
+						self.workers.update_network_difficulty(current_difficulty);
 					}
 
 					// set a new deadline for rebuilding with fresh transactions
 					deadline = Utc::now().timestamp() + config.attempt_time_per_block as i64;
-
-					self.workers.update_block_height(new_block.header.height);
-					self.workers
-						.update_network_difficulty(self.current_state.read().current_difficulty);
-
-					{
-						let mut state = self.current_state.write();
-
-						if clear_blocks {
-							state.current_block_versions.clear();
-						}
-						state.current_block_versions.push(new_block);
-					}
-					// Send this job to all connected workers
+					new_job
+				};
+				// Send this job to all connected workers
+				if new_job {
+					self.broadcast_job();
 				}
-				self.broadcast_job();
 			}
 
 			// Check workers login statuses and do IP pool maintaince
@@ -984,6 +1009,25 @@ where
 mod tests {
 	use super::*;
 
+	/// A cached block template on the same tip keeps the job id, a rebuilt
+	/// one gets the next job id and a new tip starts over.
+	#[test]
+	fn test_job_id_reuse() {
+		let mut state = State::new(1);
+		let block = Block::default();
+		assert!(state.add_block_version(block.clone(), true, true));
+		assert_eq!(state.current_job_id(), 0);
+
+		assert!(!state.add_block_version(block.clone(), false, false));
+		assert_eq!(state.current_job_id(), 0);
+
+		assert!(state.add_block_version(block.clone(), true, false));
+		assert_eq!(state.current_job_id(), 1);
+
+		assert!(state.add_block_version(block, false, true));
+		assert_eq!(state.current_job_id(), 0);
+	}
+
 	/// Tests deserializing an `RpcRequest` given a String as the id.
 	#[test]
 	fn test_request_deserialize_str() {
@@ -98,7 +98,7 @@ impl Miner {
 
 		while head.hash() == *latest_hash && Utc::now().timestamp() < deadline {
 			let mut ctx = global::create_pow_context::<u32>(
-				head.height,
+				Some(b.header.version),
 				global::min_edge_bits(),
 				global::proofsize(),
 				10,
@@ -19,7 +19,6 @@ use clap::ArgMatches;
 
 use crate::api;
 use crate::config::GlobalConfig;
-use crate::p2p;
 use crate::servers::ServerConfig;
 use crate::util::file::get_first_line;
 use failure::Fail;
@@ -131,9 +130,7 @@ pub fn unban_peer(config: &ServerConfig, peer_addr: &SocketAddr, api_secret: Opt
 pub fn list_connected_peers(config: &ServerConfig, api_secret: Option<String>) {
 	let mut e = term::stdout().unwrap();
 	let url = format!("http://{}/v1/peers/connected", config.api_http_addr);
-	// let peers_info: Result<Vec<p2p::PeerInfoDisplay>, api::Error>;
-
-	let peers_info = api::client::get::<Vec<p2p::types::PeerInfoDisplay>>(url.as_str(), api_secret);
+	let peers_info = api::client::get::<Vec<api::PeerInfoPrintable>>(url.as_str(), api_secret);
 
 	match peers_info.map_err(|e| Error::API(url, e)) {
 		Ok(connected_peers) => {
@@ -141,11 +138,13 @@ pub fn list_connected_peers(config: &ServerConfig, api_secret: Option<String>) {
 				writeln!(e, "Peer {}:", index).unwrap();
 				writeln!(e, "Capabilities: {:?}", connected_peer.capabilities).unwrap();
 				writeln!(e, "User agent: {}", connected_peer.user_agent).unwrap();
-				writeln!(e, "Version: {:?}", connected_peer.version).unwrap();
+				writeln!(e, "Version: {}", connected_peer.version).unwrap();
 				writeln!(e, "Peer address: {}", connected_peer.addr).unwrap();
 				writeln!(e, "Height: {}", connected_peer.height).unwrap();
 				writeln!(e, "Total difficulty: {}", connected_peer.total_difficulty).unwrap();
 				writeln!(e, "Direction: {:?}", connected_peer.direction).unwrap();
+				writeln!(e, "Connected for: {}s", connected_peer.connected_secs).unwrap();
+				writeln!(e, "Last seen: {}s ago", connected_peer.last_seen_secs).unwrap();
 				println!();
 			}
 		}
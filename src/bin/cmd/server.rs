@@ -134,6 +134,7 @@ pub fn server_command(
 		}
 
 		allow_to_stop = a.is_present("allow_to_stop");
+		server_config.readonly = a.is_present("readonly");
 	}
 
 	if allow_to_stop {
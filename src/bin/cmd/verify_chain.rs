@@ -0,0 +1,124 @@
+// Copyright 2020 The Grin Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// Offline chain audit, revalidating the blocks of the local chain
+use clap::ArgMatches;
+
+use crate::config::GlobalConfig;
+use crate::core::core::hash::Hashed;
+use crate::servers::Server;
+
+/// Print a progress line every this many blocks
+const PROGRESS_INTERVAL: u64 = 1000;
+
+/// Revalidates every block of the local chain in the requested height range
+/// (kernels, outputs, rangeproofs and MMR roots) without starting the server.
+/// Invalid blocks are reported and skipped, exits with 1 if any was found.
+pub fn verify_chain_command(args: &ArgMatches<'_>, global_config: GlobalConfig) -> i32 {
+	let server_config = global_config.members.unwrap().server;
+
+	let (_lock_file, chain) = match Server::open_chain(&server_config) {
+		Ok(res) => res,
+		Err(e) => {
+			eprintln!(
+				"Unable to open the chain in {}, {}",
+				server_config.db_root, e
+			);
+			return 1;
+		}
+	};
+	let head = match chain.head() {
+		Ok(head) => head,
+		Err(e) => {
+			eprintln!("Unable to read the chain head, {}", e);
+			return 1;
+		}
+	};
+
+	// blocks below the tail are pruned, there is nothing to verify there
+	let min_from = chain.tail().map(|t| t.height.max(1)).unwrap_or(1);
+	let from = match args.value_of("from_height").map(|h| h.parse::<u64>()) {
+		None => min_from,
+		Some(Ok(h)) if h < min_from => {
+			eprintln!(
+				"Blocks below height {} are pruned, use --from-height {} or above",
+				min_from, min_from
+			);
+			return 1;
+		}
+		Some(Ok(h)) => h,
+		Some(Err(_)) => {
+			eprintln!("Invalid --from-height");
+			return 1;
+		}
+	};
+	let to = match args.value_of("to_height").map(|h| h.parse::<u64>()) {
+		None => head.height,
+		Some(Ok(h)) => h,
+		Some(Err(_)) => {
+			eprintln!("Invalid --to-height");
+			return 1;
+		}
+	};
+
+	println!(
+		"Verifying blocks {} to {} of {}",
+		from, to, server_config.db_root
+	);
+	let mut valid = 0u64;
+	let mut invalid = 0u64;
+	let res = chain.validate_blocks(from, to, |header, res| {
+		match res {
+			Ok(_) => valid += 1,
+			Err(e) => {
+				invalid += 1;
+				println!(
+					"Invalid block {} at height {}: {}",
+					header.hash(),
+					header.height,
+					e
+				);
+			}
+		}
+		if (header.height - from + 1) % PROGRESS_INTERVAL == 0 {
+			println!(
+				"Verified {} of {} blocks, at height {}",
+				header.height - from + 1,
+				to - from + 1,
+				header.height
+			);
+		}
+		true
+	});
+	let complete = match res {
+		Ok(last_height) if last_height < to => {
+			eprintln!(
+				"Chain verification stopped at height {}, the blocks after it can't be verified",
+				last_height
+			);
+			false
+		}
+		Ok(_) => true,
+		Err(e) => {
+			eprintln!("Chain verification aborted, {}", e);
+			false
+		}
+	};
+
+	println!("Valid blocks: {}, invalid blocks: {}", valid, invalid);
+	match (complete, invalid) {
+		(true, 0) => 0,
+		_ => 1,
+	}
+}
@@ -81,9 +81,20 @@ fn real_main() -> i32 {
 	let args = App::from_yaml(yml)
 		.version(built_info::PKG_VERSION)
 		.get_matches();
-	let node_config;
+	let mut node_config;
+
+	// An explicit --chain-type is set globally right away and overrides the config file
+	let chain_type_arg = args.value_of("chain_type").map(|name| {
+		name.parse::<global::ChainTypes>()
+			.unwrap_or_else(|e| panic!("Invalid --chain-type: {}", e))
+	});
+	if let Some(chain_type) = chain_type_arg {
+		global::init_global_chain_type(chain_type);
+	}
 
-	let chain_type = if args.is_present("floonet") {
+	let chain_type = if let Some(chain_type) = chain_type_arg {
+		chain_type
+	} else if args.is_present("floonet") {
 		global::ChainTypes::Floonet
 	} else if args.is_present("usernet") {
 		global::ChainTypes::UserTesting
@@ -126,6 +137,12 @@ fn real_main() -> i32 {
 		}
 	}
 
+	if let Some(chain_type) = chain_type_arg {
+		if let Some(members) = node_config.as_mut().and_then(|c| c.members.as_mut()) {
+			members.server.chain_type = chain_type;
+		}
+	}
+
 	let mut config = node_config.clone().unwrap();
 	let mut logging_config = config.members.as_mut().unwrap().logging.clone().unwrap();
 	logging_config.tui_running = config.members.as_mut().unwrap().server.run_tui;
@@ -154,7 +171,9 @@ fn real_main() -> i32 {
 
 	// Initialize our global chain_type and feature flags (NRD kernel support currently).
 	// These are read via global and not read from config beyond this point.
-	global::init_global_chain_type(config.members.unwrap().server.chain_type);
+	if chain_type_arg.is_none() {
+		global::init_global_chain_type(config.members.unwrap().server.chain_type);
+	}
 	info!("Chain: {:?}", global::get_chain_type());
 	match global::get_chain_type() {
 		global::ChainTypes::Mainnet => {
@@ -178,6 +197,11 @@ fn real_main() -> i32 {
 		// client commands and options
 		("client", Some(client_args)) => cmd::client_command(client_args, node_config.unwrap()),
 
+		// offline chain audit
+		("verify-chain", Some(verify_args)) => {
+			cmd::verify_chain_command(verify_args, node_config.unwrap())
+		}
+
 		// clean command
 		("clean", _) => {
 			let db_root_path = node_config.unwrap().members.unwrap().server.db_root;
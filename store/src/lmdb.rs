@@ -53,6 +53,9 @@ pub enum Error {
 	/// Other error
 	#[fail(display = "Other Error")]
 	OtherErr(String),
+	/// Write attempted on a store opened read-only
+	#[fail(display = "DB is read-only, {}", _0)]
+	ReadOnly(String),
 }
 
 impl From<lmdb::error::Error> for Error {
@@ -81,8 +84,10 @@ pub struct Store {
 	env: Arc<lmdb::Environment>,
 	db: Arc<RwLock<Option<Arc<lmdb::Database<'static>>>>>,
 	name: String,
+	path: String,
 	version: ProtocolVersion,
 	alloc_chunk_size: usize,
+	readonly: bool,
 }
 
 impl Store {
@@ -95,6 +100,27 @@ impl Store {
 		env_name: Option<&str>,
 		db_name: Option<&str>,
 		max_readers: Option<u32>,
+	) -> Result<Store, Error> {
+		Store::open_env(root_path, env_name, db_name, max_readers, false)
+	}
+
+	/// Open an existing LMDB env under the provided directory without write
+	/// access. Nothing is created and every attempt to get a batch fails.
+	pub fn new_readonly(
+		root_path: &str,
+		env_name: Option<&str>,
+		db_name: Option<&str>,
+		max_readers: Option<u32>,
+	) -> Result<Store, Error> {
+		Store::open_env(root_path, env_name, db_name, max_readers, true)
+	}
+
+	fn open_env(
+		root_path: &str,
+		env_name: Option<&str>,
+		db_name: Option<&str>,
+		max_readers: Option<u32>,
+		readonly: bool,
 	) -> Result<Store, Error> {
 		let name = match env_name {
 			Some(n) => n.to_owned(),
@@ -105,12 +131,14 @@ impl Store {
 			None => "lmdb".to_owned(),
 		};
 		let full_path = [root_path.to_owned(), name].join("/");
-		fs::create_dir_all(&full_path).map_err(|e| {
-			Error::FileErr(format!(
-				"Unable to create directory 'db_root' to store chain_data: {:?}",
-				e
-			))
-		})?;
+		if !readonly {
+			fs::create_dir_all(&full_path).map_err(|e| {
+				Error::FileErr(format!(
+					"Unable to create directory 'db_root' to store chain_data: {:?}",
+					e
+				))
+			})?;
+		}
 
 		let mut env_builder = lmdb::EnvBuilder::new()?;
 		env_builder.set_maxdbs(8)?;
@@ -124,28 +152,43 @@ impl Store {
 			false => ALLOC_CHUNK_SIZE_DEFAULT_TEST,
 		};
 
-		let env = unsafe { env_builder.open(&full_path, lmdb::open::NOTLS, 0o600)? };
+		let flags = match readonly {
+			true => lmdb::open::NOTLS | lmdb::open::RDONLY,
+			false => lmdb::open::NOTLS,
+		};
+		let env = unsafe { env_builder.open(&full_path, flags, 0o600)? };
 
 		debug!("DB Mapsize for {} is {}", full_path, env.info()?.mapsize);
 		let res = Store {
 			env: Arc::new(env),
 			db: Arc::new(RwLock::new(None)),
 			name: db_name,
+			path: full_path,
 			version: DEFAULT_DB_VERSION,
 			alloc_chunk_size,
+			readonly,
 		};
 
-		{
-			let mut w = res.db.write();
-			*w = Some(Arc::new(lmdb::Database::open(
-				res.env.clone(),
-				Some(&res.name),
-				&lmdb::DatabaseOptions::new(lmdb::db::CREATE),
-			)?));
-		}
+		res.open()?;
 		Ok(res)
 	}
 
+	/// Total size (in bytes) of the files of the lmdb environment on disk.
+	pub fn disk_size(&self) -> Result<u64, Error> {
+		let mut size = 0;
+		let entries = fs::read_dir(&self.path)
+			.map_err(|e| Error::FileErr(format!("Unable to read {}, {}", self.path, e)))?;
+		for entry in entries {
+			let metadata = entry
+				.and_then(|entry| entry.metadata())
+				.map_err(|e| Error::FileErr(format!("Unable to read {}, {}", self.path, e)))?;
+			if metadata.is_file() {
+				size += metadata.len();
+			}
+		}
+		Ok(size)
+	}
+
 	/// Construct a new store using a specific protocol version.
 	/// Permits access to the db with legacy protocol versions for db migrations.
 	pub fn with_version(&self, version: ProtocolVersion) -> Store {
@@ -157,8 +200,10 @@ impl Store {
 			env: self.env.clone(),
 			db: self.db.clone(),
 			name: self.name.clone(),
+			path: self.path.clone(),
 			version,
 			alloc_chunk_size,
+			readonly: self.readonly,
 		}
 	}
 
@@ -167,13 +212,22 @@ impl Store {
 		self.version
 	}
 
+	/// Whether the store was opened without write access.
+	pub fn is_readonly(&self) -> bool {
+		self.readonly
+	}
+
 	/// Opens the database environment
 	pub fn open(&self) -> Result<(), Error> {
+		let options = match self.readonly {
+			true => lmdb::DatabaseOptions::defaults(),
+			false => lmdb::DatabaseOptions::new(lmdb::db::CREATE),
+		};
 		let mut w = self.db.write();
 		*w = Some(Arc::new(lmdb::Database::open(
 			self.env.clone(),
 			Some(&self.name),
-			&lmdb::DatabaseOptions::new(lmdb::db::CREATE),
+			&options,
 		)?));
 		Ok(())
 	}
@@ -325,6 +379,9 @@ impl Store {
 
 	/// Builds a new batch to be used with this store.
 	pub fn batch(&self) -> Result<Batch<'_>, Error> {
+		if self.readonly {
+			return Err(Error::ReadOnly(self.path.clone()));
+		}
 		// check if the db needs resizing before returning the batch
 		if self.needs_resize()? {
 			self.do_resize()?;
@@ -282,6 +282,43 @@ impl<T: PMMRable> PMMRBackend<T> {
 		})
 	}
 
+	/// Opens an existing PMMR backend in the provided dir without write access.
+	/// None of the files get created, nor rebuilt.
+	pub fn new_readonly<P: AsRef<Path>>(
+		data_dir: P,
+		prunable: bool,
+		version: ProtocolVersion,
+	) -> io::Result<PMMRBackend<T>> {
+		let data_dir = data_dir.as_ref();
+
+		let size_info = if let Some(fixed_size) = T::elmt_size() {
+			SizeInfo::FixedSize(fixed_size)
+		} else {
+			SizeInfo::VariableSize(Box::new(AppendOnlyFile::open_readonly(
+				data_dir.join(PMMR_SIZE_FILE),
+				SizeInfo::FixedSize(SizeEntry::LEN as u16),
+				version,
+			)?))
+		};
+		let hash_size_info = SizeInfo::FixedSize(Hash::LEN.try_into().unwrap());
+
+		let hash_file =
+			DataFile::open_readonly(&data_dir.join(PMMR_HASH_FILE), hash_size_info, version)?;
+		let data_file =
+			DataFile::open_readonly(&data_dir.join(PMMR_DATA_FILE), size_info, version)?;
+		let leaf_set = LeafSet::open(&data_dir.join(PMMR_LEAF_FILE))?;
+		let prune_list = PruneList::open(&data_dir.join(PMMR_PRUN_FILE))?;
+
+		Ok(PMMRBackend {
+			data_dir: data_dir.to_path_buf(),
+			prunable,
+			hash_file,
+			data_file,
+			leaf_set,
+			prune_list,
+		})
+	}
+
 	fn is_pruned(&self, pos: u64) -> bool {
 		self.prune_list.is_pruned(pos)
 	}
@@ -312,6 +349,18 @@ impl<T: PMMRable> PMMRBackend<T> {
 		self.hash_file.size()
 	}
 
+	/// Total size (in bytes) of the files of this backend on disk.
+	pub fn disk_size(&self) -> io::Result<u64> {
+		let mut size = 0;
+		for entry in fs::read_dir(&self.data_dir)? {
+			let metadata = entry?.metadata()?;
+			if metadata.is_file() {
+				size += metadata.len();
+			}
+		}
+		Ok(size)
+	}
+
 	/// Syncs all files to disk. A call to sync is required to ensure all the
 	/// data has been successfully written to disk.
 	pub fn sync(&mut self) -> io::Result<()> {
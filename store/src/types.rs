@@ -91,6 +91,20 @@ where
 		})
 	}
 
+	/// Open an existing file at the provided path on disk without write access.
+	pub fn open_readonly<P>(
+		path: P,
+		size_info: SizeInfo,
+		version: ProtocolVersion,
+	) -> io::Result<DataFile<T>>
+	where
+		P: AsRef<Path> + Debug,
+	{
+		Ok(DataFile {
+			file: AppendOnlyFile::open_readonly(path, size_info, version)?,
+		})
+	}
+
 	/// Append an element to the file.
 	/// Will not be written to disk until flush() is subsequently called.
 	/// Alternatively discard() may be called to discard any pending changes.
@@ -166,6 +180,7 @@ pub struct AppendOnlyFile<T> {
 	file: Option<File>,
 	size_info: SizeInfo,
 	version: ProtocolVersion,
+	readonly: bool,
 	mmap: Option<memmap::Mmap>,
 
 	// Buffer of unsync'd bytes. These bytes will be appended to the file when flushed.
@@ -196,6 +211,31 @@ where
 		size_info: SizeInfo,
 		version: ProtocolVersion,
 	) -> io::Result<AppendOnlyFile<T>>
+	where
+		P: AsRef<Path> + Debug,
+	{
+		AppendOnlyFile::open_with(path, size_info, version, false)
+	}
+
+	/// Open an existing file without write access, backed by a mmap.
+	/// Nothing is created and flushing fails.
+	pub fn open_readonly<P>(
+		path: P,
+		size_info: SizeInfo,
+		version: ProtocolVersion,
+	) -> io::Result<AppendOnlyFile<T>>
+	where
+		P: AsRef<Path> + Debug,
+	{
+		AppendOnlyFile::open_with(path, size_info, version, true)
+	}
+
+	fn open_with<P>(
+		path: P,
+		size_info: SizeInfo,
+		version: ProtocolVersion,
+		readonly: bool,
+	) -> io::Result<AppendOnlyFile<T>>
 	where
 		P: AsRef<Path> + Debug,
 	{
@@ -204,6 +244,7 @@ where
 			path: path.as_ref().to_path_buf(),
 			size_info,
 			version,
+			readonly,
 			mmap: None,
 			buffer: vec![],
 			buffer_start_pos: 0,
@@ -219,6 +260,12 @@ where
 		let expected_size = aof.size()?;
 		if let SizeInfo::VariableSize(ref mut size_file) = &mut aof.size_info {
 			if size_file.sum_sizes()? != expected_size {
+				if aof.readonly {
+					return Err(io::Error::new(
+						io::ErrorKind::PermissionDenied,
+						format!("size file inconsistent with {:?}, read-only", aof.path),
+					));
+				}
 				aof.rebuild_size_file()?;
 
 				// (Re)init the entire file as we just rebuilt the size_file
@@ -237,13 +284,15 @@ where
 			size_file.init()?;
 		}
 
-		self.file = Some(
+		self.file = Some(if self.readonly {
+			OpenOptions::new().read(true).open(self.path.clone())?
+		} else {
 			OpenOptions::new()
 				.read(true)
 				.append(true)
 				.create(true)
-				.open(self.path.clone())?,
-		);
+				.open(self.path.clone())?
+		});
 
 		// If we have a non-empty file then mmap it.
 		if self.size()? == 0 {
@@ -333,6 +382,12 @@ where
 	/// Syncs all writes (fsync), reallocating the memory map to make the newly
 	/// written data accessible.
 	pub fn flush(&mut self) -> io::Result<()> {
+		if self.readonly {
+			return Err(io::Error::new(
+				io::ErrorKind::PermissionDenied,
+				format!("can't flush {:?}, read-only", self.path),
+			));
+		}
 		if let SizeInfo::VariableSize(ref mut size_file) = &mut self.size_info {
 			// Flush the associated size_file if we have one.
 			size_file.flush()?
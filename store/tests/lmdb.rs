@@ -103,3 +103,30 @@ fn lmdb_allocate() -> Result<(), store::Error> {
 
 	Ok(())
 }
+
+#[test]
+fn lmdb_readonly() -> Result<(), store::Error> {
+	let test_dir = "test_output/lmdb_readonly";
+	setup(test_dir);
+	let key = store::to_key(b'P', "phat_chunk");
+	{
+		let store = store::Store::new(test_dir, Some("test1"), None, None)?;
+		let batch = store.batch()?;
+		batch.put_ser(&key, &PhatChunkStruct::new())?;
+		batch.commit()?;
+	}
+	{
+		let store = store::Store::new_readonly(test_dir, Some("test1"), None, None)?;
+		assert!(store.is_readonly());
+		assert!(store.get_ser::<PhatChunkStruct>(&key)?.is_some());
+		match store.batch() {
+			Err(store::Error::ReadOnly(_)) => {}
+			Err(e) => panic!("expected a read-only error, got {}", e),
+			Ok(_) => panic!("read-only store should not provide a batch"),
+		}
+	}
+	// nothing to open
+	assert!(store::Store::new_readonly("test_output/lmdb_missing", None, None, None).is_err());
+	clean_output_dir(test_dir);
+	Ok(())
+}
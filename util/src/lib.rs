@@ -97,6 +97,18 @@ where
 		*inner = Some(value);
 	}
 
+	/// Initializes the OneTime if it isn't yet, checked and set under the same
+	/// lock so concurrent callers can't both succeed. Returns true if the value
+	/// was set by this call, false (and the value dropped) if already initialized.
+	pub fn try_init(&self, value: T) -> bool {
+		let mut inner = self.inner.write();
+		if inner.is_some() {
+			return false;
+		}
+		*inner = Some(value);
+		true
+	}
+
 	/// Borrows the OneTime, should only be called after initialization.
 	/// Will panic (via expect) if called before initialization.
 	pub fn borrow(&self) -> T {
@@ -117,6 +129,11 @@ pub fn to_base64(s: &str) -> String {
 	base64::encode(s)
 }
 
+/// Encode bytes to a base64 string
+pub fn bytes_to_base64(b: &[u8]) -> String {
+	base64::encode(b)
+}
+
 /// Global stopped/paused state shared across various subcomponents of Grin.
 ///
 /// "Stopped" allows a clean shutdown of the Grin server.
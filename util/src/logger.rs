@@ -18,7 +18,7 @@ use std::ops::Deref;
 use backtrace::Backtrace;
 use std::{panic, thread};
 
-use log::{Level, Record};
+use log::{Level, LevelFilter, Record};
 use log4rs;
 use log4rs::append::console::ConsoleAppender;
 use log4rs::append::file::FileAppender;
@@ -29,6 +29,7 @@ use log4rs::append::rolling_file::{
 };
 use log4rs::append::Append;
 use log4rs::config::{Appender, Config, Root};
+use log4rs::encode::json::JsonEncoder;
 use log4rs::encode::pattern::PatternEncoder;
 use log4rs::encode::writer::simple::SimpleWriter;
 use log4rs::encode::Encode;
@@ -82,6 +83,11 @@ pub struct LoggingConfig {
 	pub log_max_files: Option<u32>,
 	/// Whether the tui is running (optional)
 	pub tui_running: Option<bool>,
+	/// Whether to log to stdout and file as JSON lines (optional)
+	pub log_json: Option<bool>,
+	/// Per module log levels, overriding the stdout and file levels for these
+	/// modules, i.e. "chain=debug,p2p=info,api=warn" (optional)
+	pub log_module_levels: Option<String>,
 }
 
 impl Default for LoggingConfig {
@@ -96,10 +102,40 @@ impl Default for LoggingConfig {
 			log_max_size: Some(1024 * 1024 * 16), // 16 megabytes default
 			log_max_files: Some(DEFAULT_ROTATE_LOG_FILES),
 			tui_running: None,
+			log_json: Some(false),
+			log_module_levels: None,
 		}
 	}
 }
 
+impl LoggingConfig {
+	/// Per module log levels, from log_module_levels. Invalid entries are skipped.
+	pub fn module_filters(&self) -> Vec<(String, LevelFilter)> {
+		match self.log_module_levels {
+			Some(ref levels) => parse_module_filters(levels),
+			None => vec![],
+		}
+	}
+}
+
+/// Parse "module=level" pairs, comma separated, i.e. "chain=debug,p2p=info".
+/// Module names can be given with or without the crate prefix ("chain" or
+/// "grin_chain"). Entries that can't be parsed are skipped.
+pub fn parse_module_filters(levels: &str) -> Vec<(String, LevelFilter)> {
+	levels
+		.split(',')
+		.filter_map(|entry| {
+			let mut parts = entry.splitn(2, '=');
+			let module = parts.next()?.trim();
+			let level = parts.next()?.trim().parse().ok()?;
+			if module.is_empty() {
+				return None;
+			}
+			Some((module.to_owned(), level))
+		})
+		.collect()
+}
+
 /// This filter is rejecting messages that doesn't start with "grin"
 /// in order to save log space for only Grin-related records
 #[derive(Debug)]
@@ -120,6 +156,52 @@ impl Filter for GrinFilter {
 	}
 }
 
+/// Applies the per module log levels. Records of a configured module are
+/// accepted or rejected on its level alone, the others are left to the
+/// appender's threshold. The most specific module matching wins.
+#[derive(Debug)]
+struct ModuleFilter {
+	filters: Vec<(String, LevelFilter)>,
+}
+
+impl ModuleFilter {
+	fn level(&self, module_path: &str) -> Option<LevelFilter> {
+		// "grin_chain::pipe" matches "chain" as well as "grin_chain"
+		let short_path = module_path
+			.trim_start_matches("grin_")
+			.trim_start_matches("mwc_");
+		self.filters
+			.iter()
+			.filter(|(module, _)| {
+				[module_path, short_path].iter().any(|path| {
+					path.starts_with(module.as_str())
+						&& (path.len() == module.len() || path[module.len()..].starts_with("::"))
+				})
+			})
+			.max_by_key(|(module, _)| module.len())
+			.map(|(_, level)| *level)
+	}
+}
+
+impl Filter for ModuleFilter {
+	fn filter(&self, record: &Record<'_>) -> Response {
+		match record.module_path().and_then(|path| self.level(path)) {
+			Some(level) if record.level() <= level => Response::Accept,
+			Some(_) => Response::Reject,
+			None => Response::Neutral,
+		}
+	}
+}
+
+/// Encoder for stdout and file records, JSON lines or the logging pattern
+fn encoder(json: bool) -> Box<dyn Encode> {
+	if json {
+		Box::new(JsonEncoder::new())
+	} else {
+		Box::new(PatternEncoder::new(&LOGGING_PATTERN))
+	}
+}
+
 #[derive(Debug)]
 struct ChannelAppender {
 	output: Mutex<SyncSender<LogEntry>>,
@@ -161,18 +243,19 @@ pub fn init_logger(config: Option<LoggingConfig>, logs_tx: Option<mpsc::SyncSend
 
 		let level_stdout = c.stdout_log_level.to_level_filter();
 		let level_file = c.file_log_level.to_level_filter();
+		let json = c.log_json.unwrap_or(false);
+		let module_filters = c.module_filters();
 
 		// Determine minimum logging level for Root logger
-		let level_minimum = if level_stdout > level_file {
-			level_stdout
-		} else {
-			level_file
-		};
+		let level_minimum = module_filters
+			.iter()
+			.map(|(_, level)| *level)
+			.chain(vec![level_stdout, level_file])
+			.max()
+			.unwrap_or(level_file);
 
 		// Start logger
-		let stdout = ConsoleAppender::builder()
-			.encoder(Box::new(PatternEncoder::new(&LOGGING_PATTERN)))
-			.build();
+		let stdout = ConsoleAppender::builder().encoder(encoder(json)).build();
 
 		let mut root = Root::builder();
 
@@ -194,6 +277,9 @@ pub fn init_logger(config: Option<LoggingConfig>, logs_tx: Option<mpsc::SyncSend
 		} else if c.log_to_stdout {
 			appenders.push(
 				Appender::builder()
+					.filter(Box::new(ModuleFilter {
+						filters: module_filters.clone(),
+					}))
 					.filter(Box::new(ThresholdFilter::new(level_stdout)))
 					.filter(Box::new(GrinFilter))
 					.build("stdout", Box::new(stdout)),
@@ -218,7 +304,7 @@ pub fn init_logger(config: Option<LoggingConfig>, logs_tx: Option<mpsc::SyncSend
 					Box::new(
 						RollingFileAppender::builder()
 							.append(c.log_file_append)
-							.encoder(Box::new(PatternEncoder::new(&LOGGING_PATTERN)))
+							.encoder(encoder(json))
 							.build(c.log_file_path, Box::new(policy))
 							.expect("Failed to create logfile"),
 					)
@@ -226,7 +312,7 @@ pub fn init_logger(config: Option<LoggingConfig>, logs_tx: Option<mpsc::SyncSend
 					Box::new(
 						FileAppender::builder()
 							.append(c.log_file_append)
-							.encoder(Box::new(PatternEncoder::new(&LOGGING_PATTERN)))
+							.encoder(encoder(json))
 							.build(c.log_file_path)
 							.expect("Failed to create logfile"),
 					)
@@ -235,6 +321,9 @@ pub fn init_logger(config: Option<LoggingConfig>, logs_tx: Option<mpsc::SyncSend
 
 			appenders.push(
 				Appender::builder()
+					.filter(Box::new(ModuleFilter {
+						filters: module_filters.clone(),
+					}))
 					.filter(filter)
 					.filter(Box::new(GrinFilter))
 					.build("file", file),
@@ -250,8 +339,8 @@ pub fn init_logger(config: Option<LoggingConfig>, logs_tx: Option<mpsc::SyncSend
 		let _ = log4rs::init_config(config).unwrap();
 
 		info!(
-			"log4rs is initialized, file level: {:?}, stdout level: {:?}, min. level: {:?}",
-			level_file, level_stdout, level_minimum
+			"log4rs is initialized, file level: {:?}, stdout level: {:?}, min. level: {:?}, module levels: {:?}",
+			level_file, level_stdout, level_minimum, module_filters
 		);
 
 		// Mark logger as initialized
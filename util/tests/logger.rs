@@ -0,0 +1,67 @@
+// Copyright 2020 The Grin Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grin_util as util;
+
+use crate::util::init_logger;
+use crate::util::logger::{parse_module_filters, LoggingConfig};
+use log::{debug, LevelFilter};
+use std::fs;
+
+mod quiet {
+	pub fn log() {
+		log::debug!("quiet module debug message");
+		log::warn!("quiet module warn message");
+	}
+}
+
+#[test]
+fn module_filters() {
+	assert_eq!(
+		parse_module_filters("chain=debug, p2p=Info,api,=warn,pool=loud"),
+		vec![
+			("chain".to_owned(), LevelFilter::Debug),
+			("p2p".to_owned(), LevelFilter::Info),
+		]
+	);
+	assert!(parse_module_filters("").is_empty());
+}
+
+#[test]
+fn json_logs_with_module_levels() {
+	let log_path = ".json_logs_with_module_levels.log";
+	let _ = fs::remove_file(log_path);
+
+	// the test modules are outside the grin crates, only logged through module levels
+	let config = LoggingConfig {
+		log_to_stdout: false,
+		log_file_path: log_path.to_owned(),
+		log_max_size: None,
+		log_json: Some(true),
+		log_module_levels: Some("logger=debug,logger::quiet=warn".to_owned()),
+		..LoggingConfig::default()
+	};
+	init_logger(Some(config), None);
+
+	debug!("main module debug message");
+	quiet::log();
+
+	let logs = fs::read_to_string(log_path).unwrap();
+	let lines: Vec<&str> = logs.lines().collect();
+	assert!(lines.iter().all(|l| l.starts_with('{') && l.ends_with('}')));
+	assert!(logs.contains("\"message\":\"main module debug message\""));
+	assert!(logs.contains("\"message\":\"quiet module warn message\""));
+	assert!(!logs.contains("quiet module debug message"));
+	let _ = fs::remove_file(log_path);
+}
@@ -0,0 +1,45 @@
+// Copyright 2020 The Grin Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use grin_util as util;
+
+use self::util::OneTime;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn one_time_try_init() {
+	let one_time = OneTime::new();
+	assert!(one_time.try_init(1));
+	assert!(!one_time.try_init(2));
+	assert_eq!(one_time.borrow(), 1);
+}
+
+#[test]
+fn one_time_try_init_concurrent() {
+	let one_time = Arc::new(OneTime::new());
+	let threads: Vec<_> = (0..8)
+		.map(|n| {
+			let one_time = one_time.clone();
+			thread::spawn(move || one_time.try_init(n))
+		})
+		.collect();
+	let initialized = threads
+		.into_iter()
+		.map(|t| t.join().unwrap())
+		.filter(|init| *init)
+		.count();
+	assert_eq!(initialized, 1);
+	assert!(one_time.is_init());
+}